@@ -0,0 +1,106 @@
+use crate::player_movement::Player;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const RELOAD_TIME: f32 = 1.5;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerReload;
+
+/// The balls a character has left to throw. Throws come out of the magazine, and reloading
+/// refills it from the reserve. Kept apart from the [`Inventory`](crate::inventory::Inventory),
+/// whose stacks have no magazine to load or reload time.
+#[derive(Component)]
+pub struct Ammo {
+    magazine: u32,
+    magazine_size: u32,
+    reserve: u32,
+    /// When the ongoing reload finishes, in elapsed seconds.
+    reload_done_at: Option<f32>,
+}
+
+impl Ammo {
+    /// A full magazine with `reserve` more balls to reload from.
+    pub fn new(magazine_size: u32, reserve: u32) -> Self {
+        Self {
+            magazine: magazine_size,
+            magazine_size,
+            reserve,
+            reload_done_at: None,
+        }
+    }
+
+    /// Whether there is a ball in the magazine and no reload in the way of throwing it.
+    pub fn can_fire(&self) -> bool {
+        self.magazine > 0 && self.reload_done_at.is_none()
+    }
+
+    /// Takes a ball out of the magazine. Check [`Self::can_fire`] first.
+    pub fn consume(&mut self) {
+        self.magazine = self.magazine.saturating_sub(1);
+    }
+
+    fn can_reload(&self) -> bool {
+        self.reload_done_at.is_none() && self.magazine < self.magazine_size && self.reserve > 0
+    }
+
+    pub fn magazine(&self) -> u32 {
+        self.magazine
+    }
+
+    pub fn reserve(&self) -> u32 {
+        self.reserve
+    }
+
+    /// Adds balls to the reserve, to be reloaded from.
+    pub fn add_reserve(&mut self, amount: u32) {
+        self.reserve += amount;
+    }
+
+    /// How far along the ongoing reload is at `now`, from 0 to 1, if there is one.
+    pub fn reload_progress(&self, now: f32) -> Option<f32> {
+        self.reload_done_at
+            .map(|done_at| (1.0 - (done_at - now) / RELOAD_TIME).clamp(0.0, 1.0))
+    }
+}
+
+pub struct AmmoPlugin;
+
+impl Plugin for AmmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, finish_reload)
+            .add_observer(start_reload);
+    }
+}
+
+fn start_reload(
+    trigger: Trigger<Started<PlayerReload>>,
+    time: Res<Time>,
+    mut query: Query<&mut Ammo, With<Player>>,
+) {
+    let Ok(mut ammo) = query.get_mut(trigger.target()) else {
+        return;
+    };
+
+    if ammo.can_reload() {
+        ammo.reload_done_at = Some(time.elapsed_secs() + RELOAD_TIME);
+    }
+}
+
+/// Tops up the magazine from the reserve once a reload is done.
+fn finish_reload(time: Res<Time>, mut query: Query<&mut Ammo>) {
+    for mut ammo in &mut query {
+        let Some(done_at) = ammo.reload_done_at else {
+            continue;
+        };
+        if time.elapsed_secs() < done_at {
+            continue;
+        }
+
+        let moved = (ammo.magazine_size - ammo.magazine).min(ammo.reserve);
+        ammo.magazine += moved;
+        ammo.reserve -= moved;
+        ammo.reload_done_at = None;
+    }
+}
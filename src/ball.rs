@@ -1,54 +1,373 @@
-use avian3d::prelude::*;
+use crate::combat::{Damage, DamageKind, Health};
+use crate::game_assets::{BALL_RADIUS, GameAssets};
+use crate::layers::GameLayer;
+use crate::penetration::Penetrating;
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
 use bevy::prelude::*;
-use chrono::{DateTime, Duration, Utc};
+
+const BALL_LIFETIME: f32 = 3.0;
+/// How many times a bouncy ball bounces before it goes away.
+const BOUNCY_BALL_BOUNCES: u32 = 5;
+/// Bouncy balls that come to rest before running out of bounces still go away after this long.
+const BOUNCY_BALL_LIFETIME: f32 = 15.0;
+const BOUNCY_BALL_RESTITUTION: Scalar = 0.9;
+/// Balls slower than this don't hurt what they hit.
+const BALL_DAMAGE_MIN_SPEED: Scalar = 10.0;
+const BALL_DAMAGE_PER_SPEED: f32 = 0.5;
+const HOMING_BALL_LIFETIME: f32 = 6.0;
+/// How far away homing balls pick up targets.
+const HOMING_RANGE: Scalar = 30.0;
+/// Targets further than this angle off a homing ball's heading are ignored.
+const HOMING_CONE_ANGLE: Scalar = 0.5;
+/// The fastest a homing ball turns, in radians per second, so it can be dodged.
+const HOMING_TURN_RATE: Scalar = 2.5;
+/// How many meters of cube a ball can punch through.
+const BALL_PENETRATION_POWER: f32 = 1.5;
+/// How long thrown projectiles pass through players, so they don't hit whoever threw them on
+/// their way out.
+const PASS_THROUGH_PLAYERS_TIME: f32 = 0.1;
 
 #[derive(Component)]
+#[require(CollisionEventsEnabled)]
 pub struct Ball;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BallKind {
+    Regular,
+    /// Ricochets off surfaces and goes away after [`BOUNCY_BALL_BOUNCES`] bounces instead of
+    /// after a fixed time.
+    Bouncy,
+    /// Flies straight, without gravity, and steers toward the nearest target ahead of it.
+    Homing,
+}
+
+impl BallKind {
+    fn restitution(self) -> Restitution {
+        match self {
+            Self::Regular | Self::Homing => Restitution::default(),
+            // Max, so the ball keeps bouncing off surfaces with no restitution of their own
+            Self::Bouncy => {
+                Restitution::new(BOUNCY_BALL_RESTITUTION).with_combine_rule(CoefficientCombine::Max)
+            }
+        }
+    }
+
+    fn lifetime(self) -> DespawnAfter {
+        match self {
+            Self::Regular => DespawnAfter::new(BALL_LIFETIME),
+            Self::Bouncy => DespawnAfter::new(BOUNCY_BALL_LIFETIME),
+            Self::Homing => DespawnAfter::new(HOMING_BALL_LIFETIME),
+        }
+    }
+
+    fn gravity_scale(self) -> GravityScale {
+        match self {
+            Self::Regular | Self::Bouncy => GravityScale(1.0),
+            Self::Homing => GravityScale(0.0),
+        }
+    }
+}
+
+/// Steers a ball toward the nearest target with [`Health`] ahead of it. Players aren't
+/// targeted, so balls don't turn back on whoever threw them.
+#[derive(Component)]
+pub struct Homing;
+
+/// The bounces a bouncy ball has left.
 #[derive(Component)]
-pub struct DespawnAfter(DateTime<Utc>);
+pub struct BouncesLeft(u32);
+
+/// Counts down in game time, so it stops while the game is paused. Balls go back into the
+/// [`BallPool`] once it runs out, anything else is despawned.
+#[derive(Component)]
+pub struct DespawnAfter(Timer);
+
+impl DespawnAfter {
+    pub fn new(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// Keeps a freshly thrown projectile from colliding with players until it runs out. Used
+/// together with [`thrown_layers`].
+#[derive(Component)]
+pub struct PassThroughPlayers(Timer);
+
+impl Default for PassThroughPlayers {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            PASS_THROUGH_PLAYERS_TIME,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// Who threw a projectile, credited with the damage it does.
+#[derive(Component, Clone, Copy)]
+pub struct Thrower(pub Entity);
+
+/// The collision layers of a projectile that was just thrown, colliding with everything but
+/// players.
+pub fn thrown_layers() -> CollisionLayers {
+    let mut filters = LayerMask::ALL;
+    filters.remove(GameLayer::Player);
+    CollisionLayers::new(GameLayer::Projectile, filters)
+}
 
 #[derive(Bundle)]
 pub struct BallBundle {
     ball: Ball,
     pub rigid_body: RigidBody,
     pub collider: Collider,
+    pub collision_layers: CollisionLayers,
+    pub pass_through_players: PassThroughPlayers,
     pub mesh3d: Mesh3d,
     pub mesh_material3d: MeshMaterial3d<StandardMaterial>,
     pub transform: Transform,
     pub linear_velocity: LinearVelocity,
+    pub restitution: Restitution,
+    pub gravity_scale: GravityScale,
     pub despawn_after: DespawnAfter,
+    pub penetrating: Penetrating,
 }
 
 impl BallBundle {
-    pub fn new(
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut materials: ResMut<Assets<StandardMaterial>>,
-        transform: Transform,
-    ) -> Self {
-        let size = 0.1;
-        let despawn_after = DespawnAfter(Utc::now() + Duration::seconds(3));
-        let mut velocity = transform.forward().normalize() * 100.0;
-        // Aim slightly upward so the ball doesn't immediately start to fall after thrown
-        velocity.y += 0.1;
-
+    pub fn new(assets: &GameAssets, transform: Transform, speed: f32, kind: BallKind) -> Self {
         Self {
             ball: Ball,
             rigid_body: RigidBody::Dynamic,
-            collider: Collider::sphere(size),
-            mesh3d: Mesh3d(meshes.add(Sphere::new(size))),
-            mesh_material3d: MeshMaterial3d(materials.add(Color::BLACK)),
-            linear_velocity: LinearVelocity(velocity),
-            despawn_after,
+            collider: Collider::sphere(BALL_RADIUS),
+            collision_layers: thrown_layers(),
+            pass_through_players: PassThroughPlayers::default(),
+            mesh3d: Mesh3d(assets.ball_mesh.clone()),
+            mesh_material3d: MeshMaterial3d(assets.ball_material(kind)),
+            linear_velocity: throw_velocity(&transform, speed),
+            restitution: kind.restitution(),
+            gravity_scale: kind.gravity_scale(),
+            despawn_after: kind.lifetime(),
+            penetrating: Penetrating::new(BALL_PENETRATION_POWER),
             transform,
         }
     }
 }
 
-pub fn handle_despawn_after(mut commands: Commands, query: Query<(Entity, &DespawnAfter)>) {
-    for (entity, despawn_after) in query {
-        if despawn_after.0 <= Utc::now() {
+fn throw_velocity(transform: &Transform, speed: f32) -> LinearVelocity {
+    let mut velocity = transform.forward().normalize() * speed;
+    // Aim slightly upward so the ball doesn't immediately start to fall after thrown
+    velocity.y += 0.1;
+    LinearVelocity(velocity)
+}
+
+/// How thrown balls are simulated.
+#[derive(Resource)]
+pub struct BallSettings {
+    /// Sweeps balls along their path every physics step, so fast throws can't tunnel through
+    /// the ground or thin objects between steps. Costs some extra time per ball in flight.
+    pub continuous_collision: bool,
+}
+
+impl Default for BallSettings {
+    fn default() -> Self {
+        Self {
+            continuous_collision: true,
+        }
+    }
+}
+
+/// Balls that have run out their lifetime, kept disabled and hidden to be thrown again.
+#[derive(Resource, Default)]
+pub struct BallPool {
+    free: Vec<Entity>,
+}
+
+impl BallPool {
+    /// Throws a ball of the given kind from `transform` along its forward direction at `speed`,
+    /// reusing a pooled one if there is any.
+    #[allow(clippy::too_many_arguments)]
+    pub fn throw(
+        &mut self,
+        commands: &mut Commands,
+        thrower: Entity,
+        assets: &GameAssets,
+        transform: Transform,
+        speed: f32,
+        kind: BallKind,
+        settings: &BallSettings,
+    ) {
+        let ball = loop {
+            let Some(ball) = self.free.pop() else {
+                break commands
+                    .spawn(BallBundle::new(assets, transform, speed, kind))
+                    .id();
+            };
+            // Pooled balls may have been despawned by something else in the meantime
+            let Ok(mut ball) = commands.get_entity(ball) else {
+                continue;
+            };
+
+            ball.remove::<(RigidBodyDisabled, ColliderDisabled)>()
+                .insert((
+                    throw_velocity(&transform, speed),
+                    transform,
+                    thrown_layers(),
+                    PassThroughPlayers::default(),
+                    AngularVelocity::ZERO,
+                    kind.restitution(),
+                    kind.gravity_scale(),
+                    kind.lifetime(),
+                    Penetrating::new(BALL_PENETRATION_POWER),
+                    MeshMaterial3d(assets.ball_material(kind)),
+                    Visibility::Inherited,
+                ));
+            break ball.id();
+        };
+
+        let mut ball = commands.entity(ball);
+        ball.insert(Thrower(thrower));
+        match kind {
+            BallKind::Regular => {}
+            BallKind::Bouncy => {
+                ball.insert(BouncesLeft(BOUNCY_BALL_BOUNCES));
+            }
+            BallKind::Homing => {
+                ball.insert(Homing);
+            }
+        }
+        // Balls don't need to spin to hit things, so a linear sweep is enough
+        if settings.continuous_collision {
+            ball.insert(SweptCcd::LINEAR);
+        } else {
+            ball.remove::<SweptCcd>();
+        }
+    }
+
+    pub(crate) fn release(&mut self, commands: &mut Commands, ball: Entity) {
+        commands
+            .entity(ball)
+            .remove::<(DespawnAfter, BouncesLeft, Homing, PassThroughPlayers)>()
+            .insert((RigidBodyDisabled, ColliderDisabled, Visibility::Hidden));
+        self.free.push(ball);
+    }
+}
+
+pub fn handle_despawn_after(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut ball_pool: ResMut<BallPool>,
+    query: Query<(Entity, &mut DespawnAfter, Has<Ball>)>,
+) {
+    for (entity, mut despawn_after, is_ball) in query {
+        if !despawn_after.0.tick(time.delta()).finished() {
+            continue;
+        }
+
+        if is_ball {
+            ball_pool.release(&mut commands, entity);
+        } else {
             commands.entity(entity).despawn();
         }
     }
 }
+
+/// Lets projectiles collide with players again once they're clear of whoever threw them.
+pub fn stop_passing_through_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    query: Query<(Entity, &mut PassThroughPlayers, &mut CollisionLayers)>,
+) {
+    for (entity, mut pass_through, mut layers) in query {
+        if !pass_through.0.tick(time.delta()).finished() {
+            continue;
+        }
+
+        layers.filters.add(GameLayer::Player);
+        commands.entity(entity).remove::<PassThroughPlayers>();
+    }
+}
+
+/// Counts the bounces of bouncy balls and puts them back into the [`BallPool`] once they have
+/// none left.
+pub fn count_bounces(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    mut ball_pool: ResMut<BallPool>,
+    mut balls: Query<&mut BouncesLeft>,
+) {
+    for CollisionStarted(entity1, entity2) in collisions.read() {
+        for entity in [*entity1, *entity2] {
+            let Ok(mut bounces_left) = balls.get_mut(entity) else {
+                continue;
+            };
+
+            // Already released by an earlier collision this frame
+            if bounces_left.0 == 0 {
+                continue;
+            }
+
+            bounces_left.0 -= 1;
+            if bounces_left.0 == 0 {
+                ball_pool.release(&mut commands, entity);
+            }
+        }
+    }
+}
+
+/// Hurts characters hit by balls, more the faster the ball was going.
+pub fn damage_on_hit(
+    mut collisions: EventReader<CollisionStarted>,
+    mut damage: EventWriter<Damage>,
+    balls: Query<(&LinearVelocity, Option<&Thrower>), With<Ball>>,
+    characters: Query<(), With<Health>>,
+) {
+    for CollisionStarted(entity1, entity2) in collisions.read() {
+        for (ball, target) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            let Ok((velocity, thrower)) = balls.get(ball) else {
+                continue;
+            };
+            let excess_speed = velocity.length() - BALL_DAMAGE_MIN_SPEED;
+            if excess_speed > 0.0 && characters.contains(target) {
+                damage.write(Damage {
+                    target,
+                    amount: excess_speed * BALL_DAMAGE_PER_SPEED,
+                    kind: DamageKind::Projectile,
+                    source: thrower.map(|thrower| thrower.0),
+                });
+            }
+        }
+    }
+}
+
+/// Turns homing balls toward the nearest target inside their cone, by no more than
+/// [`HOMING_TURN_RATE`], keeping their speed.
+#[allow(clippy::type_complexity)]
+pub fn steer_homing(
+    time: Res<Time>,
+    mut balls: Query<(&Position, &mut LinearVelocity), With<Homing>>,
+    targets: Query<&Position, (With<Health>, Without<Player>, Without<Homing>)>,
+) {
+    let max_turn = HOMING_TURN_RATE * time.delta_secs();
+
+    for (position, mut velocity) in &mut balls {
+        let Ok(heading) = Dir3::new(velocity.0) else {
+            continue;
+        };
+
+        let target = targets
+            .iter()
+            .map(|target| target.0 - position.0)
+            .filter(|offset| {
+                offset.length() <= HOMING_RANGE
+                    && heading.angle_between(*offset) <= HOMING_CONE_ANGLE
+            })
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        let Some(Ok(wanted)) = target.map(Dir3::new) else {
+            continue;
+        };
+
+        let turn = Quat::from_rotation_arc(*heading, *wanted);
+        let (axis, angle) = turn.to_axis_angle();
+        let turn = Quat::from_axis_angle(axis, angle.min(max_turn));
+        velocity.0 = turn * velocity.0;
+    }
+}
@@ -0,0 +1,190 @@
+use crate::camera::CharacterCameras;
+use crate::cube::{Cube, CubeBundle};
+use crate::game_assets::{CUBE_SIZE, GameAssets};
+use crate::layers::GameLayer;
+use crate::profile::Profile;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The blueprint's file name in the profile's folder, where it's saved to and loaded from.
+const BLUEPRINT_FILE: &str = "build.blueprint.ron";
+/// How far away a blueprint can be placed where the camera looks. Looking further, it's placed
+/// this far straight ahead.
+const BLUEPRINT_RANGE: Scalar = 20.0;
+
+/// Saves every cube into the blueprint file.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct SaveBlueprint;
+
+/// Spawns the cubes of the blueprint file where the camera looks.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct LoadBlueprint;
+
+/// Cubes stacked into something, saved to be built again elsewhere. Cubes are placed relative
+/// to the blueprint's base: the middle of its footprint, level with the bottom of its lowest
+/// cube.
+#[derive(Serialize, Deserialize)]
+pub struct Blueprint {
+    pub cubes: Vec<BlueprintCube>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlueprintCube {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// The cube's sRGB color.
+    pub color: [f32; 3],
+    pub mass: f32,
+}
+
+pub struct BlueprintPlugin;
+
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(save_blueprint)
+            .add_observer(load_blueprint);
+    }
+}
+
+fn save_blueprint(
+    _trigger: Trigger<Started<SaveBlueprint>>,
+    profile: Res<Profile>,
+    materials: Res<Assets<StandardMaterial>>,
+    cubes: Query<(&Transform, &Mass, &MeshMaterial3d<StandardMaterial>), With<Cube>>,
+) {
+    if cubes.is_empty() {
+        info!("There are no cubes to save into a blueprint");
+        return;
+    }
+
+    let count = cubes.iter().count() as f32;
+    let center = cubes
+        .iter()
+        .map(|(transform, ..)| transform.translation)
+        .sum::<Vec3>()
+        / count;
+    let bottom = cubes
+        .iter()
+        .map(|(transform, ..)| transform.translation.y)
+        .fold(f32::INFINITY, f32::min)
+        - CUBE_SIZE / 2.0;
+    let base = center.with_y(bottom);
+
+    let blueprint = Blueprint {
+        cubes: cubes
+            .iter()
+            .map(|(transform, mass, material)| {
+                let color = materials
+                    .get(&material.0)
+                    .map_or(Color::WHITE, |material| material.base_color)
+                    .to_srgba();
+                BlueprintCube {
+                    translation: transform.translation - base,
+                    rotation: transform.rotation,
+                    color: [color.red, color.green, color.blue],
+                    mass: mass.0,
+                }
+            })
+            .collect(),
+    };
+    let path = profile.dir().join(BLUEPRINT_FILE);
+    blueprint.save(&path);
+    info!(
+        "Saved {} cubes into {}",
+        blueprint.cubes.len(),
+        path.display()
+    );
+}
+
+/// Builds the blueprint with its base on what the camera looks at.
+fn load_blueprint(
+    trigger: Trigger<Started<LoadBlueprint>>,
+    mut commands: Commands,
+    profile: Res<Profile>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<GameAssets>,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+) {
+    let path = profile.dir().join(BLUEPRINT_FILE);
+    let Some(blueprint) = Blueprint::load(&path) else {
+        return;
+    };
+
+    let player = trigger.target();
+    let Some(camera) = cameras.transform(player) else {
+        return;
+    };
+    let filter = SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([player]);
+    let base = spatial_query
+        .cast_ray(
+            camera.translation(),
+            camera.forward(),
+            BLUEPRINT_RANGE,
+            true,
+            &filter,
+        )
+        .map_or(BLUEPRINT_RANGE, |hit| hit.distance);
+    let base = camera.translation() + camera.forward() * base;
+
+    for cube in &blueprint.cubes {
+        let [red, green, blue] = cube.color;
+        let mut bundle = CubeBundle::new(
+            &assets,
+            Transform::from_translation(base + cube.translation).with_rotation(cube.rotation),
+        );
+        bundle.mesh_material3d = MeshMaterial3d(materials.add(Color::srgb(red, green, blue)));
+        bundle.mass = Mass(cube.mass);
+        commands.spawn(bundle);
+    }
+    info!(
+        "Built {} cubes from {}",
+        blueprint.cubes.len(),
+        path.display()
+    );
+}
+
+impl Blueprint {
+    fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path)
+            .inspect_err(|error| {
+                warn!("Couldn't read a blueprint from {}: {error}", path.display());
+            })
+            .ok()?;
+        ron::from_str(&contents)
+            .inspect_err(|error| {
+                warn!(
+                    "Couldn't parse a blueprint from {}: {error}",
+                    path.display()
+                );
+            })
+            .ok()
+    }
+
+    fn save(&self, path: &Path) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize the blueprint: {error}");
+                return;
+            }
+        };
+
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|()| fs::write(path, contents)),
+            None => fs::write(path, contents),
+        };
+        if let Err(error) = result {
+            error!(
+                "Couldn't write the blueprint to {}: {error}",
+                path.display()
+            );
+        }
+    }
+}
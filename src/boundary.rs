@@ -0,0 +1,49 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+/// How far boundary walls reach above the ground, high enough that nothing launched in the
+/// level makes it over.
+const BOUNDARY_HEIGHT: f32 = 200.0;
+/// How far boundary walls reach below the ground, for terrain dipping under it.
+const BOUNDARY_DEPTH: f32 = 20.0;
+const BOUNDARY_THICKNESS: f32 = 2.0;
+
+/// An invisible wall at the edge of a level's playable area.
+#[derive(Component)]
+pub struct BoundaryWall;
+
+/// The four walls closing in a square playable area `size` wide, centered on the origin. They
+/// stand just outside of it, so the whole area stays reachable.
+pub fn boundary_walls(size: f32) -> [impl Bundle + use<>; 4] {
+    let height = BOUNDARY_HEIGHT + BOUNDARY_DEPTH;
+    let length = size + 2.0 * BOUNDARY_THICKNESS;
+    let offset = (size + BOUNDARY_THICKNESS) / 2.0;
+    let y = (BOUNDARY_HEIGHT - BOUNDARY_DEPTH) / 2.0;
+
+    [
+        (
+            Vec3::new(length, height, BOUNDARY_THICKNESS),
+            Vec3::new(0.0, y, offset),
+        ),
+        (
+            Vec3::new(length, height, BOUNDARY_THICKNESS),
+            Vec3::new(0.0, y, -offset),
+        ),
+        (
+            Vec3::new(BOUNDARY_THICKNESS, height, length),
+            Vec3::new(offset, y, 0.0),
+        ),
+        (
+            Vec3::new(BOUNDARY_THICKNESS, height, length),
+            Vec3::new(-offset, y, 0.0),
+        ),
+    ]
+    .map(|(size, position)| {
+        (
+            BoundaryWall,
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            Transform::from_translation(position),
+        )
+    })
+}
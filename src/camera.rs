@@ -0,0 +1,487 @@
+use crate::input_routing::LocalPlayer;
+use crate::layers::GameLayer;
+use crate::player_movement::{IsSprinting, Landed, Player, Stance, ThrowCharge};
+use avian3d::{math::*, prelude::*};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy_enhanced_input::prelude::*;
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+/// The camera's resting height above the character's center.
+pub(crate) const CAMERA_HEIGHT: f32 = 0.2;
+/// The camera's resting height above the character's center while prone.
+const PRONE_CAMERA_HEIGHT: f32 = 0.1;
+/// Landings faster than this dip the camera, more the harder the impact.
+const CAMERA_KNOCK_MIN_SPEED: f32 = 10.0;
+const CAMERA_KNOCK_PER_SPEED: f32 = 0.01;
+const CAMERA_KNOCK_MAX: f32 = 0.3;
+/// How far the camera jolts around at full shake.
+const SHAKE_AMPLITUDE: f32 = 0.15;
+/// How much shake wears off per second. Shake is squared when applied, so it eases out.
+const SHAKE_DECAY: f32 = 1.5;
+/// The horizontal speed at which head bob reaches its full amplitude.
+const HEAD_BOB_FULL_SPEED: f32 = 10.0;
+/// How far below the screen center the throw charge bar sits, in pixels.
+const CHARGE_BAR_OFFSET: f32 = 40.0;
+const CHARGE_BAR_WIDTH: f32 = 60.0;
+const CHARGE_BAR_HEIGHT: f32 = 4.0;
+
+/// The radius kept clear around the third-person camera so it doesn't clip into geometry.
+const CAMERA_COLLISION_RADIUS: Scalar = 0.2;
+/// How quickly the third-person boom extends again after geometry pushed the camera in.
+const BOOM_EXTEND_RESPONSIVENESS: f32 = 4.0;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerAim;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerToggleView;
+
+/// Tunes how the first-person camera reacts to the player's movement. Angles are in radians.
+#[derive(Resource)]
+pub struct CameraSettings {
+    /// The field of view while walking or standing still.
+    pub base_fov: f32,
+    /// The widest the field of view ever gets.
+    pub max_fov: f32,
+    /// How much wider the field of view gets while sprinting.
+    pub sprint_fov_boost: f32,
+    /// How much wider the field of view gets when falling at `fall_fov_full_speed` or faster.
+    pub fall_fov_boost: f32,
+    /// The falling speed at which the field of view starts to widen.
+    pub fall_fov_start_speed: f32,
+    pub fall_fov_full_speed: f32,
+    /// The field of view while aiming. Mouse sensitivity scales down along with it.
+    pub aim_fov: f32,
+    /// How quickly the field of view follows changes.
+    pub fov_responsiveness: f32,
+    /// Whether the camera bobs while walking. Can be turned off for motion-sensitive players.
+    pub head_bob: bool,
+    /// How far the camera bobs up and down at full speed. It sways half as far sideways.
+    pub head_bob_amplitude: f32,
+    /// Bobs per meter walked.
+    pub head_bob_frequency: f32,
+    /// Whether explosions shake the camera. Can be turned off for motion-sensitive players.
+    pub camera_shake: bool,
+    /// How far behind the player the third-person camera sits when nothing is in the way.
+    pub third_person_distance: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            // Bevy's default perspective field of view
+            base_fov: FRAC_PI_4,
+            max_fov: 70.0_f32.to_radians(),
+            sprint_fov_boost: 8.0_f32.to_radians(),
+            fall_fov_boost: 12.0_f32.to_radians(),
+            fall_fov_start_speed: 10.0,
+            fall_fov_full_speed: 30.0,
+            aim_fov: 25.0_f32.to_radians(),
+            fov_responsiveness: 6.0,
+            head_bob: true,
+            head_bob_amplitude: 0.04,
+            head_bob_frequency: 0.35,
+            camera_shake: true,
+            third_person_distance: 4.0,
+        }
+    }
+}
+
+/// The offsets that position a player camera relative to its player.
+#[derive(Component)]
+pub struct CameraRig {
+    /// The height the camera is easing toward its resting height with.
+    height: f32,
+    /// How far a landing currently has the camera dipped.
+    knock: f32,
+    /// How far through its bob cycle the camera is, in radians.
+    bob_phase: f32,
+    /// How hard the camera is shaking, from 0 to 1.
+    shake: f32,
+    aiming: bool,
+    third_person: bool,
+    /// The current length of the third-person boom, shortened when geometry is in the way.
+    boom: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            height: CAMERA_HEIGHT,
+            knock: 0.0,
+            bob_phase: 0.0,
+            shake: 0.0,
+            aiming: false,
+            third_person: false,
+            boom: 0.0,
+        }
+    }
+}
+
+impl CameraRig {
+    /// Shakes the camera harder, by `amount` from 0 to 1.
+    pub fn shake(&mut self, amount: f32) {
+        self.shake = (self.shake + amount).min(1.0);
+    }
+}
+
+/// Finds the camera of a character, which is spawned as one of its children, so actions aim
+/// with the camera of the player doing them.
+#[derive(SystemParam)]
+pub struct CharacterCameras<'w, 's> {
+    children: Query<'w, 's, &'static Children>,
+    cameras: Query<'w, 's, (Entity, &'static GlobalTransform), With<CameraRig>>,
+}
+
+impl CharacterCameras<'_, '_> {
+    pub fn get(&self, character: Entity) -> Option<(Entity, &GlobalTransform)> {
+        let children = self.children.get(character).ok()?;
+        self.cameras.iter_many(children).next()
+    }
+
+    /// Just the camera's transform, for actions that don't need the camera itself.
+    pub fn transform(&self, character: Entity) -> Option<&GlobalTransform> {
+        self.get(character).map(|(_, transform)| transform)
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .add_systems(
+                Update,
+                (
+                    update_fov,
+                    knock_camera,
+                    position_camera,
+                    spawn_charge_bars,
+                    update_charge_bar,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, split_screen)
+            .add_observer(start_aiming)
+            .add_observer(stop_aiming)
+            .add_observer(toggle_view);
+    }
+}
+
+/// The bar under the crosshair of this camera, showing how far its player's throw is charged.
+/// Hidden while not charging.
+#[derive(Component)]
+struct ChargeBar(Entity);
+
+#[derive(Component)]
+struct ChargeBarFill(Entity);
+
+/// Gives every new player camera a charge bar of its own, drawn over that camera's view.
+fn spawn_charge_bars(mut commands: Commands, cameras: Query<Entity, Added<CameraRig>>) {
+    for camera in &cameras {
+        commands
+            .spawn((
+                ChargeBar(camera),
+                UiTargetCamera(camera),
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                Visibility::Hidden,
+            ))
+            .with_child((
+                Node {
+                    width: Val::Px(CHARGE_BAR_WIDTH),
+                    height: Val::Px(CHARGE_BAR_HEIGHT),
+                    top: Val::Px(CHARGE_BAR_OFFSET),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE.with_alpha(0.3)),
+                children![(
+                    ChargeBarFill(camera),
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.8)),
+                )],
+            ));
+    }
+}
+
+fn start_aiming(
+    trigger: Trigger<Started<PlayerAim>>,
+    children: Query<&Children>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    let Ok(children) = children.get(trigger.target()) else {
+        return;
+    };
+    let mut rigs = cameras.iter_many_mut(children);
+    while let Some(mut rig) = rigs.fetch_next() {
+        rig.aiming = true;
+    }
+}
+
+fn stop_aiming(
+    trigger: Trigger<Completed<PlayerAim>>,
+    children: Query<&Children>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    let Ok(children) = children.get(trigger.target()) else {
+        return;
+    };
+    let mut rigs = cameras.iter_many_mut(children);
+    while let Some(mut rig) = rigs.fetch_next() {
+        rig.aiming = false;
+    }
+}
+
+fn toggle_view(
+    trigger: Trigger<Started<PlayerToggleView>>,
+    children: Query<&Children>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    let Ok(children) = children.get(trigger.target()) else {
+        return;
+    };
+    let mut rigs = cameras.iter_many_mut(children);
+    while let Some(mut rig) = rigs.fetch_next() {
+        rig.third_person = !rig.third_person;
+        rig.boom = 0.0;
+    }
+}
+
+/// Narrows the player camera's field of view while aiming, or widens it while sprinting or
+/// falling fast, and eases it back afterwards.
+fn update_fov(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    players: Query<(&IsSprinting, &LinearVelocity, &Children), With<Player>>,
+    mut cameras: Query<(&mut Projection, &CameraRig)>,
+) {
+    for (is_sprinting, velocity, children) in &players {
+        let mut target_fov = settings.base_fov;
+        if is_sprinting.0 {
+            target_fov += settings.sprint_fov_boost;
+        }
+
+        let fall_amount = ((-velocity.y - settings.fall_fov_start_speed)
+            / (settings.fall_fov_full_speed - settings.fall_fov_start_speed))
+            .clamp(0.0, 1.0);
+        target_fov += settings.fall_fov_boost * fall_amount;
+        let target_fov = target_fov.min(settings.max_fov);
+
+        let mut iter = cameras.iter_many_mut(children);
+        while let Some((mut projection, rig)) = iter.fetch_next() {
+            let target_fov = if rig.aiming {
+                settings.aim_fov
+            } else {
+                target_fov
+            };
+
+            if let Projection::Perspective(perspective) = &mut *projection {
+                perspective.fov.smooth_nudge(
+                    &target_fov,
+                    settings.fov_responsiveness,
+                    time.delta_secs(),
+                );
+            }
+        }
+    }
+}
+
+/// Dips the camera on hard landings.
+fn knock_camera(
+    mut landed: EventReader<Landed>,
+    players: Query<&Children, With<Player>>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    for event in landed.read() {
+        let Ok(children) = players.get(event.entity) else {
+            continue;
+        };
+
+        let knock = ((event.impact_speed - CAMERA_KNOCK_MIN_SPEED) * CAMERA_KNOCK_PER_SPEED)
+            .clamp(0.0, CAMERA_KNOCK_MAX);
+        let mut iter = cameras.iter_many_mut(children);
+        while let Some(mut rig) = iter.fetch_next() {
+            rig.knock = (rig.knock + knock).min(CAMERA_KNOCK_MAX);
+        }
+    }
+}
+
+/// Places each player camera at its resting height, which is lower while prone, minus any
+/// landing dip. In first person, head bob is added while walking on the ground. In third
+/// person, the camera sits on a boom behind that point that shortens when geometry is in
+/// the way. Either way, the camera is jolted around while it's shaking.
+#[allow(clippy::type_complexity)]
+fn position_camera(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    spatial_query: SpatialQuery,
+    players: Query<
+        (
+            Entity,
+            &Transform,
+            &Stance,
+            &LinearVelocity,
+            &ShapeHits,
+            &Children,
+        ),
+        With<Player>,
+    >,
+    mut cameras: Query<(&mut CameraRig, &mut Transform), Without<Player>>,
+) {
+    let delta = time.delta_secs();
+
+    for (entity, player_transform, stance, velocity, ground_hits, children) in &players {
+        let target_height = if *stance == Stance::Prone {
+            PRONE_CAMERA_HEIGHT
+        } else {
+            CAMERA_HEIGHT
+        };
+        let speed = if ground_hits.is_empty() {
+            0.0
+        } else {
+            velocity.xz().length()
+        };
+
+        let mut iter = cameras.iter_many_mut(children);
+        while let Some((mut rig, mut transform)) = iter.fetch_next() {
+            rig.height.smooth_nudge(&target_height, 8.0, delta);
+            rig.knock.smooth_nudge(&0.0, 8.0, delta);
+            rig.bob_phase =
+                (rig.bob_phase + TAU * settings.head_bob_frequency * speed * delta) % TAU;
+            rig.shake = (rig.shake - SHAKE_DECAY * delta).max(0.0);
+
+            let bob = if settings.head_bob {
+                settings.head_bob_amplitude * (speed / HEAD_BOB_FULL_SPEED).min(1.0)
+            } else {
+                0.0
+            };
+            let shake = if settings.camera_shake {
+                Vec3::new(
+                    rand::random_range(-1.0..1.0),
+                    rand::random_range(-1.0..1.0),
+                    rand::random_range(-1.0..1.0),
+                ) * SHAKE_AMPLITUDE
+                    * rig.shake.powi(2)
+            } else {
+                Vec3::ZERO
+            };
+            let pivot = Vec3::Y * (rig.height - rig.knock);
+
+            if !rig.third_person {
+                transform.translation = pivot
+                    + shake
+                    + Vec3::new(
+                        bob / 2.0 * rig.bob_phase.sin(),
+                        bob * (2.0 * rig.bob_phase).sin(),
+                        0.0,
+                    );
+                continue;
+            }
+
+            // Cast from the pivot back along the camera's view to find how much room there is
+            let back = transform.back();
+            let world_pivot = player_transform.transform_point(pivot);
+            let world_back = player_transform.rotation * back;
+            let room = spatial_query
+                .cast_shape(
+                    &Collider::sphere(CAMERA_COLLISION_RADIUS),
+                    world_pivot,
+                    Quat::IDENTITY,
+                    world_back,
+                    &ShapeCastConfig::from_max_distance(settings.third_person_distance),
+                    &SpatialQueryFilter::from_mask(GameLayer::solid())
+                        .with_excluded_entities([entity]),
+                )
+                .map_or(settings.third_person_distance, |hit| hit.distance);
+
+            // Pull in right away so the camera never clips, but ease back out
+            if room < rig.boom {
+                rig.boom = room;
+            } else {
+                rig.boom
+                    .smooth_nudge(&room, BOOM_EXTEND_RESPONSIVENESS, delta);
+            }
+            transform.translation = pivot + back * rig.boom + shake;
+        }
+    }
+}
+
+/// Splits the window side by side between the local players' cameras, in player order, or gives
+/// all of it to the only one.
+fn split_screen(
+    window: Single<&Window>,
+    players: Query<&LocalPlayer>,
+    mut cameras: Query<(&ChildOf, &mut Camera), With<CameraRig>>,
+) {
+    let mut views = cameras
+        .iter_mut()
+        .filter_map(|(child_of, camera)| Some((*players.get(child_of.parent()).ok()?, camera)))
+        .collect::<Vec<_>>();
+    views.sort_by_key(|(player, _)| *player);
+
+    let count = views.len() as u32;
+    let size = window.physical_size();
+    for (index, (_, mut camera)) in (0..).zip(views) {
+        let viewport = (count > 1).then(|| Viewport {
+            physical_position: UVec2::new(size.x / count * index, 0),
+            physical_size: UVec2::new(size.x / count, size.y),
+            ..default()
+        });
+        let bounds = |viewport: &Option<Viewport>| {
+            viewport
+                .as_ref()
+                .map(|viewport| (viewport.physical_position, viewport.physical_size))
+        };
+        // Only touched when it changes, so cameras aren't marked changed every frame
+        if bounds(&camera.viewport) != bounds(&viewport) {
+            camera.viewport = viewport;
+        }
+        if camera.order != index as isize {
+            camera.order = index as isize;
+        }
+    }
+}
+
+/// Shows the charge of the player each bar's camera belongs to, and despawns the bars of
+/// cameras that are gone.
+fn update_charge_bar(
+    mut commands: Commands,
+    time: Res<Time>,
+    cameras: Query<&ChildOf, With<CameraRig>>,
+    players: Query<&ThrowCharge, With<Player>>,
+    mut bars: Query<(Entity, &ChargeBar, &mut Visibility)>,
+    mut fills: Query<(&ChargeBarFill, &mut Node)>,
+) {
+    let charge = |camera| {
+        let player = cameras.get(camera).ok()?.parent();
+        players.get(player).ok()?.charge(time.elapsed_secs())
+    };
+
+    for (entity, bar, mut visibility) in &mut bars {
+        if !cameras.contains(bar.0) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        *visibility = if charge(bar.0).is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for (fill, mut node) in &mut fills {
+        node.width = Val::Percent(charge(fill.0).unwrap_or(0.0) * 100.0);
+    }
+}
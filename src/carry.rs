@@ -0,0 +1,157 @@
+use crate::camera::CharacterCameras;
+use crate::layers::GameLayer;
+use crate::player_movement::{Player, PlayerAction, ThrowCharge};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+/// How far from the camera things can be interacted with.
+const INTERACT_REACH: Scalar = 3.0;
+/// How far from the camera objects can be picked up.
+const CARRY_REACH: Scalar = 2.5;
+/// Anything heavier than this is too heavy to pick up.
+const CARRY_MAX_MASS: Scalar = 5.0;
+/// How far in front of the camera carried objects are held.
+const CARRY_DISTANCE: Scalar = 1.2;
+/// How fast a carried object is pulled toward the hold point, per meter it is away from it.
+const CARRY_STIFFNESS: Scalar = 20.0;
+const CARRY_MAX_SPEED: Scalar = 15.0;
+/// A carried object that gets caught on something this far from the hold point slips out of
+/// the character's hands.
+const CARRY_SLIP_DISTANCE: Scalar = 2.0;
+const TOSS_SPEED: Scalar = 8.0;
+
+/// Drops the object being carried, or else picks up the small object in front of the
+/// character. Anything else in front of it gets an [`Interacted`] instead.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerInteract;
+
+/// Sent to what a player interacts with when it isn't picked up, like a
+/// [`Door`](crate::door::Door) or an [`Npc`](crate::dialog::Npc). Only one thing is interacted
+/// with per press, so dropping an object in a doorway doesn't also slam the door.
+#[derive(Event)]
+pub struct Interacted;
+
+/// The object a character is carrying in its hands, if any. While carrying, the character's
+/// tools are put away and [`PlayerAction`] tosses the object instead.
+#[derive(Component, Default)]
+pub struct Carry(Option<Entity>);
+
+impl Carry {
+    pub fn is_carrying(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+pub struct CarryPlugin;
+
+impl Plugin for CarryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, carry_objects)
+            .add_observer(interact)
+            .add_observer(toss);
+    }
+}
+
+fn interact(
+    trigger: Trigger<Started<PlayerInteract>>,
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+    bodies: Query<(&RigidBody, &ComputedMass), Without<RigidBodyDisabled>>,
+    mut query: Query<(&mut Carry, &mut ThrowCharge), With<Player>>,
+) {
+    let entity = trigger.target();
+    let Ok((mut carry, mut throw_charge)) = query.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+
+    // Dropping just lets go, so the object keeps the momentum it was carried with
+    if carry.0.take().is_some() {
+        return;
+    }
+
+    let filter = SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+    let Some(hit) = spatial_query.cast_ray(
+        camera.translation(),
+        camera.forward(),
+        INTERACT_REACH,
+        true,
+        &filter,
+    ) else {
+        return;
+    };
+
+    let carriable = bodies
+        .get(hit.entity)
+        .is_ok_and(|(rigid_body, mass)| rigid_body.is_dynamic() && mass.value() <= CARRY_MAX_MASS);
+    if carriable && hit.distance <= CARRY_REACH {
+        carry.0 = Some(hit.entity);
+        // Hands are full, so a throw being charged can't go off anymore
+        throw_charge.0 = None;
+    } else {
+        commands.trigger_targets(Interacted, hit.entity);
+    }
+}
+
+/// Tosses the carried object on release, so the press that started it doesn't also reach the
+/// tools.
+fn toss(
+    trigger: Trigger<Completed<PlayerAction>>,
+    cameras: CharacterCameras,
+    mut bodies: Query<&mut LinearVelocity>,
+    mut query: Query<&mut Carry, With<Player>>,
+) {
+    let Ok(mut carry) = query.get_mut(trigger.target()) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(trigger.target()) else {
+        return;
+    };
+    let Some(carried) = carry.0.take() else {
+        return;
+    };
+
+    if let Ok(mut velocity) = bodies.get_mut(carried) {
+        velocity.0 += camera.forward() * TOSS_SPEED;
+    }
+}
+
+/// Pulls carried objects toward the point in front of the camera. They stay dynamic, so they
+/// still collide with the world instead of clipping through it, and slip out of the
+/// character's hands when they get caught on something.
+fn carry_objects(
+    cameras: CharacterCameras,
+    mut bodies: Query<
+        (&Position, &mut LinearVelocity, &mut AngularVelocity),
+        Without<RigidBodyDisabled>,
+    >,
+    mut query: Query<(Entity, &mut Carry), With<Player>>,
+) {
+    for (entity, mut carry) in &mut query {
+        let Some(carried) = carry.0 else {
+            continue;
+        };
+        let Some(camera) = cameras.transform(entity) else {
+            continue;
+        };
+        // Despawned, or put back into a pool
+        let Ok((position, mut velocity, mut angular_velocity)) = bodies.get_mut(carried) else {
+            carry.0 = None;
+            continue;
+        };
+
+        let offset = camera.translation() + camera.forward() * CARRY_DISTANCE - position.0;
+        if offset.length() > CARRY_SLIP_DISTANCE {
+            carry.0 = None;
+            continue;
+        }
+
+        velocity.0 = (offset * CARRY_STIFFNESS).clamp_length_max(CARRY_MAX_SPEED);
+        angular_velocity.0 = Vector::ZERO;
+    }
+}
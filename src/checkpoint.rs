@@ -0,0 +1,186 @@
+use crate::combat::{Dead, Health};
+use crate::player_movement::{
+    FallSpeed, PLAYER_HEALTH, Player, Stamina, StaminaConfig, ThrowCharge,
+};
+use crate::spawn_point::{SpawnKind, SpawnPoints};
+use crate::trigger::{TriggerEnter, TriggerVolume, TriggerVolumeBundle};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// How long the screen takes to fade out before a respawn, and back in after it.
+const RESPAWN_FADE_TIME: f32 = 0.5;
+
+/// A trigger volume recording where a player touching it respawns: at the checkpoint itself.
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct Checkpoint;
+
+#[derive(Bundle)]
+pub struct CheckpointBundle {
+    checkpoint: Checkpoint,
+    pub trigger_volume: TriggerVolumeBundle,
+    pub mesh3d: Mesh3d,
+    pub mesh_material3d: MeshMaterial3d<StandardMaterial>,
+    pub transform: Transform,
+}
+
+impl CheckpointBundle {
+    pub fn new(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        size: Vec3,
+        transform: Transform,
+    ) -> Self {
+        Self {
+            checkpoint: Checkpoint,
+            trigger_volume: TriggerVolumeBundle::new(
+                Collider::cuboid(size.x, size.y, size.z),
+                transform,
+            ),
+            transform,
+            mesh3d: Mesh3d(meshes.add(Cuboid::from_size(size))),
+            mesh_material3d: MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.2, 0.8, 1.0, 0.15),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+        }
+    }
+}
+
+/// The checkpoint the player last touched. Players respawn at a spawn point instead if there is
+/// none, or it's gone with the level it was in.
+#[derive(Component, Default)]
+pub struct LastCheckpoint(Option<Entity>);
+
+/// Set while a player is being respawned, from the elapsed seconds it started at. The screen
+/// fades out, the player is moved back and healed, and the screen fades back in.
+#[derive(Component)]
+pub struct Respawning {
+    started: f32,
+    moved: bool,
+}
+
+impl Respawning {
+    pub fn new(now: f32) -> Self {
+        Self {
+            started: now,
+            moved: false,
+        }
+    }
+}
+
+/// Covers the screen while respawning.
+#[derive(Component)]
+struct ScreenFade;
+
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_screen_fade)
+            .add_systems(Update, respawn_players)
+            .add_observer(reach_checkpoint);
+    }
+}
+
+fn spawn_screen_fade(mut commands: Commands) {
+    commands.spawn((
+        ScreenFade,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.0)),
+        // Above the HUD, so nothing but black is seen during the respawn
+        GlobalZIndex(5),
+        Pickable::IGNORE,
+    ));
+}
+
+fn reach_checkpoint(
+    trigger: Trigger<TriggerEnter>,
+    checkpoints: Query<(), With<Checkpoint>>,
+    mut players: Query<&mut LastCheckpoint, With<Player>>,
+) {
+    let checkpoint = trigger.target();
+    if !checkpoints.contains(checkpoint) {
+        return;
+    }
+    if let Ok(mut last_checkpoint) = players.get_mut(trigger.entity) {
+        last_checkpoint.0 = Some(checkpoint);
+    }
+}
+
+/// Fades the screen out, moves respawning players to their last checkpoint with their speed,
+/// health, stamina and throw charge reset, and fades back in.
+#[allow(clippy::type_complexity)]
+fn respawn_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    stamina_config: Res<StaminaConfig>,
+    spawn_points: SpawnPoints,
+    checkpoints: Query<&GlobalTransform, With<Checkpoint>>,
+    mut players: Query<
+        (
+            Entity,
+            &LastCheckpoint,
+            &mut Respawning,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut FallSpeed,
+            &mut Health,
+            &mut Stamina,
+            &mut ThrowCharge,
+        ),
+        With<Player>,
+    >,
+    mut fades: Query<&mut BackgroundColor, With<ScreenFade>>,
+) {
+    let mut fade = 0.0;
+
+    for (
+        entity,
+        last_checkpoint,
+        mut respawning,
+        mut transform,
+        mut velocity,
+        mut fall_speed,
+        mut health,
+        mut stamina,
+        mut throw_charge,
+    ) in &mut players
+    {
+        let progress = (time.elapsed_secs() - respawning.started) / RESPAWN_FADE_TIME;
+
+        if progress >= 1.0 && !respawning.moved {
+            let respawn = last_checkpoint
+                .0
+                .and_then(|checkpoint| checkpoints.get(checkpoint).ok())
+                .map(GlobalTransform::compute_transform)
+                .or_else(|| spawn_points.pick(SpawnKind::Player, None));
+            if let Some(respawn) = respawn {
+                transform.translation = respawn.translation;
+            }
+            velocity.0 = Vector::ZERO;
+            fall_speed.0 = 0.0;
+            health.0 = PLAYER_HEALTH;
+            stamina.0 = stamina_config.max;
+            throw_charge.0 = None;
+            commands.entity(entity).remove::<Dead>();
+            respawning.moved = true;
+        }
+        if progress >= 2.0 {
+            commands.entity(entity).remove::<Respawning>();
+        }
+
+        fade = f32::max(fade, 1.0 - (progress - 1.0).abs());
+    }
+
+    for mut color in &mut fades {
+        color.0 = color.0.with_alpha(fade.clamp(0.0, 1.0));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::trigger::{TriggerEnter, TriggerExit, TriggerVolume};
+use bevy::prelude::*;
+
+/// How much more damage an entity can take before it dies.
+#[derive(Component)]
+pub struct Health(pub(crate) f32);
+
+/// What dealt some [`Damage`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageKind {
+    Fall,
+    Explosion,
+    Melee,
+    Projectile,
+    Hazard,
+}
+
+/// Sent to hurt an entity with [`Health`]. Anything that does damage sends these rather than
+/// touching health itself, so UI and other reactions only need to listen here.
+#[derive(Event)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+    /// Who dealt the damage, like the player who threw the ball, or `None` for the world, like
+    /// falls and hazards.
+    pub source: Option<Entity>,
+}
+
+/// Sent once when an entity's health runs out.
+#[derive(Event)]
+pub struct Died {
+    pub entity: Entity,
+    /// The damage that finished it off.
+    pub kind: DamageKind,
+    /// Who dealt the damage that finished it off, see [`Damage::source`].
+    pub source: Option<Entity>,
+}
+
+/// Added once an entity's health runs out, so it doesn't die again. Respawning removes it.
+#[derive(Component)]
+pub struct Dead;
+
+/// Keeps an entity from taking [`Damage`] until this many elapsed seconds, like while dashing.
+#[derive(Component)]
+pub struct Invulnerable(pub f32);
+
+/// Hurts every entity with [`Health`] inside it, by the given damage per second.
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct Hazard {
+    damage: f32,
+    /// The bodies inside, as they trigger [`TriggerEnter`] and [`TriggerExit`].
+    inside: Vec<Entity>,
+}
+
+impl Hazard {
+    pub fn new(damage: f32) -> Self {
+        Self {
+            damage,
+            inside: Vec::new(),
+        }
+    }
+}
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Died>()
+            .add_systems(
+                Update,
+                (damage_from_hazards, apply_damage, log_deaths).chain(),
+            )
+            .add_observer(enter_hazard)
+            .add_observer(exit_hazard);
+    }
+}
+
+fn enter_hazard(trigger: Trigger<TriggerEnter>, mut hazards: Query<&mut Hazard>) {
+    if let Ok(mut hazard) = hazards.get_mut(trigger.target()) {
+        hazard.inside.push(trigger.entity);
+    }
+}
+
+fn exit_hazard(trigger: Trigger<TriggerExit>, mut hazards: Query<&mut Hazard>) {
+    if let Ok(mut hazard) = hazards.get_mut(trigger.target()) {
+        hazard.inside.retain(|&entity| entity != trigger.entity);
+    }
+}
+
+fn damage_from_hazards(
+    time: Res<Time>,
+    mut damage: EventWriter<Damage>,
+    hazards: Query<&Hazard>,
+    targets: Query<(), With<Health>>,
+) {
+    for hazard in &hazards {
+        for &target in &hazard.inside {
+            if targets.contains(target) {
+                damage.write(Damage {
+                    target,
+                    amount: hazard.damage * time.delta_secs(),
+                    kind: DamageKind::Hazard,
+                    source: None,
+                });
+            }
+        }
+    }
+}
+
+/// Takes [`Damage`] off health, and marks entities whose health runs out as [`Dead`].
+/// [`Invulnerable`] entities take none.
+fn apply_damage(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut damage: EventReader<Damage>,
+    mut died: EventWriter<Died>,
+    mut query: Query<(&mut Health, Option<&Invulnerable>), Without<Dead>>,
+) {
+    let now = time.elapsed_secs();
+    for event in damage.read() {
+        let Ok((mut health, invulnerable)) = query.get_mut(event.target) else {
+            continue;
+        };
+        if invulnerable.is_some_and(|invulnerable| now < invulnerable.0) {
+            continue;
+        }
+        // Already dying from an earlier hit this frame
+        if health.0 <= 0.0 {
+            continue;
+        }
+
+        health.0 = (health.0 - event.amount).max(0.0);
+        if health.0 == 0.0 {
+            commands.entity(event.target).insert(Dead);
+            died.write(Died {
+                entity: event.target,
+                kind: event.kind,
+                source: event.source,
+            });
+        }
+    }
+}
+
+fn log_deaths(mut died: EventReader<Died>) {
+    for event in died.read() {
+        info!("{} died from {:?} damage", event.entity, event.kind);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::camera::{CameraRig, CameraSettings};
+use crate::hit_feedback::HitMarker;
+use crate::player_movement::{FireRate, Player};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How much of its gap the crosshair keeps when fully zoomed in to aim.
+const AIM_GAP_FACTOR: f32 = 0.4;
+/// How long the crosshair takes to settle back after a throw spreads it out.
+const SPREAD_RECOVERY_TIME: f32 = 0.3;
+
+/// Switches the crosshair to its next style.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct CycleCrosshair;
+
+/// What the crosshair is drawn as.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum CrosshairStyle {
+    /// Four arms around the screen center.
+    #[default]
+    Cross,
+    /// A single dot at the screen center.
+    Dot,
+    CrossAndDot,
+    Hidden,
+}
+
+impl CrosshairStyle {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Cross => Self::Dot,
+            Self::Dot => Self::CrossAndDot,
+            Self::CrossAndDot => Self::Hidden,
+            Self::Hidden => Self::Cross,
+        }
+    }
+
+    fn has_arms(self) -> bool {
+        matches!(self, Self::Cross | Self::CrossAndDot)
+    }
+
+    fn has_dot(self) -> bool {
+        matches!(self, Self::Dot | Self::CrossAndDot)
+    }
+}
+
+/// How the crosshair looks. Sizes are in pixels.
+#[derive(Resource)]
+pub struct CrosshairSettings {
+    pub style: CrosshairStyle,
+    pub color: Color,
+    /// The color the crosshair flashes while the hit marker shows a hit.
+    pub hit_color: Color,
+    /// How far each arm sits from the screen center at rest.
+    pub gap: f32,
+    pub arm_length: f32,
+    pub arm_width: f32,
+    pub dot_size: f32,
+    /// How much further out the arms jump right after a throw, before settling back.
+    pub fire_spread: f32,
+}
+
+impl Default for CrosshairSettings {
+    fn default() -> Self {
+        Self {
+            style: CrosshairStyle::default(),
+            color: Color::WHITE.with_alpha(0.8),
+            hit_color: Color::srgb(1.0, 0.3, 0.3),
+            gap: 8.0,
+            arm_length: 8.0,
+            arm_width: 2.0,
+            dot_size: 3.0,
+            fire_spread: 10.0,
+        }
+    }
+}
+
+/// One arm of the crosshair, pointing away from the screen center in `0`.
+#[derive(Component)]
+struct CrosshairArm(Vec2);
+
+#[derive(Component)]
+struct CrosshairDot;
+
+pub struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrosshairSettings>()
+            .add_systems(Startup, spawn_crosshair)
+            .add_systems(Update, update_crosshair)
+            .add_observer(cycle_crosshair);
+    }
+}
+
+/// Spawns the crosshair's parts unsized, for [`update_crosshair`] to lay out by the settings.
+fn spawn_crosshair(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            // A zero-sized anchor at the screen center for the parts to be positioned around
+            parent.spawn(Node::default()).with_children(|center| {
+                for direction in [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y] {
+                    center.spawn((
+                        CrosshairArm(direction),
+                        Node {
+                            position_type: PositionType::Absolute,
+                            ..default()
+                        },
+                        BackgroundColor::default(),
+                    ));
+                }
+                center.spawn((
+                    CrosshairDot,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    BackgroundColor::default(),
+                ));
+            });
+        });
+}
+
+/// Changes the style in the [`Settings`], so it's kept for next time.
+fn cycle_crosshair(_trigger: Trigger<Started<CycleCrosshair>>, mut settings: ResMut<Settings>) {
+    settings.video.crosshair = settings.video.crosshair.next();
+    info!("Crosshair is now {:?}", settings.video.crosshair);
+}
+
+/// Lays out the crosshair in its style, pulling the arms in as the camera zooms in to aim,
+/// spreading them out after a throw and flashing it on hits.
+#[allow(clippy::too_many_arguments)]
+fn update_crosshair(
+    time: Res<Time>,
+    settings: Res<CrosshairSettings>,
+    camera_settings: Res<CameraSettings>,
+    cameras: Query<&Projection, With<CameraRig>>,
+    players: Query<&FireRate, With<Player>>,
+    markers: Query<&HitMarker>,
+    mut arms: Query<
+        (
+            &CrosshairArm,
+            &mut Node,
+            &mut BackgroundColor,
+            &mut Visibility,
+        ),
+        Without<CrosshairDot>,
+    >,
+    mut dots: Query<(&mut Node, &mut BackgroundColor, &mut Visibility), With<CrosshairDot>>,
+) {
+    let now = time.elapsed_secs();
+
+    let zoom = match cameras.iter().next() {
+        Some(Projection::Perspective(perspective)) => ((camera_settings.base_fov
+            - perspective.fov)
+            / (camera_settings.base_fov - camera_settings.aim_fov))
+            .clamp(0.0, 1.0),
+        _ => 0.0,
+    };
+    let spread = players
+        .iter()
+        .filter_map(FireRate::last_shot)
+        .map(|last_shot| (1.0 - (now - last_shot) / SPREAD_RECOVERY_TIME).clamp(0.0, 1.0))
+        .fold(0.0, f32::max);
+    // Eased so the arms jump out and slow down as they settle
+    let gap = settings.gap.lerp(settings.gap * AIM_GAP_FACTOR, zoom)
+        + settings.fire_spread * spread * spread;
+
+    let color = if markers.iter().any(|marker| marker.is_shown(now)) {
+        settings.hit_color
+    } else {
+        settings.color
+    };
+
+    for (arm, mut node, mut background, mut visibility) in &mut arms {
+        let size = if arm.0.x == 0.0 {
+            Vec2::new(settings.arm_width, settings.arm_length)
+        } else {
+            Vec2::new(settings.arm_length, settings.arm_width)
+        };
+        let offset = arm.0 * (gap + settings.arm_length / 2.0);
+        node.width = Val::Px(size.x);
+        node.height = Val::Px(size.y);
+        node.left = Val::Px(offset.x - size.x / 2.0);
+        node.top = Val::Px(-offset.y - size.y / 2.0);
+        background.0 = color;
+        *visibility = if settings.style.has_arms() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    for (mut node, mut background, mut visibility) in &mut dots {
+        node.width = Val::Px(settings.dot_size);
+        node.height = Val::Px(settings.dot_size);
+        node.left = Val::Px(-settings.dot_size / 2.0);
+        node.top = Val::Px(-settings.dot_size / 2.0);
+        background.0 = color;
+        *visibility = if settings.style.has_dot() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -1,14 +1,31 @@
-use avian3d::prelude::*;
+use crate::ball::{Ball, DespawnAfter};
+use crate::game_assets::{CUBE_SIZE, FRAGMENT_SIZE, GameAssets};
+use crate::layers::GameLayer;
+use crate::penetration::Penetrated;
+use avian3d::{math::*, prelude::*};
 use bevy::prelude::*;
 
+/// Balls hitting a cube with at least this much impulse shatter it.
+const SHATTER_MIN_IMPULSE: Scalar = 0.2;
+/// The speed fragments fly apart from the cube's center with.
+const FRAGMENT_BURST_SPEED: Scalar = 3.0;
+const FRAGMENT_LIFETIME: f32 = 4.0;
+
 #[derive(Component)]
-struct Cube;
+pub struct Cube;
+
+/// Sent when a cube shatters, with where it was.
+#[derive(Event)]
+pub struct CubeShattered {
+    pub position: Vector,
+}
 
 #[derive(Bundle)]
 pub struct CubeBundle {
     cube: Cube,
     pub rigid_body: RigidBody,
     pub collider: Collider,
+    pub collision_layers: CollisionLayers,
     pub mesh3d: Mesh3d,
     pub mesh_material3d: MeshMaterial3d<StandardMaterial>,
     pub transform: Transform,
@@ -16,23 +33,90 @@ pub struct CubeBundle {
 }
 
 impl CubeBundle {
-    pub fn new(
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut materials: ResMut<Assets<StandardMaterial>>,
-        transform: Transform,
-    ) -> Self {
-        let r = rand::random_range(0..=255);
-        let g = rand::random_range(0..=255);
-        let b = rand::random_range(0..=255);
-
+    pub fn new(assets: &GameAssets, transform: Transform) -> Self {
         Self {
             cube: Cube,
             rigid_body: RigidBody::Dynamic,
-            collider: Collider::cuboid(1.0, 1.0, 1.0),
-            mesh3d: Mesh3d(meshes.add(Cuboid::from_length(1.0))),
-            mesh_material3d: MeshMaterial3d(materials.add(Color::srgb_u8(r, g, b))),
+            collider: Collider::cuboid(CUBE_SIZE, CUBE_SIZE, CUBE_SIZE),
+            collision_layers: CollisionLayers::new(GameLayer::Prop, LayerMask::ALL),
+            mesh3d: Mesh3d(assets.cube_mesh.clone()),
+            mesh_material3d: MeshMaterial3d(assets.random_cube_material()),
             transform,
             mass: Mass(0.1),
         }
     }
 }
+
+/// Breaks cubes hit hard enough by a ball into fragments, one for each octant of the cube. The
+/// impulse is estimated from the ball's mass and how fast the two were closing in. Balls punching
+/// through a cube hit it just as hard.
+pub fn shatter_on_hit(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    mut penetrated: EventReader<Penetrated>,
+    mut cube_shattered: EventWriter<CubeShattered>,
+    assets: Res<GameAssets>,
+    balls: Query<(&LinearVelocity, &ComputedMass), With<Ball>>,
+    cubes: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            &AngularVelocity,
+            &MeshMaterial3d<StandardMaterial>,
+        ),
+        With<Cube>,
+    >,
+) {
+    let mut shattered = Vec::new();
+
+    // Each hit, with the ball's velocity as it hit if it's since been slowed down
+    let hits = collisions
+        .read()
+        .flat_map(|CollisionStarted(entity1, entity2)| {
+            [(*entity1, *entity2, None), (*entity2, *entity1, None)]
+        })
+        .chain(
+            penetrated
+                .read()
+                .map(|hit| (hit.projectile, hit.target, Some(hit.velocity))),
+        );
+    for (ball, cube, hit_velocity) in hits {
+        let Ok((ball_velocity, ball_mass)) = balls.get(ball) else {
+            continue;
+        };
+        let Ok((transform, velocity, angular_velocity, material)) = cubes.get(cube) else {
+            continue;
+        };
+        let ball_velocity = hit_velocity.unwrap_or(ball_velocity.0);
+        let impulse = (ball_velocity - velocity.0).length() * ball_mass.value();
+        if impulse < SHATTER_MIN_IMPULSE || shattered.contains(&cube) {
+            continue;
+        }
+        shattered.push(cube);
+
+        commands.entity(cube).despawn();
+        cube_shattered.write(CubeShattered {
+            position: transform.translation,
+        });
+        for octant in 0..8 {
+            let corner = Vector::new(
+                if octant & 1 == 0 { -1.0 } else { 1.0 },
+                if octant & 2 == 0 { -1.0 } else { 1.0 },
+                if octant & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let offset = transform.rotation * corner * FRAGMENT_SIZE / 2.0;
+
+            commands.spawn((
+                RigidBody::Dynamic,
+                Collider::cuboid(FRAGMENT_SIZE, FRAGMENT_SIZE, FRAGMENT_SIZE),
+                CollisionLayers::new(GameLayer::Prop, LayerMask::ALL),
+                Mesh3d(assets.fragment_mesh.clone()),
+                material.clone(),
+                transform.with_translation(transform.translation + offset),
+                LinearVelocity(velocity.0 + offset.normalize() * FRAGMENT_BURST_SPEED),
+                *angular_velocity,
+                DespawnAfter::new(FRAGMENT_LIFETIME),
+            ));
+        }
+    }
+}
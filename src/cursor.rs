@@ -0,0 +1,108 @@
+use crate::combat::Dead;
+use crate::main_menu::GameState;
+use crate::pause::PauseState;
+use crate::player_movement::Player;
+use crate::spectator::FlyCamera;
+use bevy::prelude::*;
+use bevy::window::CursorGrabMode;
+use bevy_enhanced_input::prelude::*;
+
+/// Input for menus and other UI, live whether or not the game has the cursor.
+#[derive(Component)]
+pub struct UiContext;
+
+/// UI needing the cursor while the game isn't paused, like the survival results screen. Clicks
+/// don't grab the cursor back while there is any.
+#[derive(Component)]
+pub struct HoldsCursor;
+
+/// Grabs the cursor back for the game.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct CaptureCursor;
+
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_input_context::<UiContext>()
+            .add_systems(Startup, spawn_ui_controls)
+            .add_systems(OnEnter(GameState::InGame), grab_cursor)
+            .add_systems(Update, switch_input_contexts)
+            .add_observer(capture_cursor);
+    }
+}
+
+fn spawn_ui_controls(mut commands: Commands) {
+    commands.spawn((
+        UiContext,
+        actions!(UiContext[(Action::<CaptureCursor>::new(), bindings![MouseButton::Left])]),
+    ));
+}
+
+fn grab_cursor(mut window: Single<&mut Window>) {
+    set_cursor_grab(&mut window, true);
+}
+
+/// Run condition for systems that read the mouse as game input.
+pub fn cursor_grabbed(window: Single<&Window>) -> bool {
+    window.cursor_options.grab_mode != CursorGrabMode::None
+}
+
+pub(crate) fn set_cursor_grab(window: &mut Window, grab: bool) {
+    window.cursor_options.visible = !grab;
+    window.cursor_options.grab_mode = if grab {
+        CursorGrabMode::Locked
+    } else {
+        CursorGrabMode::None
+    };
+}
+
+/// Waits for the click to be released, so the button isn't still held down once the gameplay
+/// contexts are back on, which would throw a ball. Clicks in the pause and main menus, and on UI
+/// that [`HoldsCursor`], are left to them.
+fn capture_cursor(
+    _trigger: Trigger<Completed<CaptureCursor>>,
+    pause_state: Option<Res<State<PauseState>>>,
+    holders: Query<(), With<HoldsCursor>>,
+    mut window: Single<&mut Window>,
+) {
+    if pause_state.is_some_and(|pause_state| *pause_state == PauseState::Running)
+        && holders.is_empty()
+        && window.cursor_options.grab_mode == CursorGrabMode::None
+    {
+        set_cursor_grab(&mut window, true);
+    }
+}
+
+/// Turns the gameplay contexts off while paused or the cursor is free, so clicking around menus
+/// doesn't throw balls. Players stay switched off while dead, and while a fly camera is
+/// controlled instead.
+fn switch_input_contexts(
+    mut commands: Commands,
+    window: Single<&Window>,
+    pause_state: Option<Res<State<PauseState>>>,
+    players: Query<(Entity, &ContextActivity<Player>, Has<Dead>)>,
+    fly_cameras: Query<(Entity, &ContextActivity<FlyCamera>)>,
+) {
+    let in_game = pause_state.is_some_and(|pause_state| *pause_state == PauseState::Running)
+        && window.cursor_options.grab_mode != CursorGrabMode::None;
+
+    let player_active = in_game && fly_cameras.is_empty();
+    for (player, activity, dead) in &players {
+        let active = player_active && !dead;
+        if **activity != active {
+            commands
+                .entity(player)
+                .insert(ContextActivity::<Player>::new(active));
+        }
+    }
+
+    for (fly_camera, activity) in &fly_cameras {
+        if **activity != in_game {
+            commands
+                .entity(fly_camera)
+                .insert(ContextActivity::<FlyCamera>::new(in_game));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::weather::Weather;
+use bevy::pbr::light_consts::lux;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use std::f32::consts::PI;
+
+/// The direction the sun travels across the sky in, as an angle around the vertical axis.
+const SUN_YAW: f32 = 0.6;
+const SUNRISE_HOUR: f32 = 6.0;
+const SUNSET_HOUR: f32 = 18.0;
+/// The sun's color when it's just above the horizon. It's white at noon.
+const LOW_SUN_COLOR: Color = Color::srgb(1.0, 0.55, 0.3);
+const DAY_AMBIENT: f32 = 400.0;
+const NIGHT_AMBIENT: f32 = 40.0;
+const DAY_AMBIENT_COLOR: Color = Color::WHITE;
+const NIGHT_AMBIENT_COLOR: Color = Color::srgb(0.4, 0.5, 1.0);
+
+/// The time of day in the game world, which moves the sun and changes the light.
+#[derive(Resource)]
+pub struct TimeOfDay {
+    /// From 0 to 24.
+    pub hours: f32,
+    /// How long a full day takes, in seconds of game time.
+    pub day_length: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hours: 9.0,
+            day_length: 600.0,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// How high the sun is, from 0 at sunrise to a quarter turn at noon and half a turn at
+    /// sunset. Negative or past half a turn at night.
+    fn sun_angle(&self) -> f32 {
+        (self.hours - SUNRISE_HOUR) / (SUNSET_HOUR - SUNRISE_HOUR) * PI
+    }
+}
+
+/// Moves the time of day forward or back by an hour.
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct ShiftTimeOfDay;
+
+#[derive(Component)]
+struct Sun;
+
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>()
+            .add_systems(Startup, spawn_sun)
+            .add_systems(Update, (advance_time_of_day, update_sun).chain())
+            .add_observer(shift_time_of_day);
+    }
+}
+
+fn spawn_sun(mut commands: Commands) {
+    commands.spawn((
+        Sun,
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+    ));
+}
+
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    let hours = time.delta_secs() / time_of_day.day_length * 24.0;
+    time_of_day.hours = (time_of_day.hours + hours).rem_euclid(24.0);
+}
+
+fn shift_time_of_day(
+    trigger: Trigger<Started<ShiftTimeOfDay>>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    time_of_day.hours = (time_of_day.hours + trigger.value.signum()).rem_euclid(24.0);
+    info!("Time of day is now {:.1}h", time_of_day.hours);
+}
+
+/// Points the sun according to the time of day, warming its color near the horizon and dimming
+/// it and the ambient light at night and under clouds.
+fn update_sun(
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<Weather>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut suns: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+) {
+    let angle = time_of_day.sun_angle();
+    // 0 with the sun below the horizon, 1 with it straight up
+    let daylight = angle.sin().max(0.0);
+    let brightness = daylight * weather.kind.daylight();
+
+    for (mut transform, mut light) in &mut suns {
+        transform.rotation = Quat::from_rotation_y(SUN_YAW) * Quat::from_rotation_x(-angle);
+        light.illuminance = lux::AMBIENT_DAYLIGHT * brightness;
+        light.color = LOW_SUN_COLOR.mix(&Color::WHITE, daylight);
+    }
+
+    ambient_light.brightness = NIGHT_AMBIENT.lerp(DAY_AMBIENT, brightness);
+    ambient_light.color = NIGHT_AMBIENT_COLOR.mix(&DAY_AMBIENT_COLOR, daylight);
+}
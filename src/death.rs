@@ -0,0 +1,135 @@
+use crate::checkpoint::Respawning;
+use crate::combat::Died;
+use crate::main_menu::GameMode;
+use crate::player_movement::Player;
+use bevy::prelude::*;
+
+/// How long the death screen stays up before the player respawns, in seconds.
+const RESPAWN_DELAY: f32 = 3.0;
+/// How long the death screen takes to fade in, and how dark it gets.
+const DEATH_FADE_TIME: f32 = 1.0;
+const DEATH_FADE_ALPHA: f32 = 0.6;
+
+/// Set on a player from when they die until they start [`Respawning`], with the elapsed
+/// seconds they died at. They lie ragdolled with their input off meanwhile, while the death
+/// screen counts down.
+#[derive(Component)]
+pub struct Downed {
+    died_at: f32,
+}
+
+/// Darkens the screen and counts down to the respawn while the player is [`Downed`].
+#[derive(Component)]
+struct DeathScreen;
+
+#[derive(Component)]
+struct RespawnCountdown;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_death_screen).add_systems(
+            Update,
+            (
+                // Game modes ending when the player dies respawn them themselves
+                down_dead_players.run_if(resource_equals(GameMode::Sandbox)),
+                respawn_downed_players,
+                update_death_screen,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn spawn_death_screen(mut commands: Commands) {
+    commands.spawn((
+        DeathScreen,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.3, 0.0, 0.0, 0.0)),
+        // Above the HUD, but below the respawn's own fade to black
+        GlobalZIndex(4),
+        Pickable::IGNORE,
+        Visibility::Hidden,
+        children![
+            (
+                Text::new("You died"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+            ),
+            (RespawnCountdown, Text::default()),
+        ],
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn down_dead_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut died: EventReader<Died>,
+    players: Query<(), (With<Player>, Without<Respawning>, Without<Downed>)>,
+) {
+    for event in died.read() {
+        if players.contains(event.entity) {
+            commands.entity(event.entity).insert(Downed {
+                died_at: time.elapsed_secs(),
+            });
+        }
+    }
+}
+
+fn respawn_downed_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    players: Query<(Entity, &Downed)>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, downed) in &players {
+        if now - downed.died_at >= RESPAWN_DELAY {
+            commands
+                .entity(entity)
+                .remove::<Downed>()
+                .insert(Respawning::new(now));
+        }
+    }
+}
+
+fn update_death_screen(
+    time: Res<Time>,
+    players: Query<&Downed>,
+    mut screens: Query<(&mut Visibility, &mut BackgroundColor), With<DeathScreen>>,
+    mut countdowns: Query<&mut Text, With<RespawnCountdown>>,
+) {
+    let now = time.elapsed_secs();
+    // The player who died first respawns first
+    let died_at = players.iter().map(|downed| downed.died_at).reduce(f32::min);
+
+    for (mut visibility, mut color) in &mut screens {
+        *visibility = match died_at {
+            Some(_) => Visibility::Inherited,
+            None => Visibility::Hidden,
+        };
+        let fade = died_at.map_or(0.0, |died_at| {
+            ((now - died_at) / DEATH_FADE_TIME).clamp(0.0, 1.0)
+        });
+        color.0.set_alpha(fade * DEATH_FADE_ALPHA);
+    }
+    let Some(died_at) = died_at else {
+        return;
+    };
+    let remaining = (RESPAWN_DELAY - (now - died_at)).ceil().max(1.0);
+    for mut text in &mut countdowns {
+        text.0 = format!("Respawning in {remaining}");
+    }
+}
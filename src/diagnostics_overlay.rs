@@ -0,0 +1,119 @@
+use avian3d::prelude::*;
+use bevy::diagnostic::{
+    DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use std::fmt::Write;
+
+const OVERLAY_MARGIN: f32 = 12.0;
+
+/// Shows or hides the diagnostics overlay.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleDiagnostics;
+
+/// Text in the top left corner with frame times, entity counts and physics stats, for
+/// stress-testing. Hidden until toggled on.
+#[derive(Component)]
+struct DiagnosticsOverlay;
+
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FrameTimeDiagnosticsPlugin::default(),
+            EntityCountDiagnosticsPlugin,
+        ))
+        .add_systems(Startup, spawn_overlay)
+        .add_systems(Update, update_overlay)
+        .add_observer(toggle_overlay);
+    }
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        DiagnosticsOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(OVERLAY_MARGIN),
+            left: Val::Px(OVERLAY_MARGIN),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.5)),
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_overlay(
+    _trigger: Trigger<Started<ToggleDiagnostics>>,
+    mut overlays: Query<&mut Visibility, With<DiagnosticsOverlay>>,
+) {
+    for mut visibility in &mut overlays {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+/// Fills in the overlay while it's shown. The physics engine has no simulation islands, so
+/// awake bodies stand in for how much of the world is actually being simulated.
+fn update_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    collisions: Collisions,
+    bodies: Query<(&RigidBody, Has<Sleeping>)>,
+    mut overlays: Query<(&mut Text, &Visibility), With<DiagnosticsOverlay>>,
+) {
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or(0.0)
+    };
+
+    for (mut text, visibility) in &mut overlays {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let (mut dynamic, mut kinematic, mut fixed, mut awake) = (0, 0, 0, 0);
+        for (rigid_body, is_sleeping) in &bodies {
+            match rigid_body {
+                RigidBody::Dynamic => dynamic += 1,
+                RigidBody::Kinematic => kinematic += 1,
+                RigidBody::Static => fixed += 1,
+            }
+            if !rigid_body.is_static() && !is_sleeping {
+                awake += 1;
+            }
+        }
+        let contacts = collisions
+            .iter()
+            .filter(|contacts| contacts.is_touching())
+            .count();
+
+        text.0.clear();
+        let _ = writeln!(
+            text.0,
+            "{:.0} fps ({:.2} ms)",
+            smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+            smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        );
+        let _ = writeln!(
+            text.0,
+            "{:.0} entities",
+            smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+        );
+        let _ = writeln!(
+            text.0,
+            "{dynamic} dynamic, {kinematic} kinematic, {fixed} static bodies"
+        );
+        let _ = writeln!(text.0, "{awake} awake bodies");
+        let _ = write!(text.0, "{contacts} contacts");
+    }
+}
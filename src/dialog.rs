@@ -0,0 +1,303 @@
+use crate::carry::Interacted;
+use crate::cursor::{HoldsCursor, set_cursor_grab};
+use crate::ui::ReleasedButtons;
+use crate::wiring::{Signal, SignalTargets};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// A conversation read from a `.dialog.ron` file: nodes of text paged through one page at a
+/// time, ending in choices leading to other nodes.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Dialog {
+    /// The name shown above the text.
+    pub speaker: String,
+    /// The node the conversation starts at.
+    pub start: String,
+    pub nodes: HashMap<String, DialogNode>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogNode {
+    pub pages: Vec<String>,
+    /// Shown on the last page. Without any, the last page just ends the conversation.
+    #[serde(default)]
+    pub choices: Vec<DialogChoice>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogChoice {
+    pub text: String,
+    /// The node the conversation goes on at, or `None` to end it.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Sent as a [`DialogEvent`] when the choice is picked, for quests and the like to react to.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// Sent as a [`Signal`] to the objects the NPC is wired to when the choice is picked, like
+    /// opening a door.
+    #[serde(default)]
+    pub signal: Option<bool>,
+}
+
+/// A friendly character the player can talk to through the interact action.
+#[derive(Component)]
+#[require(SignalTargets)]
+pub struct Npc {
+    pub dialog: Handle<Dialog>,
+}
+
+/// Sent when a choice with an [`event`](DialogChoice::event) is picked.
+#[derive(Event)]
+pub struct DialogEvent {
+    pub event: String,
+}
+
+/// The conversation going on, at a page of one of its nodes.
+#[derive(Resource)]
+struct ActiveDialog {
+    npc: Entity,
+    dialog: Handle<Dialog>,
+    node: String,
+    page: usize,
+}
+
+#[derive(Component)]
+struct DialogBox;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum DialogButton {
+    /// Turns to the next page, or ends the conversation on the last page of a node without
+    /// choices.
+    Next,
+    Choice(usize),
+}
+
+#[derive(Default)]
+struct DialogLoader;
+
+impl AssetLoader for DialogLoader {
+    type Asset = Dialog;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Dialog, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialog.ron"]
+    }
+}
+
+pub struct DialogPlugin;
+
+impl Plugin for DialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Dialog>()
+            .init_asset_loader::<DialogLoader>()
+            .add_event::<DialogEvent>()
+            .add_systems(
+                Update,
+                (
+                    end_dialog_without_npc,
+                    press_dialog_buttons,
+                    show_dialog.run_if(resource_exists_and_changed::<ActiveDialog>),
+                )
+                    .chain(),
+            )
+            .add_observer(talk_to_npc);
+    }
+}
+
+/// Starts the conversation of an NPC a player interacts with, freeing the cursor to click
+/// through it.
+fn talk_to_npc(
+    trigger: Trigger<Interacted>,
+    mut commands: Commands,
+    mut window: Single<&mut Window>,
+    dialogs: Res<Assets<Dialog>>,
+    npcs: Query<&Npc>,
+) {
+    let Ok(npc) = npcs.get(trigger.target()) else {
+        return;
+    };
+    let Some(dialog) = dialogs.get(&npc.dialog) else {
+        return;
+    };
+
+    set_cursor_grab(&mut window, false);
+    commands.insert_resource(ActiveDialog {
+        npc: trigger.target(),
+        dialog: npc.dialog.clone(),
+        node: dialog.start.clone(),
+        page: 0,
+    });
+}
+
+/// Redraws the dialog box for the current page: the speaker, the text, and the choices on the
+/// last page.
+fn show_dialog(
+    mut commands: Commands,
+    active: Res<ActiveDialog>,
+    dialogs: Res<Assets<Dialog>>,
+    boxes: Query<Entity, With<DialogBox>>,
+) {
+    for dialog_box in &boxes {
+        commands.entity(dialog_box).despawn();
+    }
+    let Some(dialog) = dialogs.get(&active.dialog) else {
+        return;
+    };
+    let Some(node) = dialog.nodes.get(&active.node) else {
+        warn!("Dialog has no node named {:?}", active.node);
+        return;
+    };
+
+    let last_page = active.page + 1 >= node.pages.len();
+    let buttons: Vec<_> = if last_page && !node.choices.is_empty() {
+        node.choices
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| (DialogButton::Choice(index), choice.text.as_str()))
+            .collect()
+    } else if last_page {
+        vec![(DialogButton::Next, "Goodbye")]
+    } else {
+        vec![(DialogButton::Next, "Next")]
+    };
+
+    commands
+        .spawn((
+            DialogBox,
+            HoldsCursor,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(96.0),
+                left: Val::Percent(20.0),
+                width: Val::Percent(60.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.8)),
+            BorderRadius::all(Val::Px(6.0)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(dialog.speaker.clone()),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.3)),
+            ));
+            parent.spawn(Text::new(
+                node.pages.get(active.page).cloned().unwrap_or_default(),
+            ));
+            for (button, label) in buttons {
+                parent
+                    .spawn((
+                        button,
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                    ))
+                    .with_child(Text::new(label));
+            }
+        });
+}
+
+/// Turns the page, or follows the choice clicked, ending the conversation where it leads
+/// nowhere.
+#[allow(clippy::too_many_arguments)]
+fn press_dialog_buttons(
+    mut commands: Commands,
+    mut buttons: ReleasedButtons<DialogButton>,
+    active: Option<ResMut<ActiveDialog>>,
+    dialogs: Res<Assets<Dialog>>,
+    npcs: Query<&SignalTargets, With<Npc>>,
+    boxes: Query<Entity, With<DialogBox>>,
+    mut dialog_events: EventWriter<DialogEvent>,
+    mut window: Single<&mut Window>,
+) {
+    let Some(mut active) = active else {
+        return;
+    };
+
+    let Some(button) = buttons.released() else {
+        return;
+    };
+
+    let node = dialogs
+        .get(&active.dialog)
+        .and_then(|dialog| dialog.nodes.get(&active.node));
+    let next = match (button, node) {
+        (DialogButton::Next, Some(node)) if active.page + 1 < node.pages.len() => {
+            active.page += 1;
+            return;
+        }
+        (DialogButton::Choice(index), Some(node)) => {
+            let Some(choice) = node.choices.get(index) else {
+                return;
+            };
+            if let Some(event) = &choice.event {
+                dialog_events.write(DialogEvent {
+                    event: event.clone(),
+                });
+            }
+            if let (Some(on), Ok(targets)) = (choice.signal, npcs.get(active.npc)) {
+                // Triggering no targets at all would reach every signal observer
+                if !targets.0.is_empty() {
+                    commands.trigger_targets(Signal { on }, targets.0.clone());
+                }
+            }
+            choice.next.clone()
+        }
+        _ => None,
+    };
+
+    match next {
+        Some(next) => {
+            active.node = next;
+            active.page = 0;
+        }
+        None => {
+            commands.remove_resource::<ActiveDialog>();
+            for dialog_box in &boxes {
+                commands.entity(dialog_box).despawn();
+            }
+            set_cursor_grab(&mut window, true);
+        }
+    }
+}
+
+/// Ends the conversation once the NPC is gone, like when the level is reloaded or the game left
+/// for the main menu.
+fn end_dialog_without_npc(
+    mut commands: Commands,
+    active: Option<Res<ActiveDialog>>,
+    npcs: Query<(), With<Npc>>,
+    boxes: Query<Entity, With<DialogBox>>,
+) {
+    if active.is_some_and(|active| !npcs.contains(active.npc)) {
+        commands.remove_resource::<ActiveDialog>();
+        for dialog_box in &boxes {
+            commands.entity(dialog_box).despawn();
+        }
+    }
+}
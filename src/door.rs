@@ -0,0 +1,80 @@
+use crate::carry::Interacted;
+use crate::wiring::Signal;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// A kinematic door sliding between where it was placed and an offset from there. Players open
+/// and close it by interacting with it, and [`Signal`]s open it while they're on.
+///
+/// Like platforms, doors are moved through their [`LinearVelocity`], so they push characters
+/// and props out of the way instead of teleporting into them.
+#[derive(Component)]
+pub struct Door {
+    closed: Vector,
+    offset: Vector,
+    speed: Scalar,
+    open: bool,
+    /// Locked doors only open by signal, not by hand.
+    locked: bool,
+}
+
+impl Door {
+    /// Creates a closed door at `closed`, sliding `offset` away to open at `speed`.
+    pub fn new(closed: Vector, offset: Vector, speed: Scalar) -> Self {
+        Self {
+            closed,
+            offset,
+            speed,
+            open: false,
+            locked: false,
+        }
+    }
+
+    pub fn with_locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    fn target(&self) -> Vector {
+        if self.open {
+            self.closed + self.offset
+        } else {
+            self.closed
+        }
+    }
+}
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, move_doors)
+            .add_observer(use_door)
+            .add_observer(signal_door);
+    }
+}
+
+/// Opens or closes a door a player interacts with, unless it's locked.
+fn use_door(trigger: Trigger<Interacted>, mut doors: Query<&mut Door>) {
+    let Ok(mut door) = doors.get_mut(trigger.target()) else {
+        return;
+    };
+    if !door.locked {
+        door.open = !door.open;
+    }
+}
+
+fn signal_door(trigger: Trigger<Signal>, mut doors: Query<&mut Door>) {
+    if let Ok(mut door) = doors.get_mut(trigger.target()) {
+        door.open = trigger.on;
+    }
+}
+
+fn move_doors(time: Res<Time>, mut query: Query<(&Position, &Door, &mut LinearVelocity)>) {
+    for (position, door, mut velocity) in &mut query {
+        let offset = door.target() - position.0;
+        // Stop right at the end instead of overshooting back and forth
+        let speed = door.speed.min(offset.length() / time.delta_secs());
+        velocity.0 = offset.normalize_or_zero() * speed;
+    }
+}
@@ -0,0 +1,283 @@
+use crate::combat::{Damage, DamageKind, Dead, Died, Health};
+use crate::game_assets::{ENEMY_LENGTH, ENEMY_RADIUS, GameAssets};
+use crate::layers::GameLayer;
+use crate::level::LevelEntity;
+use crate::main_menu::GameMode;
+use crate::navigation::{NavGrid, PathFollower};
+use crate::player_movement::{Knockback, Player};
+use crate::ragdoll::RagdollOnDeath;
+use crate::spawn_point::{SpawnKind, SpawnPoint};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_tnua::prelude::*;
+
+const ENEMY_HEALTH: f32 = 50.0;
+/// How high above the ground enemies float, like the player does standing.
+const ENEMY_FLOAT_HEIGHT: Scalar = 1.0;
+/// How close an enemy has to get to where it last saw a player to give up the chase.
+const LAST_SEEN_RADIUS: Scalar = 1.0;
+/// How long dead enemies lie around before they're despawned, in seconds.
+const CORPSE_TIME: f32 = 10.0;
+
+/// A character chasing the nearest player it can see, and hurting them on contact.
+#[derive(Component, Clone, Copy)]
+pub struct Enemy {
+    /// How close a player has to be to be chased, in meters.
+    pub sight_range: Scalar,
+    pub speed: Scalar,
+    pub attack_damage: f32,
+    /// The shortest time between attacks, in seconds.
+    pub attack_interval: f32,
+    /// How hard attacks shove the player away.
+    pub attack_knockback: Scalar,
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self {
+            sight_range: 25.0,
+            speed: 5.0,
+            attack_damage: 10.0,
+            attack_interval: 1.0,
+            attack_knockback: 6.0,
+        }
+    }
+}
+
+/// When the enemy last attacked, in elapsed seconds.
+#[derive(Component, Default)]
+struct LastAttack(Option<f32>);
+
+/// Where the enemy last saw the player it's chasing, which it keeps heading to after losing
+/// sight of them until it gets there.
+#[derive(Component, Default)]
+struct LastSeen(Option<Vector>);
+
+/// Counts down until a dead enemy is despawned.
+#[derive(Component)]
+struct Corpse(Timer);
+
+#[derive(Bundle)]
+pub struct EnemyBundle {
+    enemy: Enemy,
+    last_attack: LastAttack,
+    last_seen: LastSeen,
+    path_follower: PathFollower,
+    health: Health,
+    rigid_body: RigidBody,
+    collider: Collider,
+    collision_layers: CollisionLayers,
+    colliding_entities: CollidingEntities,
+    locked_axes: LockedAxes,
+    controller: TnuaController,
+    ragdoll_on_death: RagdollOnDeath,
+    mesh3d: Mesh3d,
+    mesh_material3d: MeshMaterial3d<StandardMaterial>,
+    transform: Transform,
+}
+
+impl EnemyBundle {
+    pub fn new(assets: &GameAssets, transform: Transform) -> Self {
+        Self {
+            enemy: Enemy::default(),
+            last_attack: LastAttack::default(),
+            last_seen: LastSeen::default(),
+            path_follower: PathFollower::default(),
+            health: Health(ENEMY_HEALTH),
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::capsule(ENEMY_RADIUS, ENEMY_LENGTH),
+            collision_layers: CollisionLayers::new(GameLayer::Enemy, LayerMask::ALL),
+            colliding_entities: CollidingEntities::default(),
+            locked_axes: LockedAxes::ROTATION_LOCKED,
+            controller: TnuaController::default(),
+            ragdoll_on_death: RagdollOnDeath,
+            mesh3d: Mesh3d(assets.enemy_mesh.clone()),
+            mesh_material3d: MeshMaterial3d(assets.enemy_material.clone()),
+            transform,
+        }
+    }
+
+    pub fn with_enemy(mut self, enemy: Enemy) -> Self {
+        self.enemy = enemy;
+        self
+    }
+}
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                chase_players,
+                attack_on_contact,
+                leave_corpses,
+                remove_corpses,
+            ),
+        )
+        // After transform propagation, so newly spawned points are already in place
+        .add_systems(
+            PostUpdate,
+            spawn_enemies
+                .after(TransformSystem::TransformPropagate)
+                .run_if(resource_equals(GameMode::Sandbox)),
+        );
+    }
+}
+
+/// Spawns an enemy at every new enemy spawn point, such as when a level is spawned. They go
+/// away with the level. Game modes with enemies of their own spawn them at the points instead.
+fn spawn_enemies(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    added: Query<(&SpawnPoint, &GlobalTransform), Added<SpawnPoint>>,
+) {
+    for (point, transform) in &added {
+        if point.kind == SpawnKind::Enemy {
+            commands.spawn((
+                LevelEntity,
+                EnemyBundle::new(&assets, transform.compute_transform()),
+            ));
+        }
+    }
+}
+
+/// Walks enemies toward the nearest living player in sight, around whatever's in the way. An
+/// enemy that loses sight of its player goes on to where it last saw them, and stops there.
+fn chase_players(
+    time: Res<Time>,
+    nav_grid: Res<NavGrid>,
+    spatial_query: SpatialQuery,
+    mut enemies: Query<
+        (
+            &Enemy,
+            &Position,
+            &mut LastSeen,
+            &mut PathFollower,
+            &mut TnuaController,
+        ),
+        Without<Dead>,
+    >,
+    players: Query<&Position, (With<Player>, Without<Dead>)>,
+) {
+    let now = time.elapsed_secs();
+    // Only the level itself blocks sight, so props and other characters in the way are routed
+    // around rather than making the enemy give up
+    let filter = SpatialQueryFilter::from_mask(GameLayer::World);
+
+    for (enemy, position, mut last_seen, mut path_follower, mut controller) in &mut enemies {
+        let in_sight = |player: Vector| {
+            let offset = player - position.0;
+            let Ok(direction) = Dir3::new(offset) else {
+                return true;
+            };
+            spatial_query
+                .cast_ray(position.0, direction, offset.length(), true, &filter)
+                .is_none()
+        };
+        let target = players
+            .iter()
+            .map(|player| player.0)
+            .filter(|&player| player.distance(position.0) <= enemy.sight_range && in_sight(player))
+            .min_by(|a, b| {
+                a.distance_squared(position.0)
+                    .total_cmp(&b.distance_squared(position.0))
+            });
+        if target.is_some() {
+            last_seen.0 = target;
+        } else if last_seen
+            .0
+            .is_some_and(|seen| (seen - position.0).xz().length() < LAST_SEEN_RADIUS)
+        {
+            last_seen.0 = None;
+        }
+        let direction = last_seen
+            .0
+            .and_then(|goal| path_follower.steer(&nav_grid, position.0, goal, now));
+
+        controller.basis(TnuaBuiltinWalk {
+            desired_velocity: direction.map_or(Vector::ZERO, |direction| direction * enemy.speed),
+            desired_forward: direction,
+            float_height: ENEMY_FLOAT_HEIGHT,
+            ..default()
+        });
+    }
+}
+
+/// Hurts and shoves away players touching an enemy, no more often than its attack interval.
+fn attack_on_contact(
+    time: Res<Time>,
+    mut damage: EventWriter<Damage>,
+    mut knockback: EventWriter<Knockback>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Position,
+            &CollidingEntities,
+            &mut LastAttack,
+        ),
+        Without<Dead>,
+    >,
+    players: Query<&Position, (With<Player>, Without<Dead>)>,
+) {
+    let now = time.elapsed_secs();
+
+    for (entity, enemy, position, colliding, mut last_attack) in &mut enemies {
+        if last_attack
+            .0
+            .is_some_and(|last| now - last < enemy.attack_interval)
+        {
+            continue;
+        }
+        let Some((player, player_position)) = colliding
+            .iter()
+            .find_map(|&entity| Some((entity, players.get(entity).ok()?)))
+        else {
+            continue;
+        };
+
+        last_attack.0 = Some(now);
+        damage.write(Damage {
+            target: player,
+            amount: enemy.attack_damage,
+            kind: DamageKind::Melee,
+            source: Some(entity),
+        });
+        let away = (player_position.0 - position.0)
+            .with_y(0.0)
+            .normalize_or_zero();
+        knockback.write(Knockback {
+            entity: player,
+            impulse: (away + Vector::Y * 0.5) * enemy.attack_knockback,
+        });
+    }
+}
+
+fn leave_corpses(
+    mut commands: Commands,
+    mut died: EventReader<Died>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    for event in died.read() {
+        if enemies.contains(event.entity) {
+            commands
+                .entity(event.entity)
+                .insert(Corpse(Timer::from_seconds(CORPSE_TIME, TimerMode::Once)));
+        }
+    }
+}
+
+fn remove_corpses(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut corpses: Query<(Entity, &mut Corpse)>,
+) {
+    for (entity, mut corpse) in &mut corpses {
+        if corpse.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::ball::DespawnAfter;
+use crate::camera::CameraRig;
+use crate::combat::{Damage, DamageKind, Died, Health};
+use crate::game_assets::GameAssets;
+use crate::layers::GameLayer;
+use crate::player_movement::{Knockback, Player};
+use crate::wiring::Signal;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// The impulse applied to bodies at the center of an explosion of the default strength. It
+/// falls off to nothing at the edge of the radius.
+const EXPLOSION_IMPULSE: Scalar = 1.0;
+/// The speed characters at the center of an explosion get knocked back with by default.
+const EXPLOSION_KNOCKBACK: Scalar = 20.0;
+const EXPLOSION_DAMAGE: f32 = 50.0;
+/// How long the flash of an explosion stays visible, in seconds.
+const EXPLOSION_FLASH_TIME: f32 = 0.15;
+/// Cameras closer than this many explosion radii shake, more the closer they are.
+const SHAKE_RANGE: f32 = 4.0;
+const BARREL_RADIUS: f32 = 0.4;
+const BARREL_HEIGHT: f32 = 1.2;
+const BARREL_MASS: f32 = 20.0;
+const BARREL_HEALTH: f32 = 20.0;
+const BARREL_EXPLOSION_RADIUS: Scalar = 6.0;
+
+/// Sent when something explodes, pushing away rigid bodies, knocking back characters and
+/// hurting everything with [`Health`] in range, and shaking nearby cameras. Anything can send
+/// these, like grenades, barrels or scripted events.
+#[derive(Event, Clone, Copy)]
+pub struct Explosion {
+    pub position: Vector,
+    pub radius: Scalar,
+    /// The impulse on bodies at the center, falling off to nothing at the edge.
+    pub impulse: Scalar,
+    /// The knockback speed of characters at the center, falling off like the impulse.
+    pub knockback: Scalar,
+    /// The damage at the center, falling off like the impulse.
+    pub damage: f32,
+    /// Who set the explosion off, credited with its damage. See [`Damage::source`].
+    pub source: Option<Entity>,
+}
+
+impl Explosion {
+    /// An explosion of the default strength.
+    pub fn new(position: Vector, radius: Scalar) -> Self {
+        Self {
+            position,
+            radius,
+            impulse: EXPLOSION_IMPULSE,
+            knockback: EXPLOSION_KNOCKBACK,
+            damage: EXPLOSION_DAMAGE,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+/// A barrel that explodes when its health runs out or it gets a [`Signal`], setting off other
+/// barrels caught in the blast. Whoever finished it off is credited with its explosion.
+#[derive(Component)]
+pub struct ExplosiveBarrel;
+
+#[derive(Bundle)]
+pub struct ExplosiveBarrelBundle {
+    explosive_barrel: ExplosiveBarrel,
+    pub health: Health,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub collision_layers: CollisionLayers,
+    pub mass: Mass,
+    pub mesh3d: Mesh3d,
+    pub mesh_material3d: MeshMaterial3d<StandardMaterial>,
+    pub transform: Transform,
+}
+
+impl ExplosiveBarrelBundle {
+    pub fn new(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        transform: Transform,
+    ) -> Self {
+        Self {
+            explosive_barrel: ExplosiveBarrel,
+            health: Health(BARREL_HEALTH),
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::cylinder(BARREL_RADIUS, BARREL_HEIGHT),
+            collision_layers: CollisionLayers::new(GameLayer::Prop, LayerMask::ALL),
+            mass: Mass(BARREL_MASS),
+            mesh3d: Mesh3d(meshes.add(Cylinder::new(BARREL_RADIUS, BARREL_HEIGHT))),
+            mesh_material3d: MeshMaterial3d(materials.add(Color::srgb(0.75, 0.1, 0.05))),
+            transform,
+        }
+    }
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Explosion>()
+            .add_systems(Update, (explode_dead_barrels, explode).chain())
+            .add_observer(signal_barrel);
+    }
+}
+
+fn explode_dead_barrels(
+    mut commands: Commands,
+    mut died: EventReader<Died>,
+    mut explosions: EventWriter<Explosion>,
+    barrels: Query<&Position, With<ExplosiveBarrel>>,
+) {
+    for event in died.read() {
+        if let Ok(position) = barrels.get(event.entity) {
+            let mut explosion = Explosion::new(position.0, BARREL_EXPLOSION_RADIUS);
+            explosion.source = event.source;
+            explosions.write(explosion);
+            commands.entity(event.entity).despawn();
+        }
+    }
+}
+
+fn signal_barrel(
+    trigger: Trigger<Signal>,
+    mut commands: Commands,
+    mut explosions: EventWriter<Explosion>,
+    barrels: Query<&Position, With<ExplosiveBarrel>>,
+) {
+    if !trigger.on {
+        return;
+    }
+    if let Ok(position) = barrels.get(trigger.target()) {
+        explosions.write(Explosion::new(position.0, BARREL_EXPLOSION_RADIUS));
+        commands.entity(trigger.target()).despawn();
+    }
+}
+
+/// Flashes explosions, pushes and hurts everything in their radius with a linear falloff, and
+/// shakes the cameras near them.
+#[allow(clippy::too_many_arguments)]
+fn explode(
+    mut commands: Commands,
+    mut explosions: EventReader<Explosion>,
+    mut knockback: EventWriter<Knockback>,
+    mut damage: EventWriter<Damage>,
+    spatial_query: SpatialQuery,
+    assets: Res<GameAssets>,
+    bodies: Query<(&RigidBody, &Position)>,
+    targets: Query<(), With<Health>>,
+    players: Query<(), With<Player>>,
+    mut cameras: Query<(&mut CameraRig, &GlobalTransform)>,
+) {
+    for explosion in explosions.read() {
+        commands.spawn((
+            Mesh3d(assets.explosion_mesh.clone()),
+            MeshMaterial3d(assets.explosion_material.clone()),
+            Transform::from_translation(explosion.position)
+                .with_scale(Vec3::splat(explosion.radius)),
+            DespawnAfter::new(EXPLOSION_FLASH_TIME),
+        ));
+
+        let hits = spatial_query.shape_intersections(
+            &Collider::sphere(explosion.radius),
+            explosion.position,
+            Quaternion::IDENTITY,
+            &SpatialQueryFilter::default(),
+        );
+        for entity in hits {
+            let Ok((rigid_body, position)) = bodies.get(entity) else {
+                continue;
+            };
+            let offset = position.0 - explosion.position;
+            let falloff = (1.0 - offset.length() / explosion.radius).clamp(0.0, 1.0);
+            // Push straight up at the center instead of not at all
+            let direction = offset.try_normalize().unwrap_or(Vector::Y);
+
+            if targets.contains(entity) {
+                damage.write(Damage {
+                    target: entity,
+                    amount: explosion.damage * falloff,
+                    kind: DamageKind::Explosion,
+                    source: explosion.source,
+                });
+            }
+            if players.contains(entity) {
+                knockback.write(Knockback {
+                    entity,
+                    impulse: direction * explosion.knockback * falloff,
+                });
+            } else if rigid_body.is_dynamic() {
+                commands.entity(entity).insert(ExternalImpulse::new(
+                    direction * explosion.impulse * falloff,
+                ));
+            }
+        }
+
+        for (mut rig, transform) in &mut cameras {
+            let distance = transform.translation().distance(explosion.position);
+            let range = explosion.radius * SHAKE_RANGE;
+            rig.shake((1.0 - distance / range).max(0.0));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::ball::BallKind;
+use bevy::prelude::*;
+
+pub const BALL_RADIUS: f32 = 0.1;
+pub const CUBE_SIZE: f32 = 1.0;
+/// The size of the pieces a cube shatters into, one for each octant of the cube.
+pub const FRAGMENT_SIZE: f32 = CUBE_SIZE / 2.0;
+pub const GRENADE_RADIUS: f32 = 0.15;
+/// The width, thickness and length of placed ramps.
+pub const RAMP_SIZE: Vec3 = Vec3::new(2.0, 0.2, 4.0);
+pub const LAMP_RADIUS: f32 = 0.15;
+/// The radius and the length between the end caps of an enemy's capsule.
+pub const ENEMY_RADIUS: f32 = 0.4;
+pub const ENEMY_LENGTH: f32 = 1.0;
+pub const CABLE_RADIUS: f32 = 0.02;
+/// How many differently colored materials spawned cubes pick from.
+const CUBE_COLORS: usize = 16;
+
+/// Meshes and materials shared by everything spawned at runtime, so spawning doesn't add new
+/// assets every time.
+#[derive(Resource)]
+pub struct GameAssets {
+    pub ball_mesh: Handle<Mesh>,
+    ball_material: Handle<StandardMaterial>,
+    bouncy_ball_material: Handle<StandardMaterial>,
+    homing_ball_material: Handle<StandardMaterial>,
+    pub cube_mesh: Handle<Mesh>,
+    cube_materials: Vec<Handle<StandardMaterial>>,
+    /// Fragments keep the material of the cube they broke off from.
+    pub fragment_mesh: Handle<Mesh>,
+    pub grenade_mesh: Handle<Mesh>,
+    pub grenade_material: Handle<StandardMaterial>,
+    pub ramp_mesh: Handle<Mesh>,
+    pub ramp_material: Handle<StandardMaterial>,
+    /// The glowing bulb of a placed light.
+    pub lamp_mesh: Handle<Mesh>,
+    pub lamp_material: Handle<StandardMaterial>,
+    pub enemy_mesh: Handle<Mesh>,
+    pub enemy_material: Handle<StandardMaterial>,
+    /// A unit sphere, scaled up to the explosion radius.
+    pub explosion_mesh: Handle<Mesh>,
+    pub explosion_material: Handle<StandardMaterial>,
+    /// The first-person placeholder of a melee swing.
+    pub melee_mesh: Handle<Mesh>,
+    pub melee_material: Handle<StandardMaterial>,
+    /// A cylinder of unit length, stretched along the grappling line.
+    pub cable_mesh: Handle<Mesh>,
+    pub cable_material: Handle<StandardMaterial>,
+    pub rain_mesh: Handle<Mesh>,
+    pub rain_material: Handle<StandardMaterial>,
+    pub snow_mesh: Handle<Mesh>,
+    pub snow_material: Handle<StandardMaterial>,
+    pub terrain_material: Handle<StandardMaterial>,
+}
+
+impl GameAssets {
+    pub fn ball_material(&self, kind: BallKind) -> Handle<StandardMaterial> {
+        match kind {
+            BallKind::Regular => self.ball_material.clone(),
+            BallKind::Bouncy => self.bouncy_ball_material.clone(),
+            BallKind::Homing => self.homing_ball_material.clone(),
+        }
+    }
+
+    /// One of the cube materials, picked at random.
+    pub fn random_cube_material(&self) -> Handle<StandardMaterial> {
+        self.cube_materials[rand::random_range(0..self.cube_materials.len())].clone()
+    }
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let ball_mesh = meshes.add(Sphere::new(BALL_RADIUS));
+        let cube_mesh = meshes.add(Cuboid::from_length(CUBE_SIZE));
+        let fragment_mesh = meshes.add(Cuboid::from_length(FRAGMENT_SIZE));
+        let grenade_mesh = meshes.add(Sphere::new(GRENADE_RADIUS));
+        let ramp_mesh = meshes.add(Cuboid::from_size(RAMP_SIZE));
+        let lamp_mesh = meshes.add(Sphere::new(LAMP_RADIUS));
+        let enemy_mesh = meshes.add(Capsule3d::new(ENEMY_RADIUS, ENEMY_LENGTH));
+        let explosion_mesh = meshes.add(Sphere::new(1.0));
+        let melee_mesh = meshes.add(Cuboid::new(0.05, 0.05, 0.6));
+        let cable_mesh = meshes.add(Cylinder::new(CABLE_RADIUS, 1.0));
+        let rain_mesh = meshes.add(Cuboid::new(0.01, 0.4, 0.01));
+        let snow_mesh = meshes.add(Cuboid::from_length(0.04));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let ball_material = materials.add(Color::BLACK);
+        let bouncy_ball_material = materials.add(Color::srgb(0.9, 0.1, 0.4));
+        let homing_ball_material = materials.add(Color::srgb(0.1, 0.6, 0.9));
+        let cube_materials = (0..CUBE_COLORS)
+            .map(|_| {
+                let r = rand::random_range(0..=255);
+                let g = rand::random_range(0..=255);
+                let b = rand::random_range(0..=255);
+                materials.add(Color::srgb_u8(r, g, b))
+            })
+            .collect();
+        let grenade_material = materials.add(Color::srgb(0.2, 0.3, 0.1));
+        let ramp_material = materials.add(Color::srgb(0.55, 0.45, 0.3));
+        let lamp_material = materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.9, 0.7),
+            emissive: LinearRgba::rgb(4.0, 3.5, 2.5),
+            ..default()
+        });
+        let enemy_material = materials.add(Color::srgb(0.6, 0.1, 0.15));
+        let explosion_material = materials.add(StandardMaterial {
+            base_color: Color::srgba(1.0, 0.6, 0.1, 0.5),
+            emissive: LinearRgba::rgb(8.0, 3.0, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let melee_material = materials.add(Color::srgb(0.4, 0.4, 0.45));
+        let cable_material = materials.add(Color::srgb(0.2, 0.2, 0.2));
+        let rain_material = materials.add(StandardMaterial {
+            base_color: Color::srgba(0.7, 0.8, 1.0, 0.4),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let snow_material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        });
+        let terrain_material = materials.add(Color::srgb(0.35, 0.55, 0.25));
+
+        Self {
+            ball_mesh,
+            ball_material,
+            bouncy_ball_material,
+            homing_ball_material,
+            cube_mesh,
+            cube_materials,
+            fragment_mesh,
+            grenade_mesh,
+            grenade_material,
+            ramp_mesh,
+            ramp_material,
+            lamp_mesh,
+            lamp_material,
+            enemy_mesh,
+            enemy_material,
+            explosion_mesh,
+            explosion_material,
+            melee_mesh,
+            melee_material,
+            cable_mesh,
+            cable_material,
+            rain_mesh,
+            rain_material,
+            snow_mesh,
+            snow_material,
+            terrain_material,
+        }
+    }
+}
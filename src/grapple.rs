@@ -0,0 +1,185 @@
+use crate::camera::CharacterCameras;
+use crate::game_assets::GameAssets;
+use crate::layers::GameLayer;
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const GRAPPLE_RANGE: Scalar = 30.0;
+/// How fast the cable shortens while the grapple is held.
+const GRAPPLE_REEL_SPEED: Scalar = 8.0;
+/// The cable stops reeling in at this length so the character hangs just below the anchor.
+const GRAPPLE_MIN_LENGTH: Scalar = 1.5;
+/// Where the cable leaves the character, relative to the camera.
+const HAND_OFFSET: Vec3 = Vec3::new(0.3, -0.3, 0.0);
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerGrapple;
+
+/// The character's grappling line, if one is attached.
+#[derive(Component, Default)]
+pub struct Grapple(Option<GrappleLine>);
+
+pub struct GrappleLine {
+    /// The entity the cable is attached to.
+    anchor: Entity,
+    /// The attachment point in the anchor's local space.
+    local_anchor: Vector,
+    /// The entity holding the [`DistanceJoint`] between the anchor and the character.
+    joint: Entity,
+    /// The entity holding the cable mesh.
+    cable: Entity,
+    length: Scalar,
+}
+
+pub struct GrapplePlugin;
+
+impl Plugin for GrapplePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (reel_in, update_cable).chain())
+            .add_observer(fire_grapple)
+            .add_observer(release_grapple);
+    }
+}
+
+/// Shoots the grapple where the camera is looking and attaches it to whatever it hits.
+fn fire_grapple(
+    trigger: Trigger<Started<PlayerGrapple>>,
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+    bodies: Query<(&Position, &Rotation)>,
+    mut query: Query<&mut Grapple, With<Player>>,
+) {
+    let entity = trigger.target();
+    let Ok(mut grapple) = query.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+
+    if let Some(line) = grapple.0.take() {
+        detach(&mut commands, line);
+    }
+
+    let origin = camera.translation();
+    let direction = camera.forward();
+    let filter = SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+    let Some(hit) = spatial_query.cast_ray(origin, direction, GRAPPLE_RANGE, true, &filter) else {
+        return;
+    };
+    let Ok((position, rotation)) = bodies.get(hit.entity) else {
+        return;
+    };
+
+    let point = origin + direction * hit.distance;
+    let local_anchor = rotation.inverse() * (point - position.0);
+    // The cable starts out slack at the distance it was fired from and only ever shortens
+    let length = hit.distance;
+
+    let joint = commands
+        .spawn(
+            DistanceJoint::new(hit.entity, entity)
+                .with_local_anchor_1(local_anchor)
+                .with_limits(0.0, length),
+        )
+        .id();
+    let cable = commands
+        .spawn((
+            Mesh3d(assets.cable_mesh.clone()),
+            MeshMaterial3d(assets.cable_material.clone()),
+            Transform::default(),
+        ))
+        .id();
+
+    grapple.0 = Some(GrappleLine {
+        anchor: hit.entity,
+        local_anchor,
+        joint,
+        cable,
+        length,
+    });
+}
+
+fn release_grapple(
+    trigger: Trigger<Completed<PlayerGrapple>>,
+    mut commands: Commands,
+    mut query: Query<&mut Grapple, With<Player>>,
+) {
+    let Ok(mut grapple) = query.get_mut(trigger.target()) else {
+        return;
+    };
+    if let Some(line) = grapple.0.take() {
+        detach(&mut commands, line);
+    }
+}
+
+fn detach(commands: &mut Commands, line: GrappleLine) {
+    commands.entity(line.joint).despawn();
+    commands.entity(line.cable).despawn();
+}
+
+/// Shortens attached cables, pulling the character toward the anchor. Lines whose anchor has
+/// been despawned are let go.
+fn reel_in(
+    mut commands: Commands,
+    time: Res<Time>,
+    bodies: Query<(), With<Position>>,
+    mut joints: Query<&mut DistanceJoint>,
+    mut query: Query<&mut Grapple, With<Player>>,
+) {
+    for mut grapple in &mut query {
+        let Some(line) = &mut grapple.0 else {
+            continue;
+        };
+
+        if !bodies.contains(line.anchor) {
+            if let Some(line) = grapple.0.take() {
+                detach(&mut commands, line);
+            }
+            continue;
+        }
+
+        line.length =
+            (line.length - GRAPPLE_REEL_SPEED * time.delta_secs()).max(GRAPPLE_MIN_LENGTH);
+        if let Ok(mut joint) = joints.get_mut(line.joint) {
+            joint.length_limits = Some(DistanceLimit::new(0.0, line.length));
+        }
+    }
+}
+
+/// Stretches each cable mesh between the character's hand and its anchor point.
+fn update_cable(
+    cameras: CharacterCameras,
+    bodies: Query<(&Position, &Rotation)>,
+    mut cables: Query<&mut Transform>,
+    query: Query<(Entity, &Grapple), With<Player>>,
+) {
+    for (entity, grapple) in &query {
+        let Some(line) = &grapple.0 else {
+            continue;
+        };
+        let Some(camera) = cameras.transform(entity) else {
+            continue;
+        };
+        let Ok((position, rotation)) = bodies.get(line.anchor) else {
+            continue;
+        };
+        let Ok(mut transform) = cables.get_mut(line.cable) else {
+            continue;
+        };
+
+        let hand = camera.transform_point(HAND_OFFSET);
+        let anchor = position.0 + rotation.0 * line.local_anchor;
+        let span = anchor - hand;
+
+        // The cable mesh is a unit-height cylinder along Y centered on its origin
+        *transform = Transform::from_translation(hand + span / 2.0)
+            .with_rotation(Quat::from_rotation_arc(Vec3::Y, span.normalize_or(Vec3::Y)))
+            .with_scale(Vec3::new(1.0, span.length(), 1.0));
+    }
+}
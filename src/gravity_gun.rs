@@ -0,0 +1,120 @@
+use crate::camera::CharacterCameras;
+use crate::carry::Carry;
+use crate::layers::GameLayer;
+use crate::player_movement::{Player, PlayerAction, PlayerAltAction};
+use crate::tools::Tool;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const GRAVITY_GUN_RANGE: Scalar = 15.0;
+/// How far in front of the camera held objects float.
+const HOLD_DISTANCE: Scalar = 3.0;
+/// How fast a held object is pulled toward the hold point, per meter it is away from it.
+const HOLD_STIFFNESS: Scalar = 12.0;
+const HOLD_MAX_SPEED: Scalar = 20.0;
+/// Exponential decay rate of a held object's spin, per second.
+const HOLD_SPIN_DECAY: Scalar = 5.0;
+const LAUNCH_IMPULSE: Scalar = 5.0;
+
+/// The dynamic body a character's gravity gun is holding, if any.
+#[derive(Component, Default)]
+pub struct GravityGun(Option<Entity>);
+
+pub struct GravityGunPlugin;
+
+impl Plugin for GravityGunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, hold_objects)
+            .add_observer(grab_or_launch)
+            .add_observer(drop_held);
+    }
+}
+
+/// Grabs the dynamic body the camera is looking at, or launches the one already held where the
+/// camera is looking.
+fn grab_or_launch(
+    trigger: Trigger<Started<PlayerAction>>,
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+    bodies: Query<&RigidBody>,
+    mut query: Query<(&Tool, &Carry, &mut GravityGun), With<Player>>,
+) {
+    let entity = trigger.target();
+    let Ok((tool, carry, mut gravity_gun)) = query.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+    if *tool != Tool::GravityGun || carry.is_carrying() {
+        return;
+    }
+
+    if let Some(held) = gravity_gun.0.take() {
+        if let Ok(mut held) = commands.get_entity(held) {
+            held.insert(ExternalImpulse::new(camera.forward() * LAUNCH_IMPULSE));
+        }
+        return;
+    }
+
+    let filter = SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+    let Some(hit) = spatial_query.cast_ray(
+        camera.translation(),
+        camera.forward(),
+        GRAVITY_GUN_RANGE,
+        true,
+        &filter,
+    ) else {
+        return;
+    };
+
+    if bodies.get(hit.entity).is_ok_and(RigidBody::is_dynamic) {
+        gravity_gun.0 = Some(hit.entity);
+    }
+}
+
+fn drop_held(
+    trigger: Trigger<Started<PlayerAltAction>>,
+    mut query: Query<(&Tool, &mut GravityGun), With<Player>>,
+) {
+    let Ok((tool, mut gravity_gun)) = query.get_mut(trigger.target()) else {
+        return;
+    };
+
+    if *tool == Tool::GravityGun {
+        gravity_gun.0 = None;
+    }
+}
+
+/// Pulls held objects toward the point in front of the camera like a damped spring, and
+/// settles their spin. Objects are let go when the gravity gun is put away or they're despawned.
+fn hold_objects(
+    time: Res<Time>,
+    cameras: CharacterCameras,
+    mut bodies: Query<(&Position, &mut LinearVelocity, &mut AngularVelocity)>,
+    mut query: Query<(Entity, &Tool, &mut GravityGun), With<Player>>,
+) {
+    for (entity, tool, mut gravity_gun) in &mut query {
+        let Some(held) = gravity_gun.0 else {
+            continue;
+        };
+        let Ok((position, mut velocity, mut angular_velocity)) = bodies.get_mut(held) else {
+            gravity_gun.0 = None;
+            continue;
+        };
+        if *tool != Tool::GravityGun {
+            gravity_gun.0 = None;
+            continue;
+        }
+
+        let Some(camera) = cameras.transform(entity) else {
+            continue;
+        };
+
+        let target = camera.translation() + camera.forward() * HOLD_DISTANCE;
+        velocity.0 = ((target - position.0) * HOLD_STIFFNESS).clamp_length_max(HOLD_MAX_SPEED);
+        angular_velocity.0 *= (-HOLD_SPIN_DECAY * time.delta_secs()).exp();
+    }
+}
@@ -0,0 +1,152 @@
+use crate::player_movement::Player;
+use crate::trigger::TriggerVolume;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_tnua::TnuaGravity;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// How quickly players turn to stand upright against the gravity they're in.
+const REORIENT_RESPONSIVENESS: f32 = 6.0;
+
+/// The gravity inside a [`GravityZone`].
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum GravityField {
+    /// The same everywhere in the zone.
+    Uniform(Vector),
+    /// Toward the zone's center with `strength` m/s², like a small planet.
+    Attractor { strength: Scalar },
+}
+
+impl GravityField {
+    fn at(self, center: Vector, position: Vector) -> Vector {
+        match self {
+            Self::Uniform(gravity) => gravity,
+            Self::Attractor { strength } => (center - position).normalize_or_zero() * strength,
+        }
+    }
+}
+
+/// A trigger volume replacing the gravity of dynamic bodies inside it. Where zones overlap, the
+/// one with the highest `priority` wins.
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct GravityZone {
+    pub field: GravityField,
+    pub priority: i32,
+}
+
+/// The gravity of a body inside a [`GravityZone`]. The body's own gravity is switched off
+/// meanwhile, and `gravity_scale` holds the value to restore once it leaves.
+#[derive(Component)]
+pub struct LocalGravity {
+    pub gravity: Vector,
+    gravity_scale: Scalar,
+}
+
+pub struct GravityZonePlugin;
+
+impl Plugin for GravityZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (update_local_gravity, apply_local_gravity).chain(),
+        )
+        .add_systems(Update, reorient_players);
+    }
+}
+
+/// Gives bodies the gravity of the zone they're in, and their own back once they're in none.
+/// Players also get it as their [`TnuaGravity`], so Tnua floats and jumps them against it.
+#[allow(clippy::type_complexity)]
+fn update_local_gravity(
+    mut commands: Commands,
+    zones: Query<(&GravityZone, &GlobalTransform, &CollidingEntities)>,
+    mut bodies: Query<(
+        Entity,
+        &RigidBody,
+        &Position,
+        Option<&GravityScale>,
+        Option<&mut LocalGravity>,
+        Has<Player>,
+    )>,
+) {
+    let mut inside = HashMap::new();
+    for (zone, transform, colliding) in &zones {
+        for &entity in colliding.iter() {
+            let Ok((_, rigid_body, position, ..)) = bodies.get(entity) else {
+                continue;
+            };
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            let gravity = zone.field.at(transform.translation(), position.0);
+            inside
+                .entry(entity)
+                .and_modify(|(priority, current)| {
+                    if zone.priority > *priority {
+                        (*priority, *current) = (zone.priority, gravity);
+                    }
+                })
+                .or_insert((zone.priority, gravity));
+        }
+    }
+
+    for (entity, _, _, gravity_scale, local_gravity, is_player) in &mut bodies {
+        match (local_gravity, inside.get(&entity)) {
+            (Some(mut local_gravity), Some(&(_, gravity))) => {
+                local_gravity.gravity = gravity;
+                if is_player {
+                    commands.entity(entity).insert(TnuaGravity(gravity));
+                }
+            }
+            (None, Some(&(_, gravity))) => {
+                commands.entity(entity).insert((
+                    LocalGravity {
+                        gravity,
+                        gravity_scale: gravity_scale.map_or(1.0, |gravity_scale| gravity_scale.0),
+                    },
+                    GravityScale(0.0),
+                ));
+                if is_player {
+                    commands.entity(entity).insert(TnuaGravity(gravity));
+                }
+            }
+            (Some(local_gravity), None) => {
+                commands
+                    .entity(entity)
+                    .insert(GravityScale(local_gravity.gravity_scale))
+                    .remove::<(LocalGravity, TnuaGravity)>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn apply_local_gravity(time: Res<Time>, mut bodies: Query<(&LocalGravity, &mut LinearVelocity)>) {
+    for (local_gravity, mut velocity) in &mut bodies {
+        velocity.0 += local_gravity.gravity * time.delta_secs();
+    }
+}
+
+/// Turns players so their up points away from their gravity, keeping where they're facing as
+/// much as possible. Their movement and camera follow, being relative to the body.
+fn reorient_players(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut players: Query<(&mut Transform, Option<&LocalGravity>), With<Player>>,
+) {
+    for (mut transform, local_gravity) in &mut players {
+        let gravity = local_gravity.map_or(gravity.0, |local_gravity| local_gravity.gravity);
+        let Ok(up) = Dir3::new(-gravity) else {
+            continue;
+        };
+        let turn = Quat::from_rotation_arc(*transform.up(), *up);
+        if turn.angle_between(Quat::IDENTITY) < 0.001 {
+            continue;
+        }
+
+        let amount = 1.0 - (-REORIENT_RESPONSIVENESS * time.delta_secs()).exp();
+        transform.rotation = Quat::IDENTITY.slerp(turn, amount) * transform.rotation;
+    }
+}
@@ -0,0 +1,109 @@
+use crate::ball::{PassThroughPlayers, Thrower, thrown_layers};
+use crate::camera::CharacterCameras;
+use crate::explosion::Explosion;
+use crate::game_assets::{GRENADE_RADIUS, GameAssets};
+use crate::inventory::{Inventory, Item};
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const GRENADE_THROW_SPEED: Scalar = 15.0;
+/// Extra upward speed on top of the throw, so grenades arc.
+const GRENADE_THROW_LIFT: Scalar = 5.0;
+const GRENADE_FUSE_TIME: f32 = 2.5;
+const GRENADE_EXPLOSION_RADIUS: Scalar = 5.0;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerGrenade;
+
+/// Counts down in game time until the grenade explodes.
+#[derive(Component)]
+pub struct Fuse(Timer);
+
+#[derive(Bundle)]
+pub struct GrenadeBundle {
+    fuse: Fuse,
+    thrower: Thrower,
+    rigid_body: RigidBody,
+    collider: Collider,
+    collision_layers: CollisionLayers,
+    pass_through_players: PassThroughPlayers,
+    restitution: Restitution,
+    mesh3d: Mesh3d,
+    mesh_material3d: MeshMaterial3d<StandardMaterial>,
+    transform: Transform,
+    linear_velocity: LinearVelocity,
+}
+
+impl GrenadeBundle {
+    pub fn new(assets: &GameAssets, transform: Transform, thrower: Entity) -> Self {
+        let velocity = transform.forward() * GRENADE_THROW_SPEED + Vector::Y * GRENADE_THROW_LIFT;
+
+        Self {
+            fuse: Fuse(Timer::from_seconds(GRENADE_FUSE_TIME, TimerMode::Once)),
+            thrower: Thrower(thrower),
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::sphere(GRENADE_RADIUS),
+            collision_layers: thrown_layers(),
+            pass_through_players: PassThroughPlayers::default(),
+            restitution: Restitution::new(0.3),
+            mesh3d: Mesh3d(assets.grenade_mesh.clone()),
+            mesh_material3d: MeshMaterial3d(assets.grenade_material.clone()),
+            transform,
+            linear_velocity: LinearVelocity(velocity),
+        }
+    }
+}
+
+pub struct GrenadePlugin;
+
+impl Plugin for GrenadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, burn_fuses)
+            .add_observer(throw_grenade);
+    }
+}
+
+/// Throws one of the grenades in the player's [`Inventory`], if there are any left.
+fn throw_grenade(
+    trigger: Trigger<Started<PlayerGrenade>>,
+    cameras: CharacterCameras,
+    mut inventories: Query<&mut Inventory, With<Player>>,
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+) {
+    let entity = trigger.target();
+    let Ok(mut inventory) = inventories.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+    if !inventory.take(Item::Grenade, 1) {
+        return;
+    }
+
+    let mut transform = camera.compute_transform();
+    transform.translation += transform.forward().as_vec3();
+
+    commands.spawn(GrenadeBundle::new(&assets, transform, entity));
+}
+
+fn burn_fuses(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut explosions: EventWriter<Explosion>,
+    mut grenades: Query<(Entity, &mut Fuse, &Thrower, &Transform)>,
+) {
+    for (grenade, mut fuse, thrower, transform) in &mut grenades {
+        if fuse.0.tick(time.delta()).finished() {
+            explosions.write(
+                Explosion::new(transform.translation, GRENADE_EXPLOSION_RADIUS)
+                    .with_source(thrower.0),
+            );
+            commands.entity(grenade).despawn();
+        }
+    }
+}
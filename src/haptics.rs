@@ -0,0 +1,137 @@
+use crate::player_movement::{Knockback, Landed, PlayerAction, PlayerInputSettings};
+use avian3d::math::Scalar;
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use std::time::Duration;
+
+/// Landings slower than this don't rumble.
+const LANDING_RUMBLE_MIN_SPEED: Scalar = 10.0;
+/// Landings this fast or faster rumble at full strength.
+const LANDING_RUMBLE_FULL_SPEED: Scalar = 40.0;
+const LANDING_RUMBLE_DURATION: Duration = Duration::from_millis(150);
+const THROW_RUMBLE_STRENGTH: f32 = 0.3;
+const THROW_RUMBLE_DURATION: Duration = Duration::from_millis(80);
+/// Knockbacks with an impulse this big or bigger rumble at full strength.
+const KNOCKBACK_RUMBLE_FULL_IMPULSE: Scalar = 20.0;
+const KNOCKBACK_RUMBLE_DURATION: Duration = Duration::from_millis(300);
+
+pub struct HapticsPlugin;
+
+impl Plugin for HapticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (rumble_on_landing, rumble_on_knockback))
+            .add_observer(rumble_on_throw);
+    }
+}
+
+/// Sends a rumble to the gamepads driving `player`, scaled by the rumble settings.
+fn rumble(
+    rumble_requests: &mut EventWriter<GamepadRumbleRequest>,
+    settings: &PlayerInputSettings,
+    gamepads: &Query<Entity, With<Gamepad>>,
+    device: Option<&GamepadDevice>,
+    intensity: GamepadRumbleIntensity,
+    duration: Duration,
+) {
+    if !settings.rumble {
+        return;
+    }
+
+    let intensity = GamepadRumbleIntensity {
+        strong_motor: (intensity.strong_motor * settings.rumble_intensity).clamp(0.0, 1.0),
+        weak_motor: (intensity.weak_motor * settings.rumble_intensity).clamp(0.0, 1.0),
+    };
+    let targets = gamepads.iter().filter(|gamepad| match device {
+        None | Some(GamepadDevice::Any) => true,
+        Some(GamepadDevice::Single(assigned)) => gamepad == assigned,
+        Some(GamepadDevice::None) => false,
+    });
+    for gamepad in targets {
+        rumble_requests.write(GamepadRumbleRequest::Add {
+            duration,
+            intensity,
+            gamepad,
+        });
+    }
+}
+
+fn rumble_on_landing(
+    mut landings: EventReader<Landed>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+    settings: Res<PlayerInputSettings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    devices: Query<Option<&GamepadDevice>>,
+) {
+    for landed in landings.read() {
+        if landed.impact_speed < LANDING_RUMBLE_MIN_SPEED {
+            continue;
+        }
+        let Ok(device) = devices.get(landed.entity) else {
+            continue;
+        };
+
+        let strength = ((landed.impact_speed - LANDING_RUMBLE_MIN_SPEED)
+            / (LANDING_RUMBLE_FULL_SPEED - LANDING_RUMBLE_MIN_SPEED))
+            .clamp(0.0, 1.0);
+        rumble(
+            &mut rumble_requests,
+            &settings,
+            &gamepads,
+            device,
+            GamepadRumbleIntensity {
+                strong_motor: strength,
+                weak_motor: strength / 2.0,
+            },
+            LANDING_RUMBLE_DURATION,
+        );
+    }
+}
+
+fn rumble_on_knockback(
+    mut knockbacks: EventReader<Knockback>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+    settings: Res<PlayerInputSettings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    devices: Query<Option<&GamepadDevice>>,
+) {
+    for knockback in knockbacks.read() {
+        let Ok(device) = devices.get(knockback.entity) else {
+            continue;
+        };
+
+        let strength = (knockback.impulse.length() / KNOCKBACK_RUMBLE_FULL_IMPULSE).clamp(0.0, 1.0);
+        rumble(
+            &mut rumble_requests,
+            &settings,
+            &gamepads,
+            device,
+            GamepadRumbleIntensity {
+                strong_motor: strength,
+                weak_motor: strength,
+            },
+            KNOCKBACK_RUMBLE_DURATION,
+        );
+    }
+}
+
+fn rumble_on_throw(
+    trigger: Trigger<Completed<PlayerAction>>,
+    mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+    settings: Res<PlayerInputSettings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    devices: Query<Option<&GamepadDevice>>,
+) {
+    let Ok(device) = devices.get(trigger.target()) else {
+        return;
+    };
+
+    rumble(
+        &mut rumble_requests,
+        &settings,
+        &gamepads,
+        device,
+        GamepadRumbleIntensity::weak_motor(THROW_RUMBLE_STRENGTH),
+        THROW_RUMBLE_DURATION,
+    );
+}
@@ -0,0 +1,149 @@
+use crate::combat::{Damage, Health};
+use crate::player_movement::{PLAYER_HEALTH, Player};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// How far the health bar sits from the bottom left corner of the screen, in pixels.
+const HEALTH_BAR_MARGIN: f32 = 24.0;
+const HEALTH_BAR_WIDTH: f32 = 200.0;
+const HEALTH_BAR_HEIGHT: f32 = 12.0;
+/// The resolution of the generated vignette texture, stretched over the whole screen.
+const VIGNETTE_SIZE: u32 = 64;
+/// Damage taken at once that pulses the vignette at full strength.
+const VIGNETTE_FULL_DAMAGE: f32 = 30.0;
+/// How much of a damage pulse wears off per second.
+const VIGNETTE_PULSE_DECAY: f32 = 2.0;
+/// Below this health the vignette stays on, stronger the lower it gets.
+const LOW_HEALTH: f32 = 30.0;
+/// Heartbeats per second of the low health vignette.
+const LOW_HEALTH_BEAT_RATE: f32 = 1.2;
+
+#[derive(Component)]
+struct HealthBarFill;
+
+/// The red edges of the screen, with how strong the current damage pulse is, from 0 to 1.
+#[derive(Component)]
+struct DamageVignette {
+    pulse: f32,
+}
+
+pub struct HealthHudPlugin;
+
+impl Plugin for HealthHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (spawn_health_bar, spawn_vignette))
+            .add_systems(Update, (update_health_bar, pulse_vignette, update_vignette));
+    }
+}
+
+fn spawn_health_bar(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(HEALTH_BAR_MARGIN),
+            bottom: Val::Px(HEALTH_BAR_MARGIN),
+            width: Val::Px(HEALTH_BAR_WIDTH),
+            height: Val::Px(HEALTH_BAR_HEIGHT),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.5)),
+        children![(
+            HealthBarFill,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.8, 0.15, 0.15)),
+        )],
+    ));
+}
+
+/// Spawns the vignette as a texture fading from clear in the middle to red at the edges,
+/// drawn over the world but under the HUD and menus.
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let half = VIGNETTE_SIZE as f32 / 2.0;
+    let data = (0..VIGNETTE_SIZE * VIGNETTE_SIZE)
+        .flat_map(|index| {
+            let x = (index % VIGNETTE_SIZE) as f32 + 0.5 - half;
+            let y = (index / VIGNETTE_SIZE) as f32 + 0.5 - half;
+            let distance = Vec2::new(x, y).length() / half;
+            let alpha = ((distance - 0.5) / 0.6).clamp(0.0, 1.0).powi(2);
+            [255, 255, 255, (alpha * 255.0) as u8]
+        })
+        .collect();
+    let image = Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+
+    commands.spawn((
+        DamageVignette { pulse: 0.0 },
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ImageNode::new(images.add(image)).with_color(Color::srgba(0.8, 0.0, 0.0, 0.0)),
+        GlobalZIndex(-1),
+        Pickable::IGNORE,
+    ));
+}
+
+fn update_health_bar(
+    players: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fills: Query<&mut Node, With<HealthBarFill>>,
+) {
+    let Some(health) = players.iter().next() else {
+        return;
+    };
+
+    for mut fill in &mut fills {
+        fill.width = Val::Percent((health.0 / PLAYER_HEALTH).clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+/// Pulses the vignette with damage dealt to the player, harder the more damage at once.
+fn pulse_vignette(
+    mut damage: EventReader<Damage>,
+    players: Query<(), With<Player>>,
+    mut vignettes: Query<&mut DamageVignette>,
+) {
+    for event in damage.read() {
+        if !players.contains(event.target) {
+            continue;
+        }
+        for mut vignette in &mut vignettes {
+            vignette.pulse = (vignette.pulse + event.amount / VIGNETTE_FULL_DAMAGE).min(1.0);
+        }
+    }
+}
+
+/// Fades damage pulses out, and keeps the vignette beating while the player's health is low.
+fn update_vignette(
+    time: Res<Time>,
+    players: Query<&Health, With<Player>>,
+    mut vignettes: Query<(&mut DamageVignette, &mut ImageNode)>,
+) {
+    let low_health = players
+        .iter()
+        .next()
+        .map_or(0.0, |health| (1.0 - health.0 / LOW_HEALTH).clamp(0.0, 1.0));
+    let beat =
+        0.5 + 0.5 * (time.elapsed_secs() * LOW_HEALTH_BEAT_RATE * std::f32::consts::TAU).sin();
+
+    for (mut vignette, mut image) in &mut vignettes {
+        vignette.pulse = (vignette.pulse - VIGNETTE_PULSE_DECAY * time.delta_secs()).max(0.0);
+        let strength = f32::max(vignette.pulse, low_health * (0.5 + 0.5 * beat));
+        image.color.set_alpha(strength);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::ball::DespawnAfter;
+use crate::camera::CameraRig;
+use crate::combat::Damage;
+use crate::player_movement::Player;
+use bevy::prelude::*;
+
+const HIT_MARKER_TIME: f32 = 0.15;
+/// How far each dot of the hit marker sits from the screen center, diagonally, in pixels.
+const HIT_MARKER_GAP: f32 = 10.0;
+const HIT_MARKER_DOT_SIZE: f32 = 4.0;
+const DAMAGE_NUMBER_TIME: f32 = 0.8;
+/// How far damage numbers rise over their lifetime, in meters.
+const DAMAGE_NUMBER_RISE: f32 = 1.0;
+/// How far above the target's center damage numbers start out.
+const DAMAGE_NUMBER_OFFSET: f32 = 0.5;
+
+/// The dots around the crosshair that flash when something is hit, with when they were last
+/// shown in elapsed seconds.
+#[derive(Component)]
+pub struct HitMarker(Option<f32>);
+
+impl HitMarker {
+    /// Whether a hit is being shown at `now`, in elapsed seconds.
+    pub fn is_shown(&self, now: f32) -> bool {
+        self.0
+            .is_some_and(|shown_at| now - shown_at < HIT_MARKER_TIME)
+    }
+}
+
+/// A number floating up from where damage was dealt, started at the given elapsed seconds.
+#[derive(Component)]
+struct DamageNumber {
+    position: Vec3,
+    spawned: f32,
+}
+
+pub struct HitFeedbackPlugin;
+
+impl Plugin for HitFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hit_marker).add_systems(
+            Update,
+            (show_hits, update_hit_marker, update_damage_numbers).chain(),
+        );
+    }
+}
+
+fn spawn_hit_marker(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            // A zero-sized anchor at the screen center for the dots to be positioned around
+            parent
+                .spawn((HitMarker(None), Node::default(), Visibility::Hidden))
+                .with_children(|center| {
+                    for corner in [
+                        Vec2::ONE,
+                        Vec2::new(-1.0, 1.0),
+                        -Vec2::ONE,
+                        Vec2::new(1.0, -1.0),
+                    ] {
+                        let offset = corner * HIT_MARKER_GAP - HIT_MARKER_DOT_SIZE / 2.0;
+                        center.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(offset.x),
+                                top: Val::Px(offset.y),
+                                width: Val::Px(HIT_MARKER_DOT_SIZE),
+                                height: Val::Px(HIT_MARKER_DOT_SIZE),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(1.0, 0.3, 0.3)),
+                        ));
+                    }
+                });
+        });
+}
+
+/// Flashes the hit marker and spawns a damage number for damage dealt by the player's
+/// projectiles and swings. The player hurting itself doesn't count.
+fn show_hits(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut damage: EventReader<Damage>,
+    targets: Query<&GlobalTransform, Without<Player>>,
+    players: Query<(), With<Player>>,
+    mut markers: Query<&mut HitMarker>,
+) {
+    for event in damage.read() {
+        if !event.source.is_some_and(|source| players.contains(source)) {
+            continue;
+        }
+        let Ok(target) = targets.get(event.target) else {
+            continue;
+        };
+
+        let now = time.elapsed_secs();
+        for mut marker in &mut markers {
+            marker.0 = Some(now);
+        }
+        commands.spawn((
+            DamageNumber {
+                position: target.translation() + Vec3::Y * DAMAGE_NUMBER_OFFSET,
+                spawned: now,
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Text::new(format!("{:.0}", event.amount.ceil())),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Visibility::Hidden,
+            DespawnAfter::new(DAMAGE_NUMBER_TIME),
+        ));
+    }
+}
+
+fn update_hit_marker(time: Res<Time>, mut markers: Query<(&HitMarker, &mut Visibility)>) {
+    for (marker, mut visibility) in &mut markers {
+        *visibility = if marker.is_shown(time.elapsed_secs()) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Places damage numbers on screen over the point they rise from, fading them out as they go.
+/// Numbers behind the camera are hidden.
+fn update_damage_numbers(
+    time: Res<Time>,
+    camera: Single<(&Camera, &GlobalTransform), With<CameraRig>>,
+    mut numbers: Query<(&DamageNumber, &mut Node, &mut TextColor, &mut Visibility)>,
+) {
+    let (camera, camera_transform) = *camera;
+
+    for (number, mut node, mut color, mut visibility) in &mut numbers {
+        let progress = ((time.elapsed_secs() - number.spawned) / DAMAGE_NUMBER_TIME).min(1.0);
+        let position = number.position + Vec3::Y * DAMAGE_NUMBER_RISE * progress;
+
+        let Ok(screen) = camera.world_to_viewport(camera_transform, position) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        node.left = Val::Px(screen.x);
+        node.top = Val::Px(screen.y);
+        color.0 = color.0.with_alpha(1.0 - progress);
+        *visibility = Visibility::Inherited;
+    }
+}
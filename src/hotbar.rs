@@ -0,0 +1,88 @@
+use crate::player_movement::Player;
+use crate::tools::Tool;
+use bevy::prelude::*;
+
+/// How far the hotbar sits from the bottom of the screen, in pixels.
+const HOTBAR_MARGIN: f32 = 24.0;
+const SLOT_WIDTH: f32 = 84.0;
+const SLOT_HEIGHT: f32 = 48.0;
+const SLOT_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.3);
+const SELECTED_SLOT_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// A slot of the hotbar, showing the tool its number key selects.
+#[derive(Component)]
+struct HotbarSlot(Tool);
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hotbar)
+            .add_systems(Update, highlight_selected_slot);
+    }
+}
+
+fn spawn_hotbar(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(HOTBAR_MARGIN),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|hotbar| {
+            for (slot, tool) in (1..).zip(Tool::ALL) {
+                hotbar.spawn((
+                    HotbarSlot(tool),
+                    Node {
+                        width: Val::Px(SLOT_WIDTH),
+                        height: Val::Px(SLOT_HEIGHT),
+                        border: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::all(Val::Px(4.0)),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    BackgroundColor(Color::BLACK.with_alpha(0.4)),
+                    BorderColor(SLOT_COLOR),
+                    BorderRadius::all(Val::Px(4.0)),
+                    children![
+                        (
+                            Text::new(slot.to_string()),
+                            TextFont {
+                                font_size: 12.0,
+                                ..default()
+                            },
+                        ),
+                        (
+                            Text::new(tool.name()),
+                            TextFont {
+                                font_size: 11.0,
+                                ..default()
+                            },
+                        ),
+                    ],
+                ));
+            }
+        });
+}
+
+/// Outlines the slot of the player's tool.
+fn highlight_selected_slot(
+    players: Query<&Tool, (With<Player>, Changed<Tool>)>,
+    mut slots: Query<(&HotbarSlot, &mut BorderColor)>,
+) {
+    let Some(tool) = players.iter().next() else {
+        return;
+    };
+
+    for (slot, mut border) in &mut slots {
+        border.0 = if slot.0 == *tool {
+            SELECTED_SLOT_COLOR
+        } else {
+            SLOT_COLOR
+        };
+    }
+}
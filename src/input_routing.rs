@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+/// Which local player a character is controlled by. Player 0 plays on keyboard and mouse, every
+/// other player on a gamepad of their own.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LocalPlayer(pub usize);
+
+impl LocalPlayer {
+    pub fn uses_keyboard_mouse(self) -> bool {
+        self.0 == 0
+    }
+}
+
+pub struct InputRoutingPlugin;
+
+impl Plugin for InputRoutingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, route_gamepads);
+    }
+}
+
+/// Hands out the connected gamepads in connection order, first to the gamepad players by their
+/// index, then whatever is left over to the keyboard and mouse player. Players without a
+/// gamepad ignore gamepad input, so nobody steers somebody else.
+fn route_gamepads(
+    mut commands: Commands,
+    gamepads: Query<Entity, With<Gamepad>>,
+    players: Query<(Entity, &LocalPlayer, Option<&GamepadDevice>)>,
+) {
+    let mut gamepads = gamepads.iter().collect::<Vec<_>>();
+    gamepads.sort();
+    let mut gamepads = gamepads.into_iter();
+
+    let mut players = players.iter().collect::<Vec<_>>();
+    players
+        .sort_by_key(|(_, local_player, _)| (local_player.uses_keyboard_mouse(), **local_player));
+
+    for (player, _, device) in players {
+        let assigned = gamepads
+            .next()
+            .map_or(GamepadDevice::None, GamepadDevice::Single);
+        if device != Some(&assigned) {
+            commands.entity(player).insert(assigned);
+        }
+    }
+}
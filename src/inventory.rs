@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use serde::Deserialize;
+
+/// How many slots the player's inventory has.
+pub const INVENTORY_SLOTS: usize = 20;
+
+/// Opens and closes the inventory grid.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerInventory;
+
+/// Something that can be carried in an [`Inventory`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Item {
+    Grenade,
+    /// Spent by the cube spawner, and given back when it takes a cube back.
+    Cube,
+    /// Spent by the ramp spawner, and given back when it takes a ramp back.
+    Ramp,
+    /// Spent by the light spawner, and given back when it takes a light back.
+    Lamp,
+}
+
+impl Item {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grenade => "Grenade",
+            Self::Cube => "Cube",
+            Self::Ramp => "Ramp",
+            Self::Lamp => "Lamp",
+        }
+    }
+
+    /// How many fit in one slot.
+    pub fn max_stack(self) -> u32 {
+        match self {
+            Self::Grenade => 5,
+            Self::Cube => 32,
+            Self::Ramp => 4,
+            Self::Lamp => 8,
+        }
+    }
+
+    /// The color of the item's icon in the inventory grid.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Grenade => Color::srgb(0.3, 0.45, 0.15),
+            Self::Cube => Color::srgb(0.5, 0.6, 0.9),
+            Self::Ramp => Color::srgb(0.55, 0.45, 0.3),
+            Self::Lamp => Color::srgb(1.0, 0.9, 0.6),
+        }
+    }
+}
+
+/// Some number of the same item, filling one slot.
+#[derive(Clone, Copy, Debug)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+}
+
+/// The items a character carries, in a fixed number of slots holding a stack each.
+#[derive(Component, Clone, Debug)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    /// An empty inventory with `slots` slots.
+    pub fn new(slots: usize) -> Self {
+        Self {
+            slots: vec![None; slots],
+        }
+    }
+
+    /// Adds `count` of `item` to start with, see [`Self::add`].
+    pub fn with(mut self, item: Item, count: u32) -> Self {
+        self.add(item, count);
+        self
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// How many of `item` there are across all slots.
+    pub fn count(&self, item: Item) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Adds `count` of `item`, topping up the stacks of it first and then filling empty slots.
+    /// Returns how many didn't fit.
+    pub fn add(&mut self, item: Item, mut count: u32) -> u32 {
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+            if stack.item == item {
+                let added = count.min(item.max_stack().saturating_sub(stack.count));
+                stack.count += added;
+                count -= added;
+            }
+        }
+
+        for slot in self.slots.iter_mut() {
+            if count == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let added = count.min(item.max_stack());
+                *slot = Some(ItemStack { item, count: added });
+                count -= added;
+            }
+        }
+
+        count
+    }
+
+    /// Takes `count` of `item`, emptying the last stacks first. Takes nothing and returns false
+    /// if there aren't that many.
+    pub fn take(&mut self, item: Item, mut count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+
+        for slot in self.slots.iter_mut().rev() {
+            let Some(stack) = slot.as_mut().filter(|stack| stack.item == item) else {
+                continue;
+            };
+            let taken = count.min(stack.count);
+            stack.count -= taken;
+            count -= taken;
+            if stack.count == 0 {
+                *slot = None;
+            }
+            if count == 0 {
+                break;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_tops_up_stacks_before_filling_empty_slots() {
+        let mut inventory = Inventory::new(3).with(Item::Grenade, 3);
+        assert_eq!(inventory.add(Item::Grenade, 4), 0);
+
+        let counts: Vec<_> = inventory
+            .slots()
+            .iter()
+            .map(|slot| slot.map(|stack| stack.count))
+            .collect();
+        assert_eq!(counts, [Some(5), Some(2), None]);
+    }
+
+    #[test]
+    fn add_returns_what_does_not_fit() {
+        let mut inventory = Inventory::new(2).with(Item::Lamp, 1);
+        assert_eq!(inventory.add(Item::Ramp, 6), 2);
+        assert_eq!(inventory.count(Item::Ramp), 4);
+        assert_eq!(inventory.count(Item::Lamp), 1);
+    }
+
+    #[test]
+    fn take_needs_enough_and_otherwise_takes_nothing() {
+        let mut inventory = Inventory::new(2).with(Item::Cube, 3);
+        assert!(!inventory.take(Item::Cube, 4));
+        assert_eq!(inventory.count(Item::Cube), 3);
+
+        assert!(inventory.take(Item::Cube, 3));
+        assert_eq!(inventory.count(Item::Cube), 0);
+        assert!(inventory.slots().iter().all(Option::is_none));
+    }
+}
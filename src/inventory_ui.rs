@@ -0,0 +1,118 @@
+use crate::inventory::{Inventory, PlayerInventory};
+use crate::player_movement::Player;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const INVENTORY_COLUMNS: u16 = 5;
+const SLOT_SIZE: f32 = 56.0;
+const ICON_SIZE: f32 = 28.0;
+
+/// The grid of the player's inventory slots, hidden until opened with [`PlayerInventory`].
+#[derive(Component)]
+struct InventoryGrid;
+
+pub struct InventoryUiPlugin;
+
+impl Plugin for InventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_inventory_grid)
+            .add_systems(Update, fill_inventory_grid)
+            .add_observer(toggle_inventory);
+    }
+}
+
+fn spawn_inventory_grid(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            InventoryGrid,
+            Node {
+                display: Display::Grid,
+                grid_template_columns: RepeatedGridTrack::px(INVENTORY_COLUMNS, SLOT_SIZE),
+                grid_auto_rows: vec![GridTrack::px(SLOT_SIZE)],
+                row_gap: Val::Px(4.0),
+                column_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.7)),
+            BorderRadius::all(Val::Px(6.0)),
+            Visibility::Hidden,
+        )],
+    ));
+}
+
+fn toggle_inventory(
+    _trigger: Trigger<Started<PlayerInventory>>,
+    mut grids: Query<&mut Visibility, With<InventoryGrid>>,
+) {
+    for mut visibility in &mut grids {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Rebuilds the grid's slots whenever the player's inventory changes.
+fn fill_inventory_grid(
+    mut commands: Commands,
+    players: Query<&Inventory, (With<Player>, Changed<Inventory>)>,
+    grids: Query<Entity, With<InventoryGrid>>,
+) {
+    let Some(inventory) = players.iter().next() else {
+        return;
+    };
+
+    for grid in &grids {
+        commands
+            .entity(grid)
+            .despawn_related::<Children>()
+            .with_children(|grid| {
+                for slot in inventory.slots() {
+                    let mut slot_entity = grid.spawn((
+                        Node {
+                            border: UiRect::all(Val::Px(1.0)),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE.with_alpha(0.05)),
+                        BorderColor(Color::WHITE.with_alpha(0.3)),
+                        BorderRadius::all(Val::Px(4.0)),
+                    ));
+                    let Some(stack) = slot else {
+                        continue;
+                    };
+
+                    slot_entity.with_children(|slot| {
+                        slot.spawn((
+                            Node {
+                                width: Val::Px(ICON_SIZE),
+                                height: Val::Px(ICON_SIZE),
+                                ..default()
+                            },
+                            BackgroundColor(stack.item.color()),
+                            BorderRadius::all(Val::Px(3.0)),
+                        ));
+                        slot.spawn((
+                            Text::new(format!("{} x{}", stack.item.name(), stack.count)),
+                            TextFont {
+                                font_size: 10.0,
+                                ..default()
+                            },
+                        ));
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,55 @@
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// Launches whatever stands on it with the given impulse, a change in velocity that's the same
+/// whatever the mass. Pointing it sideways makes a launch pad.
+///
+/// Characters float above the pad without touching it, so they're launched by
+/// `bounce_on_jump_pads` with a [`Knockback`](crate::player_movement::Knockback) instead, which
+/// Tnua's walk basis doesn't cancel out. Other dynamic bodies are launched by the contacts
+/// they're resting on the pad with.
+#[derive(Component)]
+#[require(CollidingEntities)]
+pub struct JumpPad(pub Vector);
+
+pub struct JumpPadPlugin;
+
+impl Plugin for JumpPadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, launch_bodies);
+    }
+}
+
+/// Launches dynamic bodies touching jump pads. Their velocity along the impulse is raised to
+/// the impulse's, rather than added to, so a body still touching the pad for a few steps isn't
+/// launched again each of them.
+#[allow(clippy::type_complexity)]
+fn launch_bodies(
+    jump_pads: Query<(&JumpPad, &CollidingEntities)>,
+    mut bodies: Query<
+        (&RigidBody, &mut LinearVelocity),
+        (Without<Player>, Without<RigidBodyDisabled>),
+    >,
+) {
+    for (jump_pad, colliding) in &jump_pads {
+        let Ok(direction) = Dir3::new(jump_pad.0) else {
+            continue;
+        };
+        let speed = jump_pad.0.length();
+
+        for &entity in colliding.iter() {
+            let Ok((rigid_body, mut velocity)) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+
+            let along = velocity.dot(*direction);
+            if along < speed {
+                velocity.0 += direction * (speed - along);
+            }
+        }
+    }
+}
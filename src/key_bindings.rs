@@ -0,0 +1,465 @@
+use crate::ammo::PlayerReload;
+use crate::camera::{PlayerAim, PlayerToggleView};
+use crate::carry::PlayerInteract;
+use crate::grapple::PlayerGrapple;
+use crate::grenade::PlayerGrenade;
+use crate::input_routing::LocalPlayer;
+use crate::inventory::PlayerInventory;
+use crate::melee::PlayerMelee;
+use crate::player_movement::{
+    Player, PlayerAction, PlayerAltAction, PlayerCrouch, PlayerDash, PlayerJump, PlayerLook,
+    PlayerMove, PlayerProne, PlayerSprint,
+};
+use crate::profile::Profile;
+use crate::tools::{PlayerCycleTool, PlayerSelectTool, Tool};
+use bevy::ecs::spawn::SpawnIter;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// The key bindings file's name, in the profile's folder. Read at startup and when the profile
+/// is switched, and written back to after a rebind.
+const KEY_BINDINGS_FILE: &str = "key_bindings.ron";
+
+/// The keys selecting each tool slot, in [`Tool::ALL`] order. These are fixed like the gamepad
+/// bindings.
+const TOOL_SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Starts a rebind: the next input picks the action to rebind, the one after that is its new
+/// binding. Escape cancels.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct StartRebind;
+
+/// The keyboard and mouse bindings of the player's actions. Gamepad bindings are fixed.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub move_forward: Binding,
+    pub move_back: Binding,
+    pub move_left: Binding,
+    pub move_right: Binding,
+    pub jump: Binding,
+    pub action: Binding,
+    pub alt_action: Binding,
+    pub reload: Binding,
+    pub interact: Binding,
+    pub melee: Binding,
+    pub aim: Binding,
+    pub toggle_view: Binding,
+    pub sprint: Binding,
+    pub crouch: Binding,
+    pub prone: Binding,
+    pub dash: Binding,
+    pub grapple: Binding,
+    pub grenade: Binding,
+    pub inventory: Binding,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_forward: KeyCode::KeyW.into(),
+            move_back: KeyCode::KeyS.into(),
+            move_left: KeyCode::KeyA.into(),
+            move_right: KeyCode::KeyD.into(),
+            jump: KeyCode::Space.into(),
+            action: MouseButton::Left.into(),
+            alt_action: KeyCode::KeyG.into(),
+            reload: KeyCode::KeyR.into(),
+            interact: KeyCode::KeyT.into(),
+            melee: KeyCode::KeyC.into(),
+            aim: MouseButton::Right.into(),
+            toggle_view: KeyCode::KeyV.into(),
+            sprint: KeyCode::ShiftLeft.into(),
+            crouch: KeyCode::ControlLeft.into(),
+            prone: KeyCode::KeyZ.into(),
+            dash: KeyCode::KeyQ.into(),
+            grapple: KeyCode::KeyF.into(),
+            grenade: KeyCode::KeyE.into(),
+            inventory: KeyCode::Tab.into(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the bindings from the profile folder `dir`, falling back to the defaults if the
+    /// file is missing or can't be parsed.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(KEY_BINDINGS_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!(
+                    "Couldn't read key bindings from {}: {error}",
+                    path.display()
+                );
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(
+                "Couldn't parse key bindings from {}: {error}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize key bindings: {error}");
+                return;
+            }
+        };
+
+        let path = dir.join(KEY_BINDINGS_FILE);
+        if let Err(error) = fs::create_dir_all(dir).and_then(|()| fs::write(&path, contents)) {
+            error!("Couldn't write key bindings to {}: {error}", path.display());
+        }
+    }
+
+    /// All bindings, so an input can be looked up to find the action it's bound to.
+    fn bindings_mut(&mut self) -> [&mut Binding; 19] {
+        [
+            &mut self.move_forward,
+            &mut self.move_back,
+            &mut self.move_left,
+            &mut self.move_right,
+            &mut self.jump,
+            &mut self.action,
+            &mut self.alt_action,
+            &mut self.reload,
+            &mut self.interact,
+            &mut self.melee,
+            &mut self.aim,
+            &mut self.toggle_view,
+            &mut self.sprint,
+            &mut self.crouch,
+            &mut self.prone,
+            &mut self.dash,
+            &mut self.grapple,
+            &mut self.grenade,
+            &mut self.inventory,
+        ]
+    }
+
+    /// Replaces every use of `old` with `new`. Anything previously bound to `new` takes `old`
+    /// instead, so no action is ever left without a binding. Returns whether `old` was bound.
+    pub fn rebind(&mut self, old: Binding, new: Binding) -> bool {
+        let mut found = false;
+        for binding in self.bindings_mut() {
+            if *binding == old {
+                *binding = new;
+                found = true;
+            } else if *binding == new {
+                *binding = old;
+            }
+        }
+        found
+    }
+
+    /// The actions of local player `player`. Only the keyboard and mouse player gets these
+    /// bindings, everyone gets the fixed gamepad bindings for whichever gamepad is routed to them.
+    pub fn player_actions(
+        &self,
+        player: LocalPlayer,
+        move_stick: &MoveStickSettings,
+    ) -> impl Bundle {
+        let key = move |binding: Binding| {
+            if player.uses_keyboard_mouse() {
+                binding
+            } else {
+                Binding::None
+            }
+        };
+
+        // Grouped, as a flat list would be longer than Bevy's tuples go
+        Actions::<Player>::spawn((
+            (
+                Spawn((
+                    Action::<PlayerJump>::new(),
+                    bindings![key(self.jump), GamepadButton::South],
+                )),
+                Spawn((
+                    Action::<PlayerMove>::new(),
+                    SmoothNudge::new(move_stick.smoothing_rate),
+                    Bindings::spawn((
+                        Cardinal {
+                            north: key(self.move_forward),
+                            east: key(self.move_right),
+                            south: key(self.move_back),
+                            west: key(self.move_left),
+                        },
+                        Axial::left_stick()
+                            .with((move_stick.dead_zone, move_stick.response_curve.modifier())),
+                    )),
+                )),
+                Spawn((
+                    Action::<PlayerLook>::new(),
+                    DeadZone::default(),
+                    Bindings::spawn(Axial::right_stick()),
+                )),
+                Spawn((
+                    Action::<PlayerSprint>::new(),
+                    bindings![key(self.sprint), GamepadButton::LeftThumb],
+                )),
+                Spawn((
+                    Action::<PlayerCrouch>::new(),
+                    bindings![key(self.crouch), GamepadButton::East],
+                )),
+                Spawn((
+                    Action::<PlayerProne>::new(),
+                    bindings![key(self.prone), GamepadButton::DPadDown],
+                )),
+                Spawn((
+                    Action::<PlayerDash>::new(),
+                    bindings![key(self.dash), GamepadButton::RightTrigger],
+                )),
+                Spawn((
+                    Action::<PlayerReload>::new(),
+                    bindings![key(self.reload), GamepadButton::West],
+                )),
+                Spawn((
+                    Action::<PlayerInteract>::new(),
+                    bindings![key(self.interact), GamepadButton::Select],
+                )),
+                Spawn((
+                    Action::<PlayerMelee>::new(),
+                    bindings![key(self.melee), GamepadButton::Mode],
+                )),
+            ),
+            (
+                Spawn((
+                    Action::<PlayerAction>::new(),
+                    bindings![key(self.action), GamepadButton::RightTrigger2],
+                )),
+                Spawn((
+                    Action::<PlayerAltAction>::new(),
+                    bindings![key(self.alt_action), GamepadButton::North],
+                )),
+                Spawn((
+                    Action::<PlayerSelectTool>::new(),
+                    Bindings::spawn(SpawnIter(
+                        TOOL_SLOT_KEYS
+                            .into_iter()
+                            .take(Tool::ALL.len())
+                            .zip(1..)
+                            .map(move |(slot_key, slot)| {
+                                (key(slot_key.into()), Scale::splat(slot as f32))
+                            }),
+                    )),
+                )),
+                Spawn((
+                    Action::<PlayerCycleTool>::new(),
+                    bindings![
+                        (key(Binding::mouse_wheel()), SwizzleAxis::YXZ),
+                        GamepadButton::DPadRight,
+                        (GamepadButton::DPadLeft, Negate::all()),
+                    ],
+                )),
+                Spawn((
+                    Action::<PlayerAim>::new(),
+                    bindings![key(self.aim), GamepadButton::LeftTrigger2],
+                )),
+                Spawn((
+                    Action::<PlayerToggleView>::new(),
+                    bindings![key(self.toggle_view), GamepadButton::RightThumb],
+                )),
+                Spawn((
+                    Action::<PlayerGrapple>::new(),
+                    bindings![key(self.grapple), GamepadButton::LeftTrigger],
+                )),
+                Spawn((
+                    Action::<PlayerGrenade>::new(),
+                    bindings![key(self.grenade), GamepadButton::DPadUp],
+                )),
+                Spawn((
+                    Action::<PlayerInventory>::new(),
+                    bindings![key(self.inventory)],
+                )),
+            ),
+        ))
+    }
+}
+
+/// How stick input is shaped on the way into an action.
+#[derive(Clone, Copy)]
+pub enum ResponseCurve {
+    Linear,
+    /// Each axis is raised to this power. Above 1 small tilts move slower, for finer control.
+    Exponential(f32),
+}
+
+impl ResponseCurve {
+    /// The power each axis is raised to, 1 for linear.
+    pub fn exponent(self) -> f32 {
+        match self {
+            Self::Linear => 1.0,
+            Self::Exponential(exponent) => exponent,
+        }
+    }
+
+    fn modifier(self) -> ExponentialCurve {
+        ExponentialCurve::splat(self.exponent())
+    }
+}
+
+/// The modifiers of the movement stick binding and the movement action.
+#[derive(Resource, Clone, Copy)]
+pub struct MoveStickSettings {
+    /// Applied to the stick binding only, keys are never partially pressed.
+    pub dead_zone: DeadZone,
+    pub response_curve: ResponseCurve,
+    /// How fast movement input eases towards the keys or stick, see [`SmoothNudge`].
+    pub smoothing_rate: f32,
+}
+
+impl Default for MoveStickSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: DeadZone::default(),
+            response_curve: ResponseCurve::Linear,
+            smoothing_rate: SmoothNudge::default().decay_rate,
+        }
+    }
+}
+
+/// Progress of an interactive rebind started with [`StartRebind`].
+#[derive(Resource, Default)]
+pub enum Rebinding {
+    #[default]
+    Idle,
+    /// Waiting for the input currently bound to the action to rebind.
+    AwaitingOld,
+    /// Waiting for the new input to bind in place of this one.
+    AwaitingNew(Binding),
+}
+
+impl Rebinding {
+    /// Whether a rebind is waiting for input, which then isn't meant for anything else.
+    pub fn is_active(&self) -> bool {
+        !matches!(self, Self::Idle)
+    }
+}
+
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        let key_bindings = KeyBindings::load(&app.world().resource::<Profile>().dir());
+        app.insert_resource(key_bindings)
+            .init_resource::<MoveStickSettings>()
+            .init_resource::<Rebinding>()
+            .add_systems(
+                Update,
+                (
+                    load_profile_key_bindings
+                        .run_if(resource_changed::<Profile>.and(not(resource_added::<Profile>))),
+                    capture_rebind,
+                    apply_key_bindings.run_if(
+                        resource_changed::<KeyBindings>.or(resource_changed::<MoveStickSettings>),
+                    ),
+                )
+                    .chain(),
+            )
+            .add_observer(start_rebind);
+    }
+}
+
+/// Swaps in the key bindings of the profile switched to.
+fn load_profile_key_bindings(mut commands: Commands, profile: Res<Profile>) {
+    commands.insert_resource(KeyBindings::load(&profile.dir()));
+}
+
+/// Waits for the key to be released, so it isn't picked up as the input to rebind.
+fn start_rebind(_trigger: Trigger<Completed<StartRebind>>, mut rebinding: ResMut<Rebinding>) {
+    info!("Rebinding: press the input to change, or Escape to cancel");
+    *rebinding = Rebinding::AwaitingOld;
+}
+
+fn capture_rebind(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut key_bindings: ResMut<KeyBindings>,
+    profile: Res<Profile>,
+) {
+    if !rebinding.is_active() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        info!("Rebinding cancelled");
+        *rebinding = Rebinding::Idle;
+        return;
+    }
+
+    let Some(input) = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::from(*key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::from(*button))
+        })
+    else {
+        return;
+    };
+
+    match *rebinding {
+        Rebinding::Idle => {}
+        Rebinding::AwaitingOld => {
+            info!("Rebinding {input:?}: press the new input");
+            *rebinding = Rebinding::AwaitingNew(input);
+        }
+        Rebinding::AwaitingNew(old) => {
+            *rebinding = Rebinding::Idle;
+            if key_bindings.rebind(old, input) {
+                info!("Rebound {old:?} to {input:?}");
+                key_bindings.save(&profile.dir());
+            } else {
+                info!("{old:?} isn't bound to anything");
+            }
+        }
+    }
+}
+
+/// Respawns the player's action entities after the bindings or stick settings changed at
+/// runtime.
+fn apply_key_bindings(
+    mut commands: Commands,
+    key_bindings: Res<KeyBindings>,
+    move_stick: Res<MoveStickSettings>,
+    players: Query<(Entity, &LocalPlayer), With<Player>>,
+) {
+    if key_bindings.is_added() {
+        return;
+    }
+
+    for (player, local_player) in &players {
+        commands
+            .entity(player)
+            .despawn_related::<Actions<Player>>()
+            .insert(key_bindings.player_actions(*local_player, &move_stick));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::ball::{Ball, BallPool};
+use crate::checkpoint::Respawning;
+use crate::player_movement::Player;
+use crate::trigger::{TriggerEnter, TriggerVolume};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// Anything falling below this height has fallen off the map: players are respawned, and
+/// dynamic bodies are despawned so they don't keep falling forever. Set by each level.
+#[derive(Resource)]
+pub struct KillPlane {
+    pub height: Scalar,
+}
+
+impl Default for KillPlane {
+    fn default() -> Self {
+        Self { height: -50.0 }
+    }
+}
+
+/// A trigger volume that works like the [`KillPlane`] for whatever enters it, for pits and
+/// chasms above the kill plane.
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct KillZone;
+
+pub struct KillPlanePlugin;
+
+impl Plugin for KillPlanePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillPlane>()
+            .add_systems(Update, (respawn_fallen_players, despawn_fallen_bodies))
+            .add_observer(enter_kill_zone);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn respawn_fallen_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    kill_plane: Res<KillPlane>,
+    players: Query<(Entity, &Position), (With<Player>, Without<Respawning>)>,
+) {
+    for (entity, position) in &players {
+        if position.y < kill_plane.height {
+            commands
+                .entity(entity)
+                .insert(Respawning::new(time.elapsed_secs()));
+        }
+    }
+}
+
+/// Despawns dynamic bodies that fell off the map, putting balls back into the [`BallPool`].
+#[allow(clippy::type_complexity)]
+fn despawn_fallen_bodies(
+    mut commands: Commands,
+    kill_plane: Res<KillPlane>,
+    mut ball_pool: ResMut<BallPool>,
+    // Pooled balls are disabled wherever they were released, and shouldn't be released again
+    bodies: Query<
+        (Entity, &RigidBody, &Position, Has<Ball>),
+        (Without<Player>, Without<RigidBodyDisabled>),
+    >,
+) {
+    for (entity, rigid_body, position, is_ball) in &bodies {
+        if !rigid_body.is_dynamic() || position.y >= kill_plane.height {
+            continue;
+        }
+
+        if is_ball {
+            ball_pool.release(&mut commands, entity);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn enter_kill_zone(
+    trigger: Trigger<TriggerEnter>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut ball_pool: ResMut<BallPool>,
+    kill_zones: Query<(), With<KillZone>>,
+    players: Query<(), (With<Player>, Without<Respawning>)>,
+    bodies: Query<(&RigidBody, Has<Ball>), (Without<Player>, Without<RigidBodyDisabled>)>,
+) {
+    if !kill_zones.contains(trigger.target()) {
+        return;
+    }
+
+    let entity = trigger.entity;
+    if players.contains(entity) {
+        commands
+            .entity(entity)
+            .insert(Respawning::new(time.elapsed_secs()));
+    } else if let Ok((rigid_body, is_ball)) = bodies.get(entity) {
+        if !rigid_body.is_dynamic() {
+            return;
+        }
+        if is_ball {
+            ball_pool.release(&mut commands, entity);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::settings::WindowModeSetting;
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::audio::AudioPlugin;
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::WgpuSettings;
+use bevy::window::{ExitCondition, WindowResolution};
+use bevy::winit::WinitPlugin;
+use std::env;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: playground [options]
+
+Options:
+  --windowed            Play in a window
+  --borderless          Play in a borderless window covering the screen
+  --fullscreen          Play in exclusive fullscreen
+  --resolution WxH      The window's size, like 1280x720
+  --level NAME          Start straight in the named level, like arena
+  --seed N              Generate every level's terrain from this seed
+  --headless            Run without a window or rendering, straight in game
+  -h, --help            Show this help";
+
+/// How often a headless app updates, since there's no window to pace it.
+const HEADLESS_UPDATE_RATE: f64 = 60.0;
+
+/// Options given on the command line, parsed before the app is built. The window mode replaces
+/// the one from the [`Settings`](crate::settings::Settings) for this run.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct LaunchOptions {
+    pub window_mode: Option<WindowModeSetting>,
+    /// The window's size in logical pixels.
+    pub resolution: Option<(u32, u32)>,
+    /// The name of the level to start in, skipping the main menu.
+    pub level: Option<String>,
+    /// Replaces the seed of every terrain spawned.
+    pub seed: Option<u64>,
+    /// Runs the game without a window, rendering or audio output, like on a server.
+    pub headless: bool,
+}
+
+impl LaunchOptions {
+    /// Parses the process's arguments, printing the usage and exiting on `--help` or arguments
+    /// that don't parse.
+    pub fn from_env() -> Self {
+        match Self::parse(env::args().skip(1)) {
+            Ok(Some(options)) => options,
+            Ok(None) => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            Err(error) => {
+                eprintln!("{error}\n\n{USAGE}");
+                process::exit(2);
+            }
+        }
+    }
+
+    /// Parses arguments, not including the program name. Returns `None` if help was asked for.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+            match arg.as_str() {
+                "--windowed" => options.window_mode = Some(WindowModeSetting::Windowed),
+                "--borderless" => {
+                    options.window_mode = Some(WindowModeSetting::BorderlessFullscreen);
+                }
+                "--fullscreen" => options.window_mode = Some(WindowModeSetting::Fullscreen),
+                "--resolution" => {
+                    let resolution = value("--resolution")?;
+                    options.resolution =
+                        Some(parse_resolution(&resolution).ok_or_else(|| {
+                            format!("Resolution {resolution:?} isn't like 1280x720")
+                        })?);
+                }
+                "--level" => options.level = Some(value("--level")?),
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("Seed {seed:?} isn't a whole number"))?,
+                    );
+                }
+                "--headless" => options.headless = true,
+                "-h" | "--help" => return Ok(None),
+                _ => return Err(format!("Unknown argument {arg:?}")),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    /// Whether to go straight into the game instead of starting on the main menu.
+    pub fn skips_main_menu(&self) -> bool {
+        self.level.is_some() || self.headless
+    }
+
+    /// Bevy's default plugins, set up for these options: the primary window sized as asked, or
+    /// no window, rendering, audio or event loop at all when headless.
+    pub fn default_plugins(&self) -> PluginGroupBuilder {
+        if self.headless {
+            return DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .disable::<WinitPlugin>()
+                .disable::<AudioPlugin>()
+                .add(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                    1.0 / HEADLESS_UPDATE_RATE,
+                )));
+        }
+
+        let mut window = Window::default();
+        if let Some((width, height)) = self.resolution {
+            window.resolution = WindowResolution::new(width as f32, height as f32);
+        }
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(window),
+            ..default()
+        })
+    }
+}
+
+fn parse_resolution(resolution: &str) -> Option<(u32, u32)> {
+    let (width, height) = resolution.split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<LaunchOptions>, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let options = parse(&[
+            "--borderless",
+            "--resolution",
+            "1280x720",
+            "--level",
+            "arena",
+            "--seed",
+            "42",
+            "--headless",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            options.window_mode,
+            Some(WindowModeSetting::BorderlessFullscreen)
+        );
+        assert_eq!(options.resolution, Some((1280, 720)));
+        assert_eq!(options.level.as_deref(), Some("arena"));
+        assert_eq!(options.seed, Some(42));
+        assert!(options.headless);
+    }
+
+    #[test]
+    fn help_parses_to_none() {
+        assert!(matches!(parse(&["--help"]), Ok(None)));
+        assert!(matches!(parse(&["--windowed", "-h"]), Ok(None)));
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(parse(&["--fly"]).is_err());
+    }
+
+    #[test]
+    fn rejects_options_missing_their_value() {
+        assert!(parse(&["--level"]).is_err());
+        assert!(parse(&["--resolution"]).is_err());
+    }
+
+    #[test]
+    fn rejects_seeds_that_are_not_whole_numbers() {
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_resolutions() {
+        assert_eq!(parse_resolution("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_resolution("0x720"), None);
+        assert_eq!(parse_resolution("1280"), None);
+        assert_eq!(parse_resolution("1280x"), None);
+        assert_eq!(parse_resolution("wide x tall"), None);
+    }
+}
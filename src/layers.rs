@@ -0,0 +1,44 @@
+use avian3d::prelude::*;
+
+/// The physics layers used to filter collisions and spatial queries.
+#[derive(PhysicsLayer, Default)]
+pub enum GameLayer {
+    /// Level geometry and the rest of the solid world. Colliders without explicit layers end
+    /// up here.
+    #[default]
+    World,
+    /// Characters controlled by players.
+    Player,
+    /// Characters controlled by AI, see [`Enemy`](crate::enemy::Enemy).
+    Enemy,
+    /// Thrown and fired objects, like balls and grenades.
+    Projectile,
+    /// Loose dynamic bodies to push and throw around, like cubes.
+    Prop,
+    /// Water volumes, which only queries looking for them should hit.
+    Water,
+    /// Ladder volumes, which characters climb while overlapping.
+    Ladder,
+    /// Hazard volumes, which hurt characters overlapping them.
+    Hazard,
+    /// Trigger volumes like checkpoints, which react to characters overlapping them.
+    Trigger,
+    /// The body parts of ragdolls, which collide with solid geometry but not with each other.
+    Ragdoll,
+    /// The segments of ropes and chains, which collide with solid geometry but not with each
+    /// other.
+    Rope,
+}
+
+impl GameLayer {
+    /// Every solid layer, for spatial queries looking for something to hit or stand on.
+    pub fn solid() -> LayerMask {
+        LayerMask::from([
+            Self::World,
+            Self::Player,
+            Self::Enemy,
+            Self::Projectile,
+            Self::Prop,
+        ])
+    }
+}
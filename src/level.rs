@@ -0,0 +1,1059 @@
+use crate::boundary::boundary_walls;
+use crate::checkpoint::CheckpointBundle;
+use crate::combat::Hazard;
+use crate::dialog::Npc;
+use crate::door::Door;
+use crate::explosion::ExplosiveBarrelBundle;
+use crate::gravity_zone::{GravityField, GravityZone};
+use crate::jump_pad::JumpPad;
+use crate::kill_plane::{KillPlane, KillZone};
+use crate::launch::LaunchOptions;
+use crate::layers::GameLayer;
+use crate::main_menu::GameState;
+use crate::obstacle_course::ObstacleCourse;
+use crate::pause::PauseState;
+use crate::pickup::{PickupBundle, PickupKind};
+use crate::platform::{MovingPlatform, PlatformActivation, PlatformEasing, PlatformPath};
+use crate::player_movement::Ladder;
+use crate::push::PushLimit;
+use crate::quest::{Quest, QuestLog};
+use crate::rope::{RopeEnd, RopeLook, RopeSettings, spawn_rope};
+use crate::spawn_point::{SpawnKind, SpawnPoint};
+use crate::streaming::{ChunkLoaded, InChunk, LoadedChunks, chunk_at};
+use crate::surface::SurfaceMaterial;
+use crate::terrain::Terrain;
+use crate::trigger::TriggerVolumeBundle;
+use crate::water::WaterVolumeBundle;
+use crate::wiring::{PressurePlateBundle, SignalTargets};
+use avian3d::prelude::*;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use bevy::scene::SceneInstanceReady;
+use bevy_enhanced_input::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Every level, relative to the assets folder, in the order [`NextLevel`] goes through them.
+/// The first one is loaded at startup.
+const LEVELS: [&str; 3] = [
+    "levels/arena.level.ron",
+    "levels/hills.level.ron",
+    "levels/expanse.level.ron",
+];
+/// Nodes of a level's glTF scene named with these prefixes get a static collider made from
+/// their meshes. Trimeshes match the mesh exactly, convex hulls are cheaper to collide with.
+const TRIMESH_NODE_PREFIX: &str = "static_";
+const CONVEX_NODE_PREFIX: &str = "convex_";
+/// Nodes named with this prefix are player spawn points, or enemy spawn points if it's followed
+/// by [`ENEMY_SPAWN_NODE_INFIX`].
+const SPAWN_NODE_PREFIX: &str = "spawn_";
+const ENEMY_SPAWN_NODE_INFIX: &str = "enemy";
+/// Nodes named with this prefix become point lights, for exporters that don't write glTF
+/// lights.
+const LIGHT_NODE_PREFIX: &str = "light_";
+
+/// A level read from a `.level.ron` file: the arena layout, its props and lights, and where
+/// characters spawn. Editing the file changes the level without recompiling.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Level {
+    /// The height of the level's [`KillPlane`], or the default one's if not set.
+    #[serde(default)]
+    pub kill_height: Option<f32>,
+    /// How wide the square playable area walled in by invisible
+    /// [`BoundaryWall`](crate::boundary::BoundaryWall)s is. Defaults to the size of the level's
+    /// ground or terrain, and levels with neither aren't walled in.
+    #[serde(default)]
+    pub bounds: Option<f32>,
+    pub objects: Vec<LevelObject>,
+    /// Goals to work through while playing the level. See [`Quest`].
+    #[serde(default)]
+    pub quests: Vec<Quest>,
+}
+
+impl Level {
+    fn bounds(&self) -> Option<f32> {
+        self.bounds.or_else(|| {
+            self.objects.iter().find_map(|object| match *object {
+                LevelObject::Ground { size, .. } | LevelObject::Terrain { size, .. } => Some(size),
+                _ => None,
+            })
+        })
+    }
+}
+
+/// Something placed in a [`Level`]. Sizes are full extents, colors are sRGB. Objects can be
+/// given a `name` for pressure plates to be wired to, and ground geometry a `surface` material.
+#[derive(Deserialize)]
+pub enum LevelObject {
+    /// An endless floor at height zero, with a square mesh of the given size drawn on it.
+    Ground {
+        size: f32,
+        color: [f32; 3],
+        #[serde(default)]
+        surface: Option<SurfaceMaterial>,
+    },
+    /// Noise-generated hills centered on the origin, instead of a flat ground. See [`Terrain`].
+    Terrain {
+        size: f32,
+        chunks: u32,
+        height: f32,
+        seed: u64,
+    },
+    /// A line of generated obstacles going off `yaw` degrees counterclockwise from -Z, with the
+    /// default set of obstacles if none are given. See [`ObstacleCourse`].
+    ObstacleCourse {
+        position: Vec3,
+        #[serde(default)]
+        yaw: f32,
+        #[serde(default)]
+        course: ObstacleCourse,
+    },
+    /// Static scenery, like walls, steps and platforms. Streamed in and out with the chunk it's
+    /// in, as players come close and move away.
+    Block {
+        size: Vec3,
+        position: Vec3,
+        color: [f32; 3],
+        #[serde(default)]
+        surface: Option<SurfaceMaterial>,
+    },
+    /// A dynamic box that can be pushed and thrown around, by players with no more than
+    /// `push_limit` newtons if it's set. See [`PushLimit`].
+    Prop {
+        size: Vec3,
+        position: Vec3,
+        color: [f32; 3],
+        mass: f32,
+        #[serde(default)]
+        angular_velocity: Vec3,
+        #[serde(default)]
+        push_limit: Option<f32>,
+    },
+    /// A rope of `segments` jointed segments hanging from `start`. It's tied down at `end` too,
+    /// unless it has a `weight`, in which case a box of that mass hangs from there instead.
+    Rope {
+        start: Vec3,
+        end: Vec3,
+        segments: u32,
+        #[serde(default)]
+        look: RopeLook,
+        #[serde(default)]
+        weight: Option<f32>,
+    },
+    Ladder {
+        size: Vec3,
+        position: Vec3,
+    },
+    Water {
+        size: Vec3,
+        position: Vec3,
+    },
+    /// A kinematic platform starting at the first waypoint. See [`MovingPlatform`].
+    MovingPlatform {
+        #[serde(default)]
+        name: Option<String>,
+        size: Vec3,
+        waypoints: Vec<Vec3>,
+        speed: f32,
+        #[serde(default)]
+        path: PlatformPath,
+        #[serde(default)]
+        easing: PlatformEasing,
+        #[serde(default)]
+        activation: PlatformActivation,
+    },
+    /// A pad launching characters and bodies on it with `impulse`. See [`JumpPad`].
+    JumpPad {
+        radius: f32,
+        impulse: Vec3,
+        position: Vec3,
+    },
+    /// A barrel blowing up when shot enough or signalled, with a name to be wired to. See
+    /// [`ExplosiveBarrel`](crate::explosion::ExplosiveBarrel).
+    ExplosiveBarrel {
+        #[serde(default)]
+        name: Option<String>,
+        position: Vec3,
+    },
+    /// A volume hurting whatever stands in it by `damage` per second, drawn as a thin slab at
+    /// its bottom.
+    Hazard {
+        damage: f32,
+        size: Vec3,
+        position: Vec3,
+    },
+    PointLight {
+        position: Vec3,
+        shadows: bool,
+    },
+    /// A sliding door, closed where it's placed and open `open_offset` away from there. See
+    /// [`Door`].
+    Door {
+        #[serde(default)]
+        name: Option<String>,
+        size: Vec3,
+        position: Vec3,
+        open_offset: Vec3,
+        speed: f32,
+        #[serde(default)]
+        locked: bool,
+    },
+    /// A volume sending a signal to the objects named in `targets` while something's on it,
+    /// drawn as a slab at its bottom. See [`PressurePlate`](crate::wiring::PressurePlate).
+    PressurePlate {
+        size: Vec3,
+        position: Vec3,
+        targets: Vec<String>,
+    },
+    /// A volume the player respawns at after touching it. See
+    /// [`Checkpoint`](crate::checkpoint::Checkpoint).
+    Checkpoint {
+        size: Vec3,
+        position: Vec3,
+    },
+    /// A volume with its own uniform `gravity` for the dynamic bodies in it, winning over
+    /// zones of lower `priority` it overlaps. See [`GravityZone`].
+    GravityZone {
+        size: Vec3,
+        position: Vec3,
+        gravity: Vec3,
+        #[serde(default)]
+        priority: i32,
+    },
+    /// A solid ball of `radius` pulling bodies within `atmosphere` of its center toward it,
+    /// over any gravity zones it overlaps.
+    Planet {
+        radius: f32,
+        atmosphere: f32,
+        strength: f32,
+        position: Vec3,
+        color: [f32; 3],
+    },
+    /// A volume respawning players and despawning bodies that fall into it. See [`KillZone`].
+    KillZone {
+        size: Vec3,
+        position: Vec3,
+    },
+    /// Something for the player to pick up, coming back `respawn` seconds after being taken if
+    /// set. See [`Pickup`](crate::pickup::Pickup).
+    Pickup {
+        kind: PickupKind,
+        position: Vec3,
+        #[serde(default)]
+        respawn: Option<f32>,
+    },
+    /// A friendly character facing `yaw` degrees counterclockwise from -Z, talked to through the
+    /// dialog file at `dialog`, relative to the assets folder. Its choices can send signals to
+    /// the objects named in `targets`. See [`Npc`].
+    Npc {
+        dialog: String,
+        position: Vec3,
+        #[serde(default)]
+        yaw: f32,
+        #[serde(default)]
+        targets: Vec<String>,
+    },
+    /// A place characters spawn, facing `yaw` degrees counterclockwise from -Z. See
+    /// [`SpawnPoint`].
+    SpawnPoint {
+        position: Vec3,
+        #[serde(default)]
+        yaw: f32,
+        #[serde(default)]
+        kind: SpawnKind,
+        #[serde(default)]
+        team: Option<u8>,
+    },
+    /// The first scene of a glTF file, relative to the assets folder. Its nodes are set up by
+    /// their names, see [`setup_scene_nodes`].
+    Scene {
+        path: String,
+        position: Vec3,
+    },
+}
+
+impl LevelObject {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        asset_server: &AssetServer,
+    ) -> Entity {
+        match *self {
+            Self::Ground {
+                size,
+                color,
+                surface,
+            } => {
+                let mut ground = commands.spawn((
+                    LevelEntity,
+                    RigidBody::Static,
+                    Collider::half_space(Vec3::Y),
+                    Mesh3d(meshes.add(Plane3d::default().mesh().size(size, size))),
+                    MeshMaterial3d(materials.add(srgb(color))),
+                ));
+                if let Some(surface) = surface {
+                    ground.insert(surface);
+                }
+                ground.id()
+            }
+            Self::Terrain {
+                size,
+                chunks,
+                height,
+                seed,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    Terrain {
+                        size,
+                        chunks,
+                        height,
+                        seed,
+                    },
+                ))
+                .id(),
+            Self::ObstacleCourse {
+                position,
+                yaw,
+                ref course,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    course.clone(),
+                    Transform::from_translation(position)
+                        .with_rotation(Quat::from_rotation_y(yaw.to_radians())),
+                ))
+                .id(),
+            Self::Block {
+                size,
+                position,
+                color,
+                surface,
+            } => {
+                let mut block = commands.spawn((
+                    LevelEntity,
+                    RigidBody::Static,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Mesh3d(meshes.add(Cuboid::from_size(size))),
+                    MeshMaterial3d(materials.add(srgb(color))),
+                    Transform::from_translation(position),
+                ));
+                if let Some(surface) = surface {
+                    block.insert(surface);
+                }
+                block.id()
+            }
+            Self::Prop {
+                size,
+                position,
+                color,
+                mass,
+                angular_velocity,
+                push_limit,
+            } => {
+                let mut prop = commands.spawn((
+                    LevelEntity,
+                    RigidBody::Dynamic,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    CollisionLayers::new(GameLayer::Prop, LayerMask::ALL),
+                    AngularVelocity(angular_velocity),
+                    Mesh3d(meshes.add(Cuboid::from_size(size))),
+                    MeshMaterial3d(materials.add(srgb(color))),
+                    Transform::from_translation(position),
+                    Mass(mass),
+                ));
+                if let Some(push_limit) = push_limit {
+                    prop.insert(PushLimit(push_limit));
+                }
+                prop.id()
+            }
+            Self::Rope {
+                start,
+                end,
+                segments,
+                look,
+                weight,
+            } => {
+                let settings = RopeSettings {
+                    segments,
+                    look,
+                    ..default()
+                };
+                let Some(weight) = weight else {
+                    let rope = spawn_rope(
+                        commands,
+                        meshes,
+                        materials,
+                        RopeEnd::fixed(start),
+                        RopeEnd::fixed(end),
+                        &settings,
+                    );
+                    commands.entity(rope).insert(LevelEntity);
+                    return rope;
+                };
+
+                // Hung by the middle of its top, right where the rope ends
+                let size = Vec3::splat(0.6);
+                let center = end - Vec3::Y * size.y / 2.0;
+                let body = commands
+                    .spawn((
+                        RigidBody::Dynamic,
+                        Collider::cuboid(size.x, size.y, size.z),
+                        CollisionLayers::new(GameLayer::Prop, LayerMask::ALL),
+                        Mesh3d(meshes.add(Cuboid::from_size(size))),
+                        MeshMaterial3d(materials.add(Color::srgb(0.4, 0.4, 0.45))),
+                        Transform::from_translation(center),
+                        Mass(weight),
+                    ))
+                    .id();
+                let rope = spawn_rope(
+                    commands,
+                    meshes,
+                    materials,
+                    RopeEnd::fixed(start),
+                    RopeEnd::body(end, body, Vec3::Y * size.y / 2.0),
+                    &settings,
+                );
+                commands.entity(body).insert(ChildOf(rope));
+                commands.entity(rope).insert(LevelEntity);
+                rope
+            }
+            Self::Ladder { size, position } => commands
+                .spawn((
+                    LevelEntity,
+                    Ladder,
+                    RigidBody::Static,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Sensor,
+                    CollisionLayers::new(GameLayer::Ladder, LayerMask::ALL),
+                    Mesh3d(meshes.add(Cuboid::from_size(size))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.6, 0.4, 0.2))),
+                    Transform::from_translation(position),
+                ))
+                .id(),
+            Self::Water { size, position } => commands
+                .spawn((
+                    LevelEntity,
+                    WaterVolumeBundle::new(
+                        meshes,
+                        materials,
+                        size,
+                        Transform::from_translation(position),
+                    ),
+                ))
+                .with_child(WaterVolumeBundle::surface(meshes, materials, size))
+                .id(),
+            Self::MovingPlatform {
+                name: _,
+                size,
+                ref waypoints,
+                speed,
+                path,
+                easing,
+                activation,
+            } => {
+                let start = waypoints.first().copied().unwrap_or_default();
+                commands
+                    .spawn((
+                        LevelEntity,
+                        MovingPlatform::along(waypoints.clone(), speed)
+                            .with_path(path)
+                            .with_easing(easing)
+                            .with_activation(activation),
+                        RigidBody::Kinematic,
+                        Collider::cuboid(size.x, size.y, size.z),
+                        Mesh3d(meshes.add(Cuboid::from_size(size))),
+                        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.6, 0.3))),
+                        Transform::from_translation(start),
+                    ))
+                    .id()
+            }
+            Self::JumpPad {
+                radius,
+                impulse,
+                position,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    JumpPad(impulse),
+                    RigidBody::Static,
+                    Collider::cylinder(radius, 0.1),
+                    Mesh3d(meshes.add(Cylinder::new(radius, 0.1))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.9, 0.5, 0.1))),
+                    Transform::from_translation(position),
+                ))
+                .id(),
+            Self::ExplosiveBarrel { name: _, position } => commands
+                .spawn((
+                    LevelEntity,
+                    ExplosiveBarrelBundle::new(
+                        meshes,
+                        materials,
+                        Transform::from_translation(position),
+                    ),
+                ))
+                .id(),
+            // Characters float above the ground, so the hazard is a volume above the slab
+            // rather than the slab itself
+            Self::Hazard {
+                damage,
+                size,
+                position,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    Hazard::new(damage),
+                    RigidBody::Static,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Sensor,
+                    CollisionLayers::new(GameLayer::Hazard, LayerMask::ALL),
+                    Transform::from_translation(position),
+                    Visibility::default(),
+                ))
+                .with_child((
+                    Mesh3d(meshes.add(Cuboid::new(size.x, 0.02, size.z))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.9, 0.2, 0.0))),
+                    Transform::from_xyz(0.0, 0.01 - size.y / 2.0, 0.0),
+                ))
+                .id(),
+            Self::PointLight { position, shadows } => commands
+                .spawn((
+                    LevelEntity,
+                    PointLight {
+                        shadows_enabled: shadows,
+                        ..default()
+                    },
+                    Transform::from_translation(position),
+                ))
+                .id(),
+            Self::Door {
+                name: _,
+                size,
+                position,
+                open_offset,
+                speed,
+                locked,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    Door::new(position, open_offset, speed).with_locked(locked),
+                    RigidBody::Kinematic,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Mesh3d(meshes.add(Cuboid::from_size(size))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.55, 0.35, 0.2))),
+                    Transform::from_translation(position),
+                ))
+                .id(),
+            // Wired up once every object has spawned, in `spawn_level`
+            Self::PressurePlate {
+                size,
+                position,
+                targets: _,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    PressurePlateBundle::new(size, Transform::from_translation(position)),
+                ))
+                .with_child(PressurePlateBundle::slab(meshes, materials, size))
+                .id(),
+            Self::Checkpoint { size, position } => commands
+                .spawn((
+                    LevelEntity,
+                    CheckpointBundle::new(
+                        meshes,
+                        materials,
+                        size,
+                        Transform::from_translation(position),
+                    ),
+                ))
+                .id(),
+            Self::GravityZone {
+                size,
+                position,
+                gravity,
+                priority,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    GravityZone {
+                        field: GravityField::Uniform(gravity),
+                        priority,
+                    },
+                    TriggerVolumeBundle::new(
+                        Collider::cuboid(size.x, size.y, size.z),
+                        Transform::from_translation(position),
+                    ),
+                ))
+                .id(),
+            Self::Planet {
+                radius,
+                atmosphere,
+                strength,
+                position,
+                color,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    RigidBody::Static,
+                    Collider::sphere(radius),
+                    Mesh3d(meshes.add(Sphere::new(radius))),
+                    MeshMaterial3d(materials.add(srgb(color))),
+                    Transform::from_translation(position),
+                ))
+                .with_child((
+                    GravityZone {
+                        field: GravityField::Attractor { strength },
+                        priority: i32::MAX,
+                    },
+                    TriggerVolumeBundle::new(Collider::sphere(atmosphere), Transform::default()),
+                ))
+                .id(),
+            Self::KillZone { size, position } => commands
+                .spawn((
+                    LevelEntity,
+                    KillZone,
+                    TriggerVolumeBundle::new(
+                        Collider::cuboid(size.x, size.y, size.z),
+                        Transform::from_translation(position),
+                    ),
+                ))
+                .id(),
+            Self::Pickup {
+                kind,
+                position,
+                respawn,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    PickupBundle::new(kind, respawn, Transform::from_translation(position)),
+                ))
+                .with_child(PickupBundle::visual(meshes, materials, kind))
+                .id(),
+            Self::SpawnPoint {
+                position,
+                yaw,
+                kind,
+                team,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    SpawnPoint { kind, team },
+                    Transform::from_translation(position)
+                        .with_rotation(Quat::from_rotation_y(yaw.to_radians())),
+                ))
+                .id(),
+            // Wired up once every object has spawned, in `spawn_level`
+            Self::Npc {
+                ref dialog,
+                position,
+                yaw,
+                targets: _,
+            } => commands
+                .spawn((
+                    LevelEntity,
+                    Npc {
+                        dialog: asset_server.load(dialog.clone()),
+                    },
+                    RigidBody::Static,
+                    Collider::capsule(0.4, 1.0),
+                    Mesh3d(meshes.add(Capsule3d::new(0.4, 1.0))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.3, 0.6, 0.8))),
+                    Transform::from_translation(position)
+                        .with_rotation(Quat::from_rotation_y(yaw.to_radians())),
+                ))
+                .id(),
+            Self::Scene { ref path, position } => commands
+                .spawn((
+                    LevelEntity,
+                    LevelScene,
+                    SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.clone()))),
+                    Transform::from_translation(position),
+                ))
+                .id(),
+        }
+    }
+
+    /// The chunk the object is streamed in with, if it's streamed rather than spawned along
+    /// with the level. Only static scenery is streamed; dynamic bodies left over a chunk that
+    /// unloads are frozen until it loads again, so they don't fall through it.
+    fn chunk(&self) -> Option<IVec2> {
+        match *self {
+            Self::Block { position, .. } => Some(chunk_at(position.xz())),
+            _ => None,
+        }
+    }
+
+    /// The name other objects refer to this one by, if it has one.
+    fn name(&self) -> Option<&str> {
+        match self {
+            Self::MovingPlatform { name, .. }
+            | Self::Door { name, .. }
+            | Self::ExplosiveBarrel { name, .. } => name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The names of the objects this one sends signals to.
+    fn targets(&self) -> &[String] {
+        match self {
+            Self::PressurePlate { targets, .. } | Self::Npc { targets, .. } => targets,
+            _ => &[],
+        }
+    }
+}
+
+fn srgb([red, green, blue]: [f32; 3]) -> Color {
+    Color::srgb(red, green, blue)
+}
+
+/// Spawned from the current [`Level`], and despawned when it's reloaded.
+#[derive(Component)]
+pub struct LevelEntity;
+
+/// The root of a glTF scene placed in a level.
+#[derive(Component)]
+struct LevelScene;
+
+/// The level being played, by its index in [`LEVELS`].
+#[derive(Resource)]
+pub struct CurrentLevel {
+    index: usize,
+    handle: Handle<Level>,
+}
+
+#[derive(SubStates, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[source(GameState = GameState::InGame)]
+pub enum LevelState {
+    /// The current level is loading. Game time is stopped and a loading screen is shown.
+    #[default]
+    Loading,
+    Playing,
+}
+
+/// Sent to despawn the current level and switch to the one at this index in [`LEVELS`].
+#[derive(Event)]
+pub struct LoadLevel(pub usize);
+
+/// Switches to the next level in [`LEVELS`], wrapping around.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct NextLevel;
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_sub_state::<LevelState>()
+            .add_event::<LoadLevel>()
+            .add_systems(OnEnter(GameState::InGame), load_first_level)
+            .add_systems(OnExit(GameState::InGame), despawn_level)
+            .add_systems(OnEnter(LevelState::Loading), show_loading_screen)
+            .add_systems(OnExit(LevelState::Loading), hide_loading_screen)
+            .add_systems(
+                Update,
+                (
+                    load_level,
+                    reload_modified_level,
+                    spawn_level
+                        .run_if(in_state(LevelState::Loading))
+                        .run_if(resource_exists::<CurrentLevel>),
+                )
+                    .chain(),
+            )
+            .add_observer(spawn_streamed_objects)
+            .add_observer(setup_scene_nodes)
+            .add_observer(next_level);
+    }
+}
+
+/// Loads the level named on the command line, or else the first one.
+fn load_first_level(options: Res<LaunchOptions>, mut load_level: EventWriter<LoadLevel>) {
+    let index = options.level.as_deref().map_or(0, |name| {
+        level_index(name).unwrap_or_else(|| {
+            warn!("There is no level named {name:?}");
+            0
+        })
+    });
+    load_level.write(LoadLevel(index));
+}
+
+/// Despawns the level when the game is left for the main menu, so the next game loads afresh.
+fn despawn_level(mut commands: Commands, spawned: Query<Entity, With<LevelEntity>>) {
+    for entity in &spawned {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<CurrentLevel>();
+}
+
+fn next_level(
+    _trigger: Trigger<Started<NextLevel>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut load_level: EventWriter<LoadLevel>,
+) {
+    let next = current_level.map_or(0, |level| (level.index + 1) % LEVELS.len());
+    load_level.write(LoadLevel(next));
+}
+
+/// The index in [`LEVELS`] of the level with this file name, without its folder or extension.
+fn level_index(name: &str) -> Option<usize> {
+    LEVELS.iter().position(|path| {
+        path.strip_prefix("levels/")
+            .and_then(|path| path.strip_suffix(".level.ron"))
+            == Some(name)
+    })
+}
+
+/// Despawns the current level and starts loading the requested one.
+fn load_level(
+    mut commands: Commands,
+    mut events: EventReader<LoadLevel>,
+    mut next_level_state: ResMut<NextState<LevelState>>,
+    asset_server: Res<AssetServer>,
+    spawned: Query<Entity, With<LevelEntity>>,
+) {
+    // Only the latest request matters
+    let Some(&LoadLevel(index)) = events.read().last() else {
+        return;
+    };
+    let Some(path) = LEVELS.get(index) else {
+        warn!("There is no level {index}");
+        return;
+    };
+
+    for entity in &spawned {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(CurrentLevel {
+        index,
+        handle: asset_server.load(*path),
+    });
+    next_level_state.set(LevelState::Loading);
+}
+
+/// Spawns the current level again whenever its file changes.
+fn reload_modified_level(
+    mut events: EventReader<AssetEvent<Level>>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut next_level_state: ResMut<NextState<LevelState>>,
+) {
+    let Some(current_level) = current_level else {
+        return;
+    };
+
+    for event in events.read() {
+        if event.is_modified(&current_level.handle) {
+            next_level_state.set(LevelState::Loading);
+        }
+    }
+}
+
+/// Spawns the current level once it has loaded, replacing anything left of the previous one.
+#[allow(clippy::too_many_arguments)]
+fn spawn_level(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    mut next_level_state: ResMut<NextState<LevelState>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    spawned: Query<Entity, With<LevelEntity>>,
+) {
+    let Some(level) = levels.get(&current_level.handle) else {
+        return;
+    };
+
+    for entity in &spawned {
+        commands.entity(entity).despawn();
+    }
+    let mut named = HashMap::new();
+    let mut wired = Vec::new();
+    for object in level
+        .objects
+        .iter()
+        .filter(|object| object.chunk().is_none())
+    {
+        let entity = object.spawn(&mut commands, &mut meshes, &mut materials, &asset_server);
+        if let Some(name) = object.name() {
+            named.insert(name, entity);
+        }
+        if !object.targets().is_empty() {
+            wired.push((entity, object.targets()));
+        }
+    }
+    // Objects can be wired to ones further down the file, so wiring waits until all have spawned
+    for (entity, targets) in wired {
+        let targets = targets
+            .iter()
+            .filter_map(|name| {
+                let target = named.get(name.as_str()).copied();
+                if target.is_none() {
+                    warn!("There is no object named {name:?} to wire to");
+                }
+                target
+            })
+            .collect();
+        commands.entity(entity).insert(SignalTargets(targets));
+    }
+    if let Some(size) = level.bounds() {
+        for wall in boundary_walls(size) {
+            commands.spawn((LevelEntity, wall));
+        }
+    }
+    commands.insert_resource(LoadedChunks::default());
+    commands.insert_resource(QuestLog::new(&level.quests));
+    commands.insert_resource(
+        level
+            .kill_height
+            .map_or_else(KillPlane::default, |height| KillPlane { height }),
+    );
+    next_level_state.set(LevelState::Playing);
+}
+
+/// Spawns the current level's streamed objects in a chunk that's just been loaded.
+fn spawn_streamed_objects(
+    trigger: Trigger<ChunkLoaded>,
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let chunk = trigger.0;
+    let Some(level) = levels.get(&current_level.handle) else {
+        return;
+    };
+
+    for object in &level.objects {
+        if object.chunk() == Some(chunk) {
+            let entity = object.spawn(&mut commands, &mut meshes, &mut materials, &asset_server);
+            commands.entity(entity).insert(InChunk(chunk));
+        }
+    }
+}
+
+/// Stops game time so nothing falls through the missing level, and covers the screen.
+fn show_loading_screen(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+
+    commands
+        .spawn((
+            LoadingScreen,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            // Above the HUD and the pause menu
+            GlobalZIndex(10),
+        ))
+        .with_child((
+            Text::new("Loading..."),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+        ));
+}
+
+/// Removes the loading screen, and restarts game time unless the game has been paused in the
+/// meantime.
+fn hide_loading_screen(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    pause_state: Res<State<PauseState>>,
+    screens: Query<Entity, With<LoadingScreen>>,
+) {
+    if **pause_state == PauseState::Running {
+        time.unpause();
+    }
+
+    for screen in &screens {
+        commands.entity(screen).despawn();
+    }
+}
+
+/// Sets up the nodes of a level's glTF scene once it has spawned, by their name prefix:
+/// colliders for [`TRIMESH_NODE_PREFIX`] and [`CONVEX_NODE_PREFIX`] nodes, spawn points for
+/// [`SPAWN_NODE_PREFIX`] nodes and a light for [`LIGHT_NODE_PREFIX`] nodes.
+fn setup_scene_nodes(
+    trigger: Trigger<SceneInstanceReady>,
+    mut commands: Commands,
+    scenes: Query<(), With<LevelScene>>,
+    children: Query<&Children>,
+    // Meshes are children of their nodes, and shouldn't be set up a second time
+    nodes: Query<&Name, Without<Mesh3d>>,
+) {
+    let scene = trigger.target();
+    if !scenes.contains(scene) {
+        return;
+    }
+
+    for node in children.iter_descendants(scene) {
+        let Ok(name) = nodes.get(node) else {
+            continue;
+        };
+        let mut node = commands.entity(node);
+
+        if name.starts_with(TRIMESH_NODE_PREFIX) {
+            node.insert((
+                RigidBody::Static,
+                ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+            ));
+        } else if name.starts_with(CONVEX_NODE_PREFIX) {
+            node.insert((
+                RigidBody::Static,
+                ColliderConstructorHierarchy::new(ColliderConstructor::ConvexHullFromMesh),
+            ));
+        } else if let Some(rest) = name.strip_prefix(SPAWN_NODE_PREFIX) {
+            let kind = if rest.starts_with(ENEMY_SPAWN_NODE_INFIX) {
+                SpawnKind::Enemy
+            } else {
+                SpawnKind::Player
+            };
+            node.insert(SpawnPoint { kind, team: None });
+        } else if name.starts_with(LIGHT_NODE_PREFIX) {
+            node.insert(PointLight {
+                shadows_enabled: true,
+                ..default()
+            });
+        }
+    }
+}
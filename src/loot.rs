@@ -0,0 +1,170 @@
+use crate::ball::DespawnAfter;
+use crate::combat::Died;
+use crate::cube::CubeShattered;
+use crate::enemy::Enemy;
+use crate::inventory::Item;
+use crate::layers::GameLayer;
+use crate::level::LevelEntity;
+use crate::pickup::{PickupBundle, PickupKind};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+/// How fast drops pop up out of whatever dropped them, and the most they fly off sideways.
+const POP_SPEED: f32 = 4.0;
+const POP_SPREAD: f32 = 1.5;
+const DROP_RADIUS: f32 = 0.2;
+/// Drops left lying around disappear after this long, in seconds.
+const DROP_LIFETIME: f32 = 30.0;
+
+/// Something a [`LootTable`] can drop, dropped with the given chance from 0 to 1.
+pub struct LootDrop {
+    pub kind: PickupKind,
+    pub chance: f32,
+}
+
+/// Rolled for each of its drops separately, so one roll can drop several things or nothing.
+#[derive(Default)]
+pub struct LootTable(pub Vec<LootDrop>);
+
+impl LootTable {
+    fn roll(&self) -> impl Iterator<Item = PickupKind> + '_ {
+        self.0
+            .iter()
+            .filter(|drop| rand::random::<f32>() < drop.chance)
+            .map(|drop| drop.kind)
+    }
+}
+
+/// What shattered cubes and dead enemies drop, as [`Pickup`](crate::pickup::Pickup)s thrown
+/// out of them.
+#[derive(Resource)]
+pub struct LootTables {
+    pub cube: LootTable,
+    pub enemy: LootTable,
+}
+
+impl Default for LootTables {
+    fn default() -> Self {
+        Self {
+            cube: LootTable(vec![
+                LootDrop {
+                    kind: PickupKind::Ammo(2),
+                    chance: 0.3,
+                },
+                LootDrop {
+                    kind: PickupKind::Item(Item::Cube, 1),
+                    chance: 0.2,
+                },
+                LootDrop {
+                    kind: PickupKind::Collectible(5),
+                    chance: 0.2,
+                },
+            ]),
+            enemy: LootTable(vec![
+                LootDrop {
+                    kind: PickupKind::Health(15.0),
+                    chance: 0.5,
+                },
+                LootDrop {
+                    kind: PickupKind::Ammo(5),
+                    chance: 0.5,
+                },
+                LootDrop {
+                    kind: PickupKind::Item(Item::Grenade, 1),
+                    chance: 0.25,
+                },
+                LootDrop {
+                    kind: PickupKind::Collectible(25),
+                    chance: 0.3,
+                },
+            ]),
+        }
+    }
+}
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LootTables>()
+            .add_systems(Update, (drop_cube_loot, drop_enemy_loot));
+    }
+}
+
+fn drop_cube_loot(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut shattered: EventReader<CubeShattered>,
+    loot_tables: Res<LootTables>,
+) {
+    for event in shattered.read() {
+        for kind in loot_tables.cube.roll() {
+            spawn_drop(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                kind,
+                event.position,
+            );
+        }
+    }
+}
+
+fn drop_enemy_loot(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut died: EventReader<Died>,
+    loot_tables: Res<LootTables>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+) {
+    for event in died.read() {
+        let Ok(transform) = enemies.get(event.entity) else {
+            continue;
+        };
+        for kind in loot_tables.enemy.roll() {
+            spawn_drop(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                kind,
+                transform.translation(),
+            );
+        }
+    }
+}
+
+/// Spawns a pickup falling and rolling around like any other body, popped up into the air in
+/// a random direction.
+fn spawn_drop(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    kind: PickupKind,
+    position: Vec3,
+) {
+    let mut pickup = PickupBundle::new(kind, None, Transform::from_translation(position));
+    pickup.trigger_volume.rigid_body = RigidBody::Dynamic;
+    let spread = Vec2::new(
+        rand::random_range(-POP_SPREAD..POP_SPREAD),
+        rand::random_range(-POP_SPREAD..POP_SPREAD),
+    );
+
+    commands.spawn((
+        LevelEntity,
+        pickup,
+        LockedAxes::ROTATION_LOCKED,
+        LinearVelocity(Vec3::new(spread.x, POP_SPEED, spread.y)),
+        DespawnAfter::new(DROP_LIFETIME),
+        children![
+            PickupBundle::visual(meshes, materials, kind),
+            // The sensor picking it up doesn't touch anything, so this keeps it from falling
+            // through the ground
+            (
+                Collider::sphere(DROP_RADIUS),
+                CollisionLayers::new(GameLayer::Prop, [GameLayer::World, GameLayer::Prop]),
+            ),
+        ],
+    ));
+}
@@ -1,116 +1,378 @@
+mod ammo;
 mod ball;
+mod blueprint;
+mod boundary;
+mod camera;
+mod carry;
+mod checkpoint;
+mod combat;
+mod crosshair;
 mod cube;
+mod cursor;
+mod day_night;
+mod death;
+mod diagnostics_overlay;
+mod dialog;
+mod door;
+mod enemy;
+mod explosion;
+mod game_assets;
+mod grapple;
+mod gravity_gun;
+mod gravity_zone;
+mod grenade;
+mod haptics;
+mod health_hud;
+mod hit_feedback;
+mod hotbar;
+mod input_routing;
+mod inventory;
+mod inventory_ui;
+mod jump_pad;
+mod key_bindings;
+mod kill_plane;
+mod launch;
+mod layers;
+mod level;
+mod loot;
+mod main_menu;
+mod mantle;
+mod melee;
+mod minimap;
+mod navigation;
+mod obstacle_course;
+mod pause;
+mod penetration;
+mod pickup;
+mod platform;
 mod player_movement;
+mod profile;
+mod push;
+mod quest;
+mod quest_hud;
+mod ragdoll;
+mod replay;
+mod rope;
+mod score;
+mod score_hud;
+mod screenshot;
+mod settings;
+mod spawn_point;
+mod spectator;
+mod stats;
+mod streaming;
+mod surface;
+mod survival;
+mod terrain;
+mod tool_hud;
+mod tools;
+mod touch;
+mod trigger;
+mod tuning;
+mod ui;
+mod wall_run;
+mod water;
+mod weather;
+mod wiring;
 
-use crate::ball::handle_despawn_after;
-use crate::player_movement::{
-    Player, PlayerAction, PlayerAltAction, PlayerBundle, PlayerJump, PlayerMove, PlayerPlugin,
-    PlayerSprint,
+use crate::ball::{
+    BallPool, BallSettings, count_bounces, damage_on_hit, handle_despawn_after, steer_homing,
+    stop_passing_through_players,
 };
+use crate::blueprint::BlueprintPlugin;
+use crate::camera::{CAMERA_HEIGHT, CameraPlugin, CameraRig};
+use crate::checkpoint::CheckpointPlugin;
+use crate::combat::CombatPlugin;
+use crate::crosshair::CrosshairPlugin;
+use crate::cube::{CubeShattered, shatter_on_hit};
+use crate::cursor::CursorPlugin;
+use crate::day_night::DayNightPlugin;
+use crate::death::DeathPlugin;
+use crate::diagnostics_overlay::DiagnosticsOverlayPlugin;
+use crate::dialog::DialogPlugin;
+use crate::door::DoorPlugin;
+use crate::enemy::EnemyPlugin;
+use crate::explosion::ExplosionPlugin;
+use crate::game_assets::GameAssets;
+use crate::gravity_zone::GravityZonePlugin;
+use crate::grenade::GrenadePlugin;
+use crate::haptics::HapticsPlugin;
+use crate::health_hud::HealthHudPlugin;
+use crate::hit_feedback::HitFeedbackPlugin;
+use crate::hotbar::HotbarPlugin;
+use crate::input_routing::{InputRoutingPlugin, LocalPlayer};
+use crate::inventory::{INVENTORY_SLOTS, Inventory, Item};
+use crate::inventory_ui::InventoryUiPlugin;
+use crate::jump_pad::JumpPadPlugin;
+use crate::key_bindings::{KeyBindings, KeyBindingsPlugin, MoveStickSettings};
+use crate::kill_plane::KillPlanePlugin;
+use crate::launch::LaunchOptions;
+use crate::level::LevelPlugin;
+use crate::loot::LootPlugin;
+use crate::main_menu::{GameState, MainMenuPlugin};
+use crate::minimap::MinimapPlugin;
+use crate::navigation::NavigationPlugin;
+use crate::obstacle_course::ObstacleCoursePlugin;
+use crate::pause::PausePlugin;
+use crate::penetration::{Penetrated, PenetrationHooks};
+use crate::pickup::PickupPlugin;
+use crate::platform::PlatformPlugin;
+use crate::player_movement::{Player, PlayerBundle, PlayerPlugin};
+use crate::profile::ProfilePlugin;
+use crate::push::PushPlugin;
+use crate::quest::QuestPlugin;
+use crate::quest_hud::QuestHudPlugin;
+use crate::ragdoll::RagdollPlugin;
+use crate::replay::ReplayPlugin;
+use crate::rope::RopePlugin;
+use crate::score::ScorePlugin;
+use crate::score_hud::ScoreHudPlugin;
+use crate::screenshot::ScreenshotPlugin;
+use crate::settings::SettingsPlugin;
+use crate::spawn_point::SpawnPointPlugin;
+use crate::spectator::SpectatorPlugin;
+use crate::stats::StatsPlugin;
+use crate::streaming::StreamingPlugin;
+use crate::surface::SurfacePlugin;
+use crate::survival::SurvivalPlugin;
+use crate::terrain::TerrainPlugin;
+use crate::tool_hud::ToolHudPlugin;
+use crate::touch::TouchControlsPlugin;
+use crate::trigger::TriggerPlugin;
+use crate::tuning::{Tuning, TuningPlugin};
+use crate::weather::WeatherPlugin;
+use crate::wiring::WiringPlugin;
 use avian3d::math::Scalar;
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use bevy::window::{CursorGrabMode, WindowMode};
 use bevy_enhanced_input::prelude::*;
 use bevy_tnua::prelude::*;
 use bevy_tnua_avian3d::TnuaAvian3dPlugin;
 
-fn setup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut window: Single<&mut Window>,
-) {
-    window.cursor_options.visible = false;
-    window.cursor_options.grab_mode = CursorGrabMode::Locked;
-    window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Primary);
-
-    // Static physics object with a collision shape
-    commands.spawn((
-        RigidBody::Static,
-        Collider::half_space(Vec3::Y),
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(128.0, 128.0))),
-        MeshMaterial3d(materials.add(Color::WHITE)),
-    ));
+/// The highest number of local players sharing the screen, including the keyboard and mouse one.
+const MAX_LOCAL_PLAYERS: usize = 4;
 
-    // Dynamic physics object with a collision shape and initial angular velocity
-    commands.spawn((
-        RigidBody::Dynamic,
-        Collider::cuboid(1.0, 1.0, 1.0),
-        AngularVelocity(Vec3::new(2.5, 3.5, 1.5)),
-        Mesh3d(meshes.add(Cuboid::from_length(1.0))),
-        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
-        Transform::from_xyz(-1.0, 4.0, -1.0),
-        Mass(0.1),
-    ));
-
-    // Light
-    commands.spawn((
-        PointLight {
-            shadows_enabled: true,
-            ..default()
-        },
-        Transform::from_xyz(4.0, 8.0, 4.0),
-    ));
-
-    // Player
+/// Spawns the character of `local_player`, moved to one of the level's spawn points once the
+/// level has loaded.
+fn spawn_player(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    key_bindings: &KeyBindings,
+    move_stick: &MoveStickSettings,
+    tuning: &Tuning,
+    local_player: LocalPlayer,
+) {
     commands
         .spawn((
             Mesh3d(meshes.add(Capsule3d::new(0.4, 1.0))),
             MeshMaterial3d(materials.add(Color::srgb(0.8, 0.7, 0.6))),
-            Transform::from_xyz(0.0, 0.0, 0.0),
-            PlayerBundle::new(Collider::capsule(0.4, 1.0)).with_movement(
-                10.0,
-                15.0,
-                (30.0 as Scalar).to_radians(),
-            ),
+            PlayerBundle::new(Collider::capsule(0.4, 1.0))
+                .with_movement(
+                    tuning.speed,
+                    tuning.jump_impulse,
+                    (30.0 as Scalar).to_radians(),
+                )
+                .with_air_jumps(1)
+                .with_air_control(20.0)
+                .with_crouch(Collider::capsule(0.4, 0.2), 0.6)
+                .with_prone(
+                    Collider::capsule_endpoints(
+                        0.3,
+                        Vec3::new(0.0, 0.0, -0.5),
+                        Vec3::new(0.0, 0.0, 0.5),
+                    ),
+                    0.35,
+                )
+                .with_step_height(0.45)
+                .with_fire_rate(5.0)
+                .with_ammo(10, 40)
+                .with_inventory(
+                    Inventory::new(INVENTORY_SLOTS)
+                        .with(Item::Grenade, 5)
+                        .with(Item::Cube, 32)
+                        .with(Item::Ramp, 4)
+                        .with(Item::Lamp, 8),
+                ),
             Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
             Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
             GravityScale(2.0),
-            actions!(Player[
-                (
-                    Action::<PlayerJump>::new(),
-                    bindings![KeyCode::Space],
-                ),
-                (
-                    Action::<PlayerMove>::new(),
-                    DeadZone::default(),
-                    SmoothNudge::default(),
-                    Bindings::spawn((
-                        Cardinal::wasd_keys(),
-                        Axial::left_stick(),
-                    ))
-                ),
-                (
-                    Action::<PlayerAction>::new(),
-                    bindings![MouseButton::Left],
-                ),
-                (
-                    Action::<PlayerAltAction>::new(),
-                    bindings![MouseButton::Right],
-                ),
-                (
-                    Action::<PlayerSprint>::new(),
-                    bindings![KeyCode::ShiftLeft]
-                )
-            ]),
+            local_player,
+            key_bindings.player_actions(local_player, move_stick),
             TnuaController::default(),
         ))
-        .with_child((Camera3d::default(), Transform::from_xyz(0.0, 0.2, 0.0)));
+        .with_child((
+            Camera3d::default(),
+            CameraRig::default(),
+            Transform::from_xyz(0.0, CAMERA_HEIGHT, 0.0),
+        ));
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    key_bindings: Res<KeyBindings>,
+    move_stick: Res<MoveStickSettings>,
+    tuning: Res<Tuning>,
+) {
+    spawn_player(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &key_bindings,
+        &move_stick,
+        &tuning,
+        LocalPlayer(0),
+    );
+}
+
+/// Despawns the players, along with their cameras, when the game is left for the main menu.
+fn despawn_players(mut commands: Commands, players: Query<Entity, With<Player>>) {
+    for player in &players {
+        commands.entity(player).despawn();
+    }
+}
+
+/// Lets another player join on a gamepad by pressing its start button, while there's a gamepad
+/// left over for them. The keyboard and mouse player is always there.
+#[allow(clippy::too_many_arguments)]
+fn join_players(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    key_bindings: Res<KeyBindings>,
+    move_stick: Res<MoveStickSettings>,
+    tuning: Res<Tuning>,
+    gamepads: Query<&Gamepad>,
+    players: Query<&LocalPlayer>,
+) {
+    if !gamepads
+        .iter()
+        .any(|gamepad| gamepad.just_pressed(GamepadButton::Start))
+    {
+        return;
+    }
+    let next = players.iter().map(|player| player.0 + 1).max().unwrap_or(0);
+    // Every player after the first needs a gamepad of their own
+    if next >= MAX_LOCAL_PLAYERS || next > gamepads.iter().count() {
+        return;
+    }
+
+    spawn_player(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &key_bindings,
+        &move_stick,
+        &tuning,
+        LocalPlayer(next),
+    );
+    info!("Local player {next} joined");
 }
 
 fn main() {
+    let options = LaunchOptions::from_env();
+    let default_plugins = options.default_plugins();
+
     App::new()
+        // Read by plugins as they're built, like the settings' for the window mode
+        .insert_resource(options)
         // Enable physics
         .add_plugins((
-            DefaultPlugins,
+            default_plugins,
             EnhancedInputPlugin,
             TnuaControllerPlugin::new(PhysicsSchedule),
             TnuaAvian3dPlugin::new(PhysicsSchedule),
-            PhysicsPlugins::default(),
+            PhysicsPlugins::default().with_collision_hooks::<PenetrationHooks>(),
+        ))
+        .add_plugins((
+            MainMenuPlugin,
+            LevelPlugin,
+            TerrainPlugin,
+            DayNightPlugin,
+            WeatherPlugin,
+            SpawnPointPlugin,
             PlayerPlugin,
+            PlatformPlugin,
+            CameraPlugin,
+            SpectatorPlugin,
+            GrenadePlugin,
+            CombatPlugin,
+            CheckpointPlugin,
+            KillPlanePlugin,
+            HitFeedbackPlugin,
+        ))
+        .add_plugins((
+            DoorPlugin,
+            WiringPlugin,
+            JumpPadPlugin,
+            SurfacePlugin,
+            ObstacleCoursePlugin,
+            StreamingPlugin,
+            ExplosionPlugin,
+            RagdollPlugin,
+            RopePlugin,
+            TriggerPlugin,
+            PushPlugin,
+            GravityZonePlugin,
+            PausePlugin,
+        ))
+        .add_plugins((
+            PickupPlugin,
+            ScorePlugin,
+            EnemyPlugin,
+            NavigationPlugin,
+            SurvivalPlugin,
+            DialogPlugin,
+            QuestPlugin,
+            LootPlugin,
+            DeathPlugin,
+            BlueprintPlugin,
+            TuningPlugin,
         ))
-        .add_systems(Startup, setup)
-        .add_systems(FixedUpdate, handle_despawn_after)
+        .add_plugins((
+            CrosshairPlugin,
+            DiagnosticsOverlayPlugin,
+            HealthHudPlugin,
+            ToolHudPlugin,
+            MinimapPlugin,
+            HotbarPlugin,
+            InventoryUiPlugin,
+            ScoreHudPlugin,
+            QuestHudPlugin,
+        ))
+        .add_plugins((
+            // Before the key bindings, settings and stats, which are read from the profile
+            ProfilePlugin,
+            KeyBindingsPlugin,
+            SettingsPlugin,
+            StatsPlugin,
+            ReplayPlugin,
+            ScreenshotPlugin,
+            CursorPlugin,
+            InputRoutingPlugin,
+            TouchControlsPlugin,
+            HapticsPlugin,
+        ))
+        .init_resource::<GameAssets>()
+        .init_resource::<BallPool>()
+        .init_resource::<BallSettings>()
+        .add_event::<CubeShattered>()
+        .add_event::<Penetrated>()
+        .add_systems(OnEnter(GameState::InGame), setup)
+        .add_systems(OnExit(GameState::InGame), despawn_players)
+        .add_systems(Update, join_players.run_if(in_state(GameState::InGame)))
+        .add_systems(
+            FixedUpdate,
+            (
+                handle_despawn_after,
+                count_bounces,
+                damage_on_hit,
+                shatter_on_hit,
+                steer_homing,
+                stop_passing_through_players,
+            ),
+        )
         .run();
 }
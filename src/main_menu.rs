@@ -0,0 +1,215 @@
+use crate::cursor::set_cursor_grab;
+use crate::launch::LaunchOptions;
+use crate::profile::{Profile, SwitchProfile};
+use crate::stats::Stats;
+use crate::ui::ReleasedButtons;
+use bevy::prelude::*;
+
+/// Where the app is at the top level. Loading a level and pausing happen within
+/// [`GameState::InGame`], as its [`LevelState`](crate::level::LevelState) and
+/// [`PauseState`](crate::pause::PauseState) sub-states.
+#[derive(States, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameState {
+    /// The title screen the app starts on, before anything of the game is spawned.
+    #[default]
+    MainMenu,
+    InGame,
+}
+
+/// The rules of the game being played, picked in the main menu.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameMode {
+    /// Free play, with the level's enemies spawned along with it.
+    #[default]
+    Sandbox,
+    /// Waves of enemies to survive, see [`Survival`](crate::survival::Survival).
+    Survival,
+}
+
+#[derive(Component)]
+struct MainMenu;
+
+/// Shows the current profile's [`Stats`].
+#[derive(Component)]
+struct StatsText;
+
+/// What a button in the main menu does.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MainMenuButton {
+    Play,
+    Survival,
+    /// Switches to the next profile, by name.
+    Profile,
+    NewProfile,
+    Quit,
+}
+
+impl MainMenuButton {
+    /// The button's text, showing the current profile on its button.
+    fn label(self, profile: &Profile) -> String {
+        match self {
+            Self::Play => "Play".into(),
+            Self::Survival => "Survival".into(),
+            Self::Profile => format!("Profile: {}", profile.name),
+            Self::NewProfile => "New profile".into(),
+            Self::Quit => "Quit".into(),
+        }
+    }
+}
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .init_resource::<GameMode>()
+            .add_systems(
+                Startup,
+                skip_main_menu.run_if(|options: Res<LaunchOptions>| options.skips_main_menu()),
+            )
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
+            .add_systems(
+                Update,
+                (
+                    press_main_menu_buttons,
+                    (update_main_menu_labels, update_stats_text),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+/// Shows the menu with a camera of its own, since the player's camera only spawns in game.
+fn spawn_main_menu(mut commands: Commands, mut window: Single<&mut Window>) {
+    set_cursor_grab(&mut window, false);
+
+    commands.spawn((MainMenu, Camera2d));
+    commands
+        .spawn((
+            MainMenu,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.08, 0.08, 0.1)),
+            // Above the HUD spawned at startup
+            GlobalZIndex(10),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Playground"),
+                TextFont {
+                    font_size: 64.0,
+                    ..default()
+                },
+            ));
+            // Written by `update_stats_text`
+            parent.spawn((StatsText, Text::default()));
+            for button in [
+                MainMenuButton::Play,
+                MainMenuButton::Survival,
+                MainMenuButton::Profile,
+                MainMenuButton::NewProfile,
+                MainMenuButton::Quit,
+            ] {
+                // Labelled by `update_main_menu_labels`
+                parent
+                    .spawn((
+                        button,
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(24.0), Val::Px(12.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                    ))
+                    .with_child(Text::default());
+            }
+        });
+}
+
+/// Starts a sandbox game right away, for launches naming a level or without a window to show
+/// the menu in.
+fn skip_main_menu(mut next_game_state: ResMut<NextState<GameState>>) {
+    next_game_state.set(GameState::InGame);
+}
+
+fn despawn_main_menu(mut commands: Commands, menus: Query<Entity, With<MainMenu>>) {
+    for menu in &menus {
+        commands.entity(menu).despawn();
+    }
+}
+
+/// Starts the game in the mode clicked, switches profiles, or quits.
+fn press_main_menu_buttons(
+    mut buttons: ReleasedButtons<MainMenuButton>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut game_mode: ResMut<GameMode>,
+    mut switch_profile: EventWriter<SwitchProfile>,
+    profile: Res<Profile>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(button) = buttons.released() else {
+        return;
+    };
+    match button {
+        MainMenuButton::Play => {
+            *game_mode = GameMode::Sandbox;
+            next_game_state.set(GameState::InGame);
+        }
+        MainMenuButton::Survival => {
+            *game_mode = GameMode::Survival;
+            next_game_state.set(GameState::InGame);
+        }
+        MainMenuButton::Profile => {
+            let profiles = Profile::all();
+            let next = profiles
+                .iter()
+                .position(|other| *other == *profile)
+                .map_or(0, |index| (index + 1) % profiles.len());
+            switch_profile.write(SwitchProfile(profiles[next].clone()));
+        }
+        MainMenuButton::NewProfile => {
+            switch_profile.write(SwitchProfile(Profile::unused(&Profile::all())));
+        }
+        MainMenuButton::Quit => {
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+/// Writes each button's label, so the profile button shows the profile switched to.
+fn update_main_menu_labels(
+    profile: Res<Profile>,
+    buttons: Query<(&MainMenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        let label = button.label(&profile);
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            if text.0 != label {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
+
+fn update_stats_text(stats: Res<Stats>, mut texts: Query<&mut Text, With<StatsText>>) {
+    let text = format!(
+        "Games: {}   Kills: {}   Deaths: {}   Best score: {}   Best wave: {}",
+        stats.games_played, stats.kills, stats.deaths, stats.best_score, stats.best_wave
+    );
+    for mut stats_text in &mut texts {
+        if stats_text.0 != text {
+            stats_text.0 = text.clone();
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::layers::GameLayer;
+use crate::player_movement::{IsClimbing, Player};
+use crate::tuning::Tuning;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_tnua::TnuaToggle;
+use bevy_tnua::prelude::{TnuaBuiltinWalk, TnuaController};
+
+/// How far in front of the character's surface a wall can be to grab its ledge.
+const LEDGE_REACH: Scalar = 0.5;
+/// How far above the top of the capsule a ledge can be and still be grabbed.
+const LEDGE_ABOVE_TOP: Scalar = 0.6;
+/// How far past the wall the ledge surface is probed for.
+const LEDGE_DEPTH: Scalar = 0.3;
+/// Ledge surfaces need a normal at least this close to straight up.
+const MIN_LEDGE_NORMAL_Y: Scalar = 0.7;
+const MANTLE_DURATION: f32 = 0.4;
+
+/// The mantle state of a character.
+#[derive(Component, Default)]
+pub enum Mantle {
+    #[default]
+    Idle,
+    /// Being lifted from `start` onto the ledge, ending up standing at `target`.
+    Climbing {
+        start: Vector,
+        target: Vector,
+        elapsed: f32,
+    },
+}
+
+pub struct MantlePlugin;
+
+impl Plugin for MantlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (grab_ledge, climb_ledge).chain());
+    }
+}
+
+/// Starts a mantle when an airborne character pushes toward a wall whose top edge is within
+/// reach and there is room to stand on it.
+#[allow(clippy::type_complexity)]
+fn grab_ledge(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    tuning: Res<Tuning>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &ShapeHits,
+            &TnuaController,
+            &IsClimbing,
+            &mut LinearVelocity,
+            &mut Mantle,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        entity,
+        transform,
+        collider,
+        ground_hits,
+        controller,
+        is_climbing,
+        mut velocity,
+        mut mantle,
+    ) in &mut query
+    {
+        if !matches!(*mantle, Mantle::Idle) || !ground_hits.is_empty() || is_climbing.0 {
+            continue;
+        }
+
+        let Ok(forward) = Dir3::new(transform.forward().as_vec3().with_y(0.0)) else {
+            continue;
+        };
+        let pushing_forward = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .is_some_and(|(basis, _)| basis.desired_velocity.dot(*forward) > 0.0);
+        if !pushing_forward {
+            continue;
+        }
+
+        let position = transform.translation;
+        let aabb = collider.aabb(position, Quat::IDENTITY);
+        let radius = (aabb.max.x - aabb.min.x) / 2.0;
+        let filter =
+            SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+
+        // There has to be a wall in front of the character
+        let Some(wall) =
+            spatial_query.cast_ray(position, forward, radius + LEDGE_REACH, true, &filter)
+        else {
+            continue;
+        };
+
+        // ...that ends below the top of the reach, where a ray cast straight down from just
+        // beyond the wall finds a walkable ledge surface
+        let probe_origin = Vector::new(position.x, aabb.max.y + LEDGE_ABOVE_TOP, position.z)
+            + forward * (wall.distance + LEDGE_DEPTH);
+        let probe_length = aabb.max.y + LEDGE_ABOVE_TOP - position.y;
+        let Some(ledge) =
+            spatial_query.cast_ray(probe_origin, Dir3::NEG_Y, probe_length, true, &filter)
+        else {
+            continue;
+        };
+        // A hit at the origin means the probe started inside geometry, so it isn't a ledge
+        if ledge.distance <= 0.0 || ledge.normal.y < MIN_LEDGE_NORMAL_Y {
+            continue;
+        }
+
+        let target = probe_origin - Vector::Y * ledge.distance + Vector::Y * tuning.float_height;
+        let blocked = !spatial_query
+            .shape_intersections(collider, target, Quat::IDENTITY, &filter)
+            .is_empty();
+        if blocked {
+            continue;
+        }
+
+        *mantle = Mantle::Climbing {
+            start: position,
+            target,
+            elapsed: 0.0,
+        };
+        velocity.0 = Vector::ZERO;
+        // Take the body away from physics and Tnua for the duration of the climb
+        commands
+            .entity(entity)
+            .insert((RigidBody::Kinematic, TnuaToggle::Disabled));
+    }
+}
+
+/// Lifts a mantling character up first and then forward onto the ledge, handing it back to
+/// physics once it stands on top.
+fn climb_ledge(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Mantle), With<Player>>,
+) {
+    for (entity, mut transform, mut mantle) in &mut query {
+        let Mantle::Climbing {
+            start,
+            target,
+            elapsed,
+        } = &mut *mantle
+        else {
+            continue;
+        };
+
+        *elapsed += time.delta_secs();
+        let progress = (*elapsed / MANTLE_DURATION).min(1.0);
+        let rise = (progress / 0.6).min(1.0);
+        let advance = ((progress - 0.4) / 0.6).max(0.0);
+
+        transform.translation = Vector::new(
+            start.x.lerp(target.x, advance),
+            start.y.lerp(target.y, rise),
+            start.z.lerp(target.z, advance),
+        );
+
+        if progress >= 1.0 {
+            *mantle = Mantle::Idle;
+            commands
+                .entity(entity)
+                .insert((RigidBody::Dynamic, TnuaToggle::Enabled));
+        }
+    }
+}
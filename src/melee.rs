@@ -0,0 +1,122 @@
+use crate::ball::DespawnAfter;
+use crate::camera::CharacterCameras;
+use crate::carry::Carry;
+use crate::combat::{Damage, DamageKind, Health};
+use crate::game_assets::GameAssets;
+use crate::layers::GameLayer;
+use crate::player_movement::{Knockback, Player};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use std::f32::consts::FRAC_PI_4;
+
+const MELEE_RANGE: Scalar = 1.5;
+/// The radius of the sphere swept in front of the camera, which sets how wide the swing is.
+const MELEE_RADIUS: Scalar = 0.6;
+const MELEE_MAX_HITS: u32 = 8;
+const MELEE_DAMAGE: f32 = 20.0;
+const MELEE_IMPULSE: Scalar = 0.5;
+/// The speed characters hit by a swing get knocked back with.
+const MELEE_KNOCKBACK: Scalar = 8.0;
+const MELEE_COOLDOWN: f32 = 0.5;
+const SWING_TIME: f32 = 0.2;
+/// How far the swing placeholder turns over a swing, from right to left.
+const SWING_ARC: f32 = 1.6;
+/// Where the swing placeholder is held, relative to the camera.
+const SWING_OFFSET: Vec3 = Vec3::new(0.0, -0.3, -0.6);
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerMelee;
+
+/// When the character can swing again, in elapsed seconds.
+#[derive(Component, Default)]
+pub struct MeleeCooldown(f32);
+
+/// The first-person placeholder of a swing in progress, started at the given elapsed seconds.
+#[derive(Component)]
+struct Swing(f32);
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, animate_swings).add_observer(swing);
+    }
+}
+
+/// Sweeps a sphere in front of the camera, hurting and knocking back characters and pushing
+/// dynamic bodies it hits. Can't be done with full hands.
+#[allow(clippy::too_many_arguments)]
+fn swing(
+    trigger: Trigger<Started<PlayerMelee>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut knockback: EventWriter<Knockback>,
+    mut damage: EventWriter<Damage>,
+    spatial_query: SpatialQuery,
+    assets: Res<GameAssets>,
+    cameras: CharacterCameras,
+    bodies: Query<&RigidBody>,
+    characters: Query<(), With<Health>>,
+    mut query: Query<(&Carry, &mut MeleeCooldown), With<Player>>,
+) {
+    let entity = trigger.target();
+    let Ok((carry, mut cooldown)) = query.get_mut(entity) else {
+        return;
+    };
+    let Some((camera, camera_transform)) = cameras.get(entity) else {
+        return;
+    };
+    let now = time.elapsed_secs();
+    if now < cooldown.0 || carry.is_carrying() {
+        return;
+    }
+    cooldown.0 = now + MELEE_COOLDOWN;
+
+    commands.entity(camera).with_child((
+        Swing(now),
+        Mesh3d(assets.melee_mesh.clone()),
+        MeshMaterial3d(assets.melee_material.clone()),
+        Transform::from_translation(SWING_OFFSET),
+        DespawnAfter::new(SWING_TIME),
+    ));
+
+    let direction = camera_transform.forward();
+    let hits = spatial_query.shape_hits(
+        &Collider::sphere(MELEE_RADIUS),
+        camera_transform.translation(),
+        Quaternion::IDENTITY,
+        direction,
+        MELEE_MAX_HITS,
+        &ShapeCastConfig::from_max_distance(MELEE_RANGE),
+        &SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]),
+    );
+    for hit in hits {
+        if characters.contains(hit.entity) {
+            damage.write(Damage {
+                target: hit.entity,
+                amount: MELEE_DAMAGE,
+                kind: DamageKind::Melee,
+                source: Some(entity),
+            });
+            knockback.write(Knockback {
+                entity: hit.entity,
+                impulse: direction * MELEE_KNOCKBACK,
+            });
+        } else if bodies.get(hit.entity).is_ok_and(RigidBody::is_dynamic) {
+            commands
+                .entity(hit.entity)
+                .insert(ExternalImpulse::new(direction * MELEE_IMPULSE));
+        }
+    }
+}
+
+/// Turns swing placeholders across the arc in front of the camera until they're despawned.
+fn animate_swings(time: Res<Time>, mut swings: Query<(&Swing, &mut Transform)>) {
+    for (swing, mut transform) in &mut swings {
+        let progress = ((time.elapsed_secs() - swing.0) / SWING_TIME).clamp(0.0, 1.0);
+        transform.rotation =
+            Quat::from_rotation_y(SWING_ARC * (progress - 0.5)) * Quat::from_rotation_z(-FRAC_PI_4);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::combat::Health;
+use crate::cube::Cube;
+use crate::enemy::Enemy;
+use crate::player_movement::Player;
+use bevy::prelude::*;
+
+/// How far from the player the minimap shows things, in meters.
+const MINIMAP_RANGE: f32 = 40.0;
+/// The minimap's width and height on screen, in pixels.
+const MINIMAP_SIZE: f32 = 160.0;
+/// How far the minimap sits from the bottom right corner of the screen, in pixels.
+const MINIMAP_MARGIN: f32 = 24.0;
+const BLIP_SIZE: f32 = 6.0;
+const PLAYER_BLIP_SIZE: f32 = 8.0;
+
+/// A radar in the corner of the screen, turning with the player so up is always ahead.
+#[derive(Component)]
+struct Minimap;
+
+/// A dot on the minimap. Dots are reused from frame to frame, and hidden while not needed.
+#[derive(Component)]
+struct Blip;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap)
+            .add_systems(Update, update_minimap);
+    }
+}
+
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        Minimap,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(MINIMAP_MARGIN),
+            bottom: Val::Px(MINIMAP_MARGIN),
+            width: Val::Px(MINIMAP_SIZE),
+            height: Val::Px(MINIMAP_SIZE),
+            border: UiRect::all(Val::Px(1.0)),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.4)),
+        BorderColor(Color::WHITE.with_alpha(0.5)),
+        BorderRadius::MAX,
+        children![(
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px((MINIMAP_SIZE - PLAYER_BLIP_SIZE) / 2.0),
+                top: Val::Px((MINIMAP_SIZE - PLAYER_BLIP_SIZE) / 2.0),
+                width: Val::Px(PLAYER_BLIP_SIZE),
+                height: Val::Px(PLAYER_BLIP_SIZE),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.9, 0.3)),
+        )],
+    ));
+}
+
+/// Places a blip for every cube, target and enemy within range of the player, relative to where the
+/// player faces.
+#[allow(clippy::type_complexity)]
+fn update_minimap(
+    mut commands: Commands,
+    players: Query<&GlobalTransform, With<Player>>,
+    cubes: Query<&GlobalTransform, (With<Cube>, Without<Player>)>,
+    targets: Query<&GlobalTransform, (With<Health>, Without<Player>, Without<Enemy>)>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    minimaps: Query<Entity, With<Minimap>>,
+    mut blips: Query<(&mut Node, &mut BackgroundColor, &mut Visibility), With<Blip>>,
+) {
+    let Some(player) = players.iter().next() else {
+        return;
+    };
+    let Some(minimap) = minimaps.iter().next() else {
+        return;
+    };
+    let to_player = player.affine().inverse();
+
+    let cube_color = Color::srgb(0.8, 0.8, 0.8);
+    let target_color = Color::srgb(0.9, 0.6, 0.2);
+    let enemy_color = Color::srgb(0.9, 0.1, 0.1);
+    let shown = cubes
+        .iter()
+        .map(|transform| (transform, cube_color))
+        .chain(targets.iter().map(|transform| (transform, target_color)))
+        .chain(enemies.iter().map(|transform| (transform, enemy_color)))
+        .filter_map(|(transform, color)| {
+            let offset = to_player.transform_point3(transform.translation()).xz();
+            (offset.length() <= MINIMAP_RANGE).then_some((offset, color))
+        });
+
+    let mut blips = blips.iter_mut();
+    for (offset, color) in shown {
+        // Ahead of the player is -Z, which is up on the map
+        let center = (offset / MINIMAP_RANGE + 1.0) * MINIMAP_SIZE / 2.0;
+        let node = Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(center.x - BLIP_SIZE / 2.0),
+            top: Val::Px(center.y - BLIP_SIZE / 2.0),
+            width: Val::Px(BLIP_SIZE),
+            height: Val::Px(BLIP_SIZE),
+            ..default()
+        };
+
+        let Some((mut blip_node, mut background, mut visibility)) = blips.next() else {
+            // Shows from the next frame on, once it's spawned
+            commands.spawn((
+                Blip,
+                node,
+                BackgroundColor(color),
+                BorderRadius::MAX,
+                ChildOf(minimap),
+            ));
+            continue;
+        };
+        *blip_node = node;
+        background.0 = color;
+        *visibility = Visibility::Inherited;
+    }
+    for (_, _, mut visibility) in blips {
+        *visibility = Visibility::Hidden;
+    }
+}
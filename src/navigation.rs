@@ -0,0 +1,435 @@
+use crate::layers::GameLayer;
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+const CELL_SIZE: Scalar = 1.0;
+/// How many cells the grid spans along each side, centered between the players.
+const GRID_CELLS: i32 = 128;
+/// How far the middle of the players can get from the grid's center before it's recentered, in
+/// cells.
+const RECENTER_DISTANCE: i32 = GRID_CELLS / 4;
+/// The most cells scanned every frame, so a recentered grid isn't scanned all at once.
+const CELLS_PER_FRAME: usize = 1024;
+/// How far above and below the players the ground is looked for.
+const SCAN_HEIGHT: Scalar = 30.0;
+/// The highest step between neighboring cells characters can walk up or down.
+const MAX_STEP: Scalar = 0.5;
+/// Ground steeper than this isn't walkable.
+const MAX_SLOPE: Scalar = FRAC_PI_4;
+/// The width and height of the space a character needs to stand in a cell.
+const CLEARANCE_WIDTH: Scalar = 0.8;
+const CLEARANCE_HEIGHT: Scalar = 1.8;
+/// The most cells a path search looks at before giving up.
+const MAX_SEARCHED_CELLS: usize = 4096;
+/// How often paths are planned again, as the goal and the grid change, in seconds.
+const REPLAN_INTERVAL: f32 = 0.5;
+/// How close a character has to get to a waypoint to head for the next one.
+const WAYPOINT_RADIUS: Scalar = 0.5;
+
+const NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+/// A grid of where characters can walk around the players, scanned from the solid world and
+/// the props in it. Each cell knows the height of its ground, and neighboring cells are
+/// connected if the step between them is low enough. Only the topmost ground of each cell is
+/// known, so nothing is routed under overhangs.
+///
+/// The whole grid is scanned when it's centered between the players, and after that only the cells
+/// under colliders that were added, moved or removed.
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    /// The cell at the grid's center, counted from the world origin.
+    center: IVec2,
+    /// The ground height of every cell, row by row along +Z, or `None` if the cell is
+    /// blocked, too steep, or hasn't been scanned yet.
+    ground: Vec<Option<Scalar>>,
+    /// The cells waiting to be scanned, oldest first.
+    dirty: VecDeque<IVec2>,
+    /// The cells in `dirty`, so none is queued twice.
+    queued: HashSet<IVec2>,
+    /// The cells each collider routed around covers, including the cells next to them, to
+    /// know which to scan again once it moves or is removed.
+    footprints: HashMap<Entity, IRect>,
+}
+
+impl NavGrid {
+    fn cell_at(position: Vector) -> IVec2 {
+        (position.xz() / CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let local = cell - self.center + GRID_CELLS / 2;
+        let in_grid = local.cmpge(IVec2::ZERO).all() && local.cmplt(IVec2::splat(GRID_CELLS)).all();
+        (in_grid && !self.ground.is_empty()).then(|| (local.y * GRID_CELLS + local.x) as usize)
+    }
+
+    fn ground(&self, cell: IVec2) -> Option<Scalar> {
+        self.index(cell).and_then(|index| self.ground[index])
+    }
+
+    fn cell_center(cell: IVec2, ground: Scalar) -> Vector {
+        let center = (cell.as_vec2() + 0.5) * CELL_SIZE;
+        Vector::new(center.x, ground, center.y)
+    }
+
+    /// Forgets every cell and centers the grid on `center`, queueing all of it to be scanned.
+    fn recenter(&mut self, center: IVec2) {
+        self.center = center;
+        self.ground = vec![None; (GRID_CELLS * GRID_CELLS) as usize];
+        self.dirty.clear();
+        self.queued.clear();
+        self.mark_dirty(IRect::from_center_half_size(
+            center,
+            IVec2::splat(GRID_CELLS / 2),
+        ));
+    }
+
+    /// Queues the cells of `area` that are on the grid to be scanned again.
+    fn mark_dirty(&mut self, area: IRect) {
+        let min = area.min.max(self.center - GRID_CELLS / 2);
+        let max = area.max.min(self.center + GRID_CELLS / 2 - 1);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = IVec2::new(x, y);
+                if self.queued.insert(cell) {
+                    self.dirty.push_back(cell);
+                }
+            }
+        }
+    }
+
+    /// A path of waypoints from `from` to `to` around whatever's in the way, leaving out the
+    /// start and the waypoints in between straight stretches. `None` if either end is off the
+    /// grid, or there's no way through within [`MAX_SEARCHED_CELLS`].
+    pub fn find_path(&self, from: Vector, to: Vector) -> Option<Vec<Vector>> {
+        let start = Self::cell_at(from);
+        let goal = Self::cell_at(to);
+        self.ground(goal)?;
+        self.index(start)?;
+
+        let mut open = BinaryHeap::from([OpenCell {
+            cell: start,
+            estimate: estimate(start, goal),
+        }]);
+        let mut came_from = HashMap::new();
+        let mut costs = HashMap::from([(start, 0.0)]);
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == goal {
+                return Some(self.waypoints(&came_from, goal));
+            }
+            if costs.len() > MAX_SEARCHED_CELLS {
+                return None;
+            }
+
+            // The start can be in a blocked cell, like when a prop was just dropped there, so
+            // characters can walk out of it
+            let height = self.ground(cell);
+            let cost = costs[&cell];
+            for offset in NEIGHBORS {
+                let next = cell + offset;
+                let Some(next_height) = self.ground(next) else {
+                    continue;
+                };
+                if height.is_some_and(|height| (next_height - height).abs() > MAX_STEP) {
+                    continue;
+                }
+                // Diagonal moves can't cut corners
+                let diagonal = offset.x != 0 && offset.y != 0;
+                if diagonal
+                    && (self.ground(cell + offset.with_y(0)).is_none()
+                        || self.ground(cell + offset.with_x(0)).is_none())
+                {
+                    continue;
+                }
+
+                let next_cost = cost + offset.as_vec2().length();
+                if costs.get(&next).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(OpenCell {
+                    cell: next,
+                    estimate: next_cost + estimate(next, goal),
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Walks back from `goal` to the start of the search, keeping only the cells where the
+    /// path turns.
+    fn waypoints(&self, came_from: &HashMap<IVec2, IVec2>, goal: IVec2) -> Vec<Vector> {
+        let mut cells = vec![goal];
+        let mut cell = goal;
+        while let Some(&previous) = came_from.get(&cell) {
+            cells.push(previous);
+            cell = previous;
+        }
+        cells.reverse();
+
+        let mut waypoints = Vec::new();
+        for (index, &cell) in cells.iter().enumerate().skip(1) {
+            let turns = cells
+                .get(index + 1)
+                .is_none_or(|&next| next - cell != cell - cells[index - 1]);
+            if turns {
+                let ground = self.ground(cell).unwrap_or_default();
+                waypoints.push(Self::cell_center(cell, ground));
+            }
+        }
+        waypoints
+    }
+}
+
+/// The cost of going from `cell` to `goal` with nothing in the way.
+fn estimate(cell: IVec2, goal: IVec2) -> Scalar {
+    let distance = (goal - cell).abs();
+    let diagonal = distance.min_element() as Scalar;
+    let straight = (distance.max_element() - distance.min_element()) as Scalar;
+    diagonal * SQRT_2 + straight
+}
+
+/// A cell waiting to be searched, ordered so the lowest estimate is searched first.
+struct OpenCell {
+    cell: IVec2,
+    estimate: Scalar,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Follows paths through the [`NavGrid`] to a goal, planning them again every so often.
+#[derive(Component, Default)]
+pub struct PathFollower {
+    waypoints: VecDeque<Vector>,
+    /// When to plan the path again, in elapsed seconds.
+    replan_at: f32,
+}
+
+impl PathFollower {
+    /// Which way to walk from `position` to get to `goal`. Heads straight for the goal when
+    /// there's no path, such as when it's off the grid.
+    pub fn steer(
+        &mut self,
+        grid: &NavGrid,
+        position: Vector,
+        goal: Vector,
+        now: f32,
+    ) -> Option<Dir3> {
+        if now >= self.replan_at {
+            self.replan_at = now + REPLAN_INTERVAL;
+            self.waypoints = grid
+                .find_path(position, goal)
+                .map(VecDeque::from)
+                .unwrap_or_default();
+        }
+
+        while self
+            .waypoints
+            .front()
+            .is_some_and(|waypoint| (waypoint - position).xz().length() < WAYPOINT_RADIUS)
+        {
+            self.waypoints.pop_front();
+        }
+
+        let target = self.waypoints.front().copied().unwrap_or(goal);
+        Dir3::new((target - position).with_y(0.0)).ok()
+    }
+}
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .add_systems(Update, (mark_dirty_cells, scan_nav_grid).chain());
+    }
+}
+
+/// The layers of what characters route around. Characters and loose projectiles move around
+/// too much to be worth it.
+fn routed_layers() -> LayerMask {
+    LayerMask::from([GameLayer::World, GameLayer::Prop])
+}
+
+/// Queues the cells under colliders routed around to be scanned again when they're added,
+/// moved onto other cells, or removed.
+fn mark_dirty_cells(
+    mut grid: ResMut<NavGrid>,
+    colliders: Query<(Entity, &ColliderAabb, Option<&CollisionLayers>), Changed<ColliderAabb>>,
+    mut removed: RemovedComponents<Collider>,
+) {
+    for entity in removed.read() {
+        if let Some(footprint) = grid.footprints.remove(&entity) {
+            grid.mark_dirty(footprint);
+        }
+    }
+
+    for (entity, aabb, layers) in &colliders {
+        // Colliders without layers are in the world layer
+        if layers.is_some_and(|layers| layers.memberships & routed_layers() == LayerMask::NONE) {
+            continue;
+        }
+        // The cells next to it are covered too, since their clearance and steps depend on it
+        let footprint = IRect::from_corners(
+            NavGrid::cell_at(aabb.min) - 1,
+            NavGrid::cell_at(aabb.max) + 1,
+        );
+        let previous = grid.footprints.insert(entity, footprint);
+        if previous == Some(footprint) {
+            continue;
+        }
+        if let Some(previous) = previous {
+            grid.mark_dirty(previous);
+        }
+        grid.mark_dirty(footprint);
+    }
+}
+
+/// Scans the queued cells of the grid around the players, starting over around them when
+/// they've gone too far from its center. The grid is centered halfway between the players
+/// furthest apart, so it covers all of them while they stay close enough together.
+fn scan_nav_grid(
+    mut grid: ResMut<NavGrid>,
+    spatial_query: SpatialQuery,
+    players: Query<&Position, With<Player>>,
+    mut clearance: Local<Option<Collider>>,
+) {
+    let mut positions = players.iter().map(|position| position.0);
+    let Some(first) = positions.next() else {
+        return;
+    };
+    let (min, max) = positions.fold((first, first), |(min, max), position| {
+        (min.min(position), max.max(position))
+    });
+    let middle_cell = NavGrid::cell_at((min + max) / 2.0);
+    if grid.ground.is_empty() || (middle_cell - grid.center).abs().max_element() > RECENTER_DISTANCE
+    {
+        grid.recenter(middle_cell);
+    }
+
+    let clearance = clearance.get_or_insert_with(|| {
+        Collider::cuboid(
+            CLEARANCE_WIDTH,
+            CLEARANCE_HEIGHT - MAX_STEP,
+            CLEARANCE_WIDTH,
+        )
+    });
+    let filter = SpatialQueryFilter::from_mask(routed_layers());
+
+    for _ in 0..CELLS_PER_FRAME {
+        let Some(cell) = grid.dirty.pop_front() else {
+            break;
+        };
+        grid.queued.remove(&cell);
+        let Some(index) = grid.index(cell) else {
+            continue;
+        };
+
+        let center = NavGrid::cell_center(cell, max.y + SCAN_HEIGHT);
+        let scan_distance = max.y - min.y + SCAN_HEIGHT * 2.0;
+        grid.ground[index] = spatial_query
+            .cast_ray(center, Dir3::NEG_Y, scan_distance, true, &filter)
+            // Starting inside something doesn't say where its top is
+            .filter(|hit| hit.distance > 0.0 && hit.normal.angle_between(Vector::Y) <= MAX_SLOPE)
+            .map(|hit| center.y - hit.distance)
+            .filter(|&ground| {
+                let above = center.with_y(ground + (CLEARANCE_HEIGHT + MAX_STEP) / 2.0);
+                spatial_query
+                    .shape_intersections(clearance, above, Quaternion::IDENTITY, &filter)
+                    .is_empty()
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid around the origin with flat ground everywhere but the `blocked` cells.
+    fn grid_with_blocked(blocked: &[IVec2]) -> NavGrid {
+        let mut grid = NavGrid {
+            ground: vec![Some(0.0); (GRID_CELLS * GRID_CELLS) as usize],
+            ..default()
+        };
+        for &cell in blocked {
+            let index = grid.index(cell).unwrap();
+            grid.ground[index] = None;
+        }
+        grid
+    }
+
+    fn at(cell: IVec2) -> Vector {
+        NavGrid::cell_center(cell, 0.0)
+    }
+
+    #[test]
+    fn start_at_the_goal_needs_no_waypoints() {
+        let grid = grid_with_blocked(&[]);
+        let path = grid.find_path(at(IVec2::new(3, 3)), at(IVec2::new(3, 3)));
+        assert_eq!(path, Some(Vec::new()));
+    }
+
+    #[test]
+    fn path_goes_around_a_blocked_cell() {
+        let blocked = IVec2::new(1, 0);
+        let grid = grid_with_blocked(&[blocked]);
+        let goal = IVec2::new(2, 0);
+
+        let path = grid.find_path(at(IVec2::ZERO), at(goal)).unwrap();
+        assert_eq!(path.last(), Some(&at(goal)));
+        // Straight through would need no waypoint but the goal
+        assert!(path.len() > 1);
+        assert!(
+            path.iter()
+                .all(|&waypoint| NavGrid::cell_at(waypoint) != blocked)
+        );
+    }
+
+    #[test]
+    fn walled_in_goal_is_unreachable() {
+        let goal = IVec2::new(5, 5);
+        let wall: Vec<_> = NEIGHBORS.iter().map(|&offset| goal + offset).collect();
+        let grid = grid_with_blocked(&wall);
+        assert_eq!(grid.find_path(at(IVec2::ZERO), at(goal)), None);
+    }
+
+    #[test]
+    fn blocked_goal_is_unreachable() {
+        let goal = IVec2::new(5, 5);
+        let grid = grid_with_blocked(&[goal]);
+        assert_eq!(grid.find_path(at(IVec2::ZERO), at(goal)), None);
+    }
+}
@@ -0,0 +1,213 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How high the deck the course runs along is, so gaps have something to fall into.
+const DECK_HEIGHT: f32 = 1.0;
+/// The length of the ramp up onto the deck at the start of every course.
+const ENTRY_RAMP_LENGTH: f32 = 3.0;
+const RAMP_THICKNESS: f32 = 0.2;
+const WALL_SECTION_LENGTH: f32 = 2.0;
+const WALL_THICKNESS: f32 = 0.3;
+const STEP_DEPTH: f32 = 0.4;
+/// The length of the flat landing at the top of stairs.
+const LANDING_LENGTH: f32 = 1.0;
+const PILLAR_SIZE: f32 = 0.6;
+const PILLAR_SPACING: f32 = 2.0;
+
+/// A section of an [`ObstacleCourse`]. Angles are in degrees.
+#[derive(Clone, Debug, Deserialize)]
+pub enum Obstacle {
+    /// A ramp `length` meters long rising at `angle`, with a drop back down to the deck at its
+    /// end.
+    Ramp { angle: f32, length: f32 },
+    /// A wall across the whole course, to jump or mantle over.
+    Wall { height: f32 },
+    /// Steps up to a landing, with a drop back down to the deck at its end.
+    Stairs { steps: u32, step_height: f32 },
+    /// A hole in the deck to jump over.
+    Gap { width: f32 },
+    /// Pillars staggered from side to side, to weave through.
+    Pillars { count: u32, height: f32 },
+}
+
+impl Obstacle {
+    /// How far along the course the section goes.
+    fn length(&self) -> f32 {
+        match *self {
+            Self::Ramp { length, .. } => length,
+            Self::Wall { .. } => WALL_SECTION_LENGTH,
+            Self::Stairs { steps, .. } => steps as f32 * STEP_DEPTH + LANDING_LENGTH,
+            Self::Gap { width } => width,
+            Self::Pillars { count, .. } => count as f32 * PILLAR_SPACING,
+        }
+    }
+
+    /// The boxes making up the section, as their size and transform relative to where the
+    /// section starts. Sections go toward -Z.
+    fn parts(&self, width: f32) -> Vec<(Vec3, Transform)> {
+        let length = self.length();
+        let mut parts = vec![deck(width, length)];
+
+        match *self {
+            Self::Ramp { angle, length } => {
+                parts.push(ramp(width, DECK_HEIGHT, length, angle.to_radians()));
+            }
+            Self::Wall { height } => parts.push((
+                Vec3::new(width, height, WALL_THICKNESS),
+                Transform::from_xyz(0.0, DECK_HEIGHT + height / 2.0, -length / 2.0),
+            )),
+            Self::Stairs { steps, step_height } => {
+                for step in 1..=steps {
+                    let height = step as f32 * step_height;
+                    parts.push((
+                        Vec3::new(width, height, STEP_DEPTH),
+                        Transform::from_xyz(
+                            0.0,
+                            DECK_HEIGHT + height / 2.0,
+                            -(step as f32 - 0.5) * STEP_DEPTH,
+                        ),
+                    ));
+                }
+                let height = steps as f32 * step_height;
+                parts.push((
+                    Vec3::new(width, height, LANDING_LENGTH),
+                    Transform::from_xyz(
+                        0.0,
+                        DECK_HEIGHT + height / 2.0,
+                        LANDING_LENGTH / 2.0 - length,
+                    ),
+                ));
+            }
+            Self::Gap { .. } => return Vec::new(),
+            Self::Pillars { count, height } => {
+                for pillar in 0..count {
+                    let side = if pillar % 2 == 0 { -1.0 } else { 1.0 };
+                    parts.push((
+                        Vec3::new(PILLAR_SIZE, height, PILLAR_SIZE),
+                        Transform::from_xyz(
+                            side * width / 4.0,
+                            DECK_HEIGHT + height / 2.0,
+                            -(pillar as f32 + 0.5) * PILLAR_SPACING,
+                        ),
+                    ));
+                }
+            }
+        }
+        parts
+    }
+}
+
+/// The deck under a section of the given length.
+fn deck(width: f32, length: f32) -> (Vec3, Transform) {
+    (
+        Vec3::new(width, DECK_HEIGHT, length),
+        Transform::from_xyz(0.0, DECK_HEIGHT / 2.0, -length / 2.0),
+    )
+}
+
+/// A ramp starting at `base` height and rising toward -Z at `angle` radians over `length`.
+fn ramp(width: f32, base: f32, length: f32, angle: f32) -> (Vec3, Transform) {
+    let rise = length * angle.tan();
+    let rotation = Quat::from_rotation_x(angle);
+    // The ramp's top face runs from the base to the top of the rise, so its center is half its
+    // thickness below that
+    let center = Vec3::new(0.0, base + rise / 2.0, -length / 2.0)
+        - rotation * Vec3::Y * RAMP_THICKNESS / 2.0;
+    (
+        Vec3::new(width, RAMP_THICKNESS, length / angle.cos()),
+        Transform::from_translation(center).with_rotation(rotation),
+    )
+}
+
+/// A line of obstacles for testing movement against, generated as static boxes going along the
+/// entity's -Z. It starts with a ramp up onto a raised deck the obstacles stand on. The boxes
+/// are regenerated whenever this changes.
+#[derive(Component, Clone, Debug, Deserialize)]
+#[require(Transform, Visibility)]
+#[serde(default)]
+pub struct ObstacleCourse {
+    pub width: f32,
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Default for ObstacleCourse {
+    fn default() -> Self {
+        Self {
+            width: 4.0,
+            obstacles: vec![
+                Obstacle::Ramp {
+                    angle: 20.0,
+                    length: 4.0,
+                },
+                Obstacle::Wall { height: 1.2 },
+                Obstacle::Stairs {
+                    steps: 5,
+                    step_height: 0.3,
+                },
+                Obstacle::Gap { width: 2.0 },
+                Obstacle::Pillars {
+                    count: 5,
+                    height: 3.0,
+                },
+                Obstacle::Ramp {
+                    angle: 35.0,
+                    length: 3.0,
+                },
+                Obstacle::Gap { width: 3.0 },
+                Obstacle::Wall { height: 2.0 },
+            ],
+        }
+    }
+}
+
+/// Marks the boxes of an [`ObstacleCourse`], which are its children.
+#[derive(Component)]
+struct ObstacleCoursePart;
+
+pub struct ObstacleCoursePlugin;
+
+impl Plugin for ObstacleCoursePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, build_obstacle_courses);
+    }
+}
+
+fn build_obstacle_courses(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &ObstacleCourse), Changed<ObstacleCourse>>,
+) {
+    for (entity, course) in &query {
+        let material = materials.add(Color::srgb(0.85, 0.55, 0.2));
+
+        let mut parts = vec![ramp(
+            course.width,
+            0.0,
+            ENTRY_RAMP_LENGTH,
+            (DECK_HEIGHT / ENTRY_RAMP_LENGTH).atan(),
+        )];
+        let mut start = -ENTRY_RAMP_LENGTH;
+        for obstacle in &course.obstacles {
+            for (size, mut transform) in obstacle.parts(course.width) {
+                transform.translation.z += start;
+                parts.push((size, transform));
+            }
+            start -= obstacle.length();
+        }
+
+        let mut entity = commands.entity(entity);
+        entity.despawn_related::<Children>();
+        for (size, transform) in parts {
+            entity.with_child((
+                ObstacleCoursePart,
+                RigidBody::Static,
+                Collider::cuboid(size.x, size.y, size.z),
+                Mesh3d(meshes.add(Cuboid::from_size(size))),
+                MeshMaterial3d(material.clone()),
+                transform,
+            ));
+        }
+    }
+}
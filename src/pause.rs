@@ -0,0 +1,300 @@
+use crate::cursor::{HoldsCursor, UiContext, set_cursor_grab};
+use crate::key_bindings::Rebinding;
+use crate::level::LevelState;
+use crate::main_menu::GameState;
+use crate::settings::Settings;
+use crate::ui::ReleasedButtons;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use bevy_enhanced_input::prelude::*;
+
+#[derive(SubStates, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[source(GameState = GameState::InGame)]
+pub enum PauseState {
+    #[default]
+    Running,
+    /// Game time, and with it physics, is stopped and the pause menu is shown.
+    Paused,
+}
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct TogglePause;
+
+#[derive(Component)]
+struct PauseMenu;
+
+/// A page of the pause menu, only one of which is shown at a time.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MenuPage {
+    Main,
+    Settings,
+}
+
+/// What a button in the pause menu does.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MenuButton {
+    Resume,
+    Settings,
+    /// Leaves the game for the main menu.
+    MainMenu,
+    /// Back from the settings to the main page.
+    Back,
+    WindowMode,
+    Volume,
+    InvertY,
+    HeadBob,
+    CameraShake,
+    Crosshair,
+    ScreenshotHud,
+}
+
+impl MenuButton {
+    /// The button's text, showing the current value for settings.
+    fn label(self, settings: &Settings) -> String {
+        let on_off = |on| if on { "On" } else { "Off" };
+        match self {
+            Self::Resume => "Resume".into(),
+            Self::Settings => "Settings".into(),
+            Self::MainMenu => "Main menu".into(),
+            Self::Back => "Back".into(),
+            Self::WindowMode => format!("Window: {:?}", settings.video.window_mode),
+            Self::Volume => format!("Volume: {:.0}%", settings.audio.master_volume * 100.0),
+            Self::InvertY => format!("Invert look: {}", on_off(settings.input.invert_y)),
+            Self::HeadBob => format!("Head bob: {}", on_off(settings.video.head_bob)),
+            Self::CameraShake => format!("Camera shake: {}", on_off(settings.video.camera_shake)),
+            Self::Crosshair => format!("Crosshair: {:?}", settings.video.crosshair),
+            Self::ScreenshotHud => format!(
+                "HUD in screenshots: {}",
+                on_off(settings.video.screenshot_hud)
+            ),
+        }
+    }
+}
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseState>()
+            .add_systems(Startup, spawn_pause_controls)
+            .add_systems(OnEnter(PauseState::Paused), pause)
+            .add_systems(OnExit(PauseState::Paused), resume)
+            .add_systems(
+                Update,
+                (
+                    pause_on_focus_loss.run_if(in_state(PauseState::Running)),
+                    (press_menu_buttons, update_menu_labels)
+                        .chain()
+                        .run_if(in_state(PauseState::Paused)),
+                ),
+            )
+            .add_observer(toggle_pause);
+    }
+}
+
+fn spawn_pause_controls(mut commands: Commands) {
+    commands.spawn((
+        UiContext,
+        actions!(UiContext[(Action::<TogglePause>::new(), bindings![KeyCode::Escape])]),
+    ));
+}
+
+fn toggle_pause(
+    _trigger: Trigger<Started<TogglePause>>,
+    pause_state: Option<Res<State<PauseState>>>,
+    rebinding: Res<Rebinding>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    // Escape cancels the rebind instead
+    if rebinding.is_active() {
+        return;
+    }
+    // There's nothing to pause outside the game
+    let Some(pause_state) = pause_state else {
+        return;
+    };
+    next_pause_state.set(match **pause_state {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+/// Alt-tabbing away pauses instead of leaving the cursor stuck to the game.
+fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_events.read().any(|event| !event.focused) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+/// Stops virtual time, which stops the fixed timestep and so the physics schedule, and shows
+/// the menu on its main page.
+fn pause(mut commands: Commands, mut time: ResMut<Time<Virtual>>, mut window: Single<&mut Window>) {
+    time.pause();
+    set_cursor_grab(&mut window, false);
+
+    let pages = [
+        (
+            MenuPage::Main,
+            "Paused",
+            &[
+                MenuButton::Resume,
+                MenuButton::Settings,
+                MenuButton::MainMenu,
+            ][..],
+        ),
+        (
+            MenuPage::Settings,
+            "Settings",
+            &[
+                MenuButton::WindowMode,
+                MenuButton::Volume,
+                MenuButton::InvertY,
+                MenuButton::HeadBob,
+                MenuButton::CameraShake,
+                MenuButton::Crosshair,
+                MenuButton::ScreenshotHud,
+                MenuButton::Back,
+            ][..],
+        ),
+    ];
+
+    commands
+        .spawn((
+            PauseMenu,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.5)),
+        ))
+        .with_children(|parent| {
+            for (page, title, buttons) in pages {
+                parent
+                    .spawn((
+                        page,
+                        Node {
+                            display: if page == MenuPage::Main {
+                                Display::Flex
+                            } else {
+                                Display::None
+                            },
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            row_gap: Val::Px(24.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|page| {
+                        page.spawn((
+                            Text::new(title),
+                            TextFont {
+                                font_size: 48.0,
+                                ..default()
+                            },
+                        ));
+                        for &button in buttons {
+                            // Labelled by `update_menu_labels`
+                            page.spawn((
+                                button,
+                                Button,
+                                Node {
+                                    padding: UiRect::axes(Val::Px(24.0), Val::Px(12.0)),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                            ))
+                            .with_child(Text::default());
+                        }
+                    });
+            }
+        });
+}
+
+fn resume(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    level_state: Option<Res<State<LevelState>>>,
+    mut window: Single<&mut Window>,
+    menus: Query<Entity, With<PauseMenu>>,
+    holders: Query<(), With<HoldsCursor>>,
+) {
+    // A loading level keeps time stopped until it's spawned
+    if level_state.is_none_or(|state| **state != LevelState::Loading) {
+        time.unpause();
+    }
+    set_cursor_grab(&mut window, holders.is_empty());
+
+    for menu in &menus {
+        commands.entity(menu).despawn();
+    }
+}
+
+/// Resumes, quits, switches between the menu's pages, or changes the setting clicked.
+fn press_menu_buttons(
+    mut buttons: ReleasedButtons<MenuButton>,
+    mut pages: Query<(&MenuPage, &mut Node)>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(button) = buttons.released() else {
+        return;
+    };
+
+    let mut show_page = |shown| {
+        for (page, mut node) in &mut pages {
+            node.display = if *page == shown {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    };
+    match button {
+        MenuButton::Resume => next_pause_state.set(PauseState::Running),
+        MenuButton::Settings => show_page(MenuPage::Settings),
+        MenuButton::MainMenu => next_game_state.set(GameState::MainMenu),
+        MenuButton::Back => show_page(MenuPage::Main),
+        MenuButton::WindowMode => {
+            settings.video.window_mode = settings.video.window_mode.next();
+        }
+        MenuButton::Volume => {
+            // Steps of a quarter, wrapping from full back around to muted
+            let steps = (settings.audio.master_volume * 4.0).round() as u32;
+            settings.audio.master_volume = ((steps + 1) % 5) as f32 / 4.0;
+        }
+        MenuButton::InvertY => settings.input.invert_y = !settings.input.invert_y,
+        MenuButton::HeadBob => settings.video.head_bob = !settings.video.head_bob,
+        MenuButton::CameraShake => {
+            settings.video.camera_shake = !settings.video.camera_shake;
+        }
+        MenuButton::Crosshair => settings.video.crosshair = settings.video.crosshair.next(),
+        MenuButton::ScreenshotHud => {
+            settings.video.screenshot_hud = !settings.video.screenshot_hud;
+        }
+    }
+}
+
+/// Writes each button's label, so settings show their new values once toggled.
+fn update_menu_labels(
+    settings: Res<Settings>,
+    buttons: Query<(&MenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        let label = button.label(&settings);
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            if text.0 != label {
+                text.0.clone_from(&label);
+            }
+        }
+    }
+}
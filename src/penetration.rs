@@ -0,0 +1,138 @@
+use crate::cube::Cube;
+use avian3d::{math::*, prelude::*};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Projectiles slower than this bounce off instead of punching through.
+const PENETRATION_MIN_SPEED: Scalar = 40.0;
+/// The share of its speed a projectile keeps per meter of material it passes through.
+const PENETRATION_SPEED_KEPT_PER_METER: Scalar = 0.5;
+
+/// A projectile that can pass through thin [`Cube`]s instead of bouncing off them. Its power is
+/// the total thickness of material it can still get through, in meters.
+#[derive(Component)]
+#[require(ActiveCollisionHooks::MODIFY_CONTACTS)]
+pub struct Penetrating {
+    power: Scalar,
+    /// The objects it has already been let through.
+    passed: Vec<Entity>,
+}
+
+impl Penetrating {
+    pub fn new(power: Scalar) -> Self {
+        Self {
+            power,
+            passed: Vec::new(),
+        }
+    }
+}
+
+/// Sent when a [`Penetrating`] projectile punches through a cube. The two never start
+/// colliding, so this stands in for the [`CollisionStarted`] the hit would have sent.
+#[derive(Event)]
+pub struct Penetrated {
+    pub projectile: Entity,
+    pub target: Entity,
+    /// The projectile's velocity as it hit, before it was slowed down.
+    pub velocity: Vector,
+}
+
+/// Drops the contacts between a [`Penetrating`] projectile and the cubes it punches through,
+/// and slows the projectile down by how thick they were along its path.
+#[derive(SystemParam)]
+pub struct PenetrationHooks<'w, 's> {
+    projectiles: Query<
+        'w,
+        's,
+        (
+            &'static Penetrating,
+            &'static Position,
+            &'static LinearVelocity,
+        ),
+    >,
+    targets: Query<
+        'w,
+        's,
+        (
+            &'static RigidBody,
+            &'static Collider,
+            &'static Position,
+            &'static Rotation,
+            &'static ColliderAabb,
+        ),
+        With<Cube>,
+    >,
+}
+
+impl CollisionHooks for PenetrationHooks<'_, '_> {
+    fn modify_contacts(&self, contacts: &mut ContactPair, commands: &mut Commands) -> bool {
+        let (projectile, target) = if self.projectiles.contains(contacts.collider1) {
+            (contacts.collider1, contacts.collider2)
+        } else {
+            (contacts.collider2, contacts.collider1)
+        };
+        let Ok((penetrating, position, velocity)) = self.projectiles.get(projectile) else {
+            return true;
+        };
+        if penetrating.passed.contains(&target) {
+            return false;
+        }
+
+        let Ok((rigid_body, collider, target_position, rotation, aabb)) = self.targets.get(target)
+        else {
+            return true;
+        };
+        let speed = velocity.length();
+        if *rigid_body != RigidBody::Dynamic || speed < PENETRATION_MIN_SPEED {
+            return true;
+        }
+
+        // How far the projectile's path runs through the target, from where it enters to where
+        // a ray cast back from beyond the target's bounds comes out
+        let direction = velocity.0 / speed;
+        // No path through the target is longer than its bounds' diagonal
+        let beyond = aabb.size().length();
+        let cast = |origin, direction, solid| {
+            collider
+                .cast_ray(
+                    *target_position,
+                    *rotation,
+                    origin,
+                    direction,
+                    beyond,
+                    solid,
+                )
+                .map(|(distance, _)| distance)
+        };
+        let Some(entry) = cast(position.0, direction, true) else {
+            return true;
+        };
+        let entry_point = position.0 + direction * entry;
+        let Some(back) = cast(entry_point + direction * beyond, -direction, false) else {
+            return true;
+        };
+        let thickness = beyond - back;
+        if thickness > penetrating.power {
+            return true;
+        }
+
+        let speed_kept = PENETRATION_SPEED_KEPT_PER_METER.powf(thickness);
+        commands.send_event(Penetrated {
+            projectile,
+            target,
+            velocity: velocity.0,
+        });
+        commands
+            .entity(projectile)
+            .queue(move |mut projectile: EntityWorldMut| {
+                if let Some(mut penetrating) = projectile.get_mut::<Penetrating>() {
+                    penetrating.power -= thickness;
+                    penetrating.passed.push(target);
+                }
+                if let Some(mut velocity) = projectile.get_mut::<LinearVelocity>() {
+                    velocity.0 *= speed_kept;
+                }
+            });
+        false
+    }
+}
@@ -0,0 +1,180 @@
+use crate::ammo::Ammo;
+use crate::combat::{Dead, Health};
+use crate::inventory::{Inventory, Item};
+use crate::player_movement::{PLAYER_HEALTH, Player};
+use crate::score::{AddScore, ScoreReason};
+use crate::trigger::{TriggerEnter, TriggerVolume, TriggerVolumeBundle};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+const PICKUP_RADIUS: f32 = 0.6;
+/// How far pickups bob up and down around where they're placed, in meters.
+const BOB_HEIGHT: f32 = 0.15;
+const BOB_SPEED: f32 = 2.0;
+/// How fast pickups spin, in radians per second.
+const SPIN_SPEED: f32 = 1.5;
+
+/// What a [`Pickup`] gives the player touching it.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum PickupKind {
+    /// Heals by this much, up to full health.
+    Health(f32),
+    /// Adds this many balls to the ammo reserve.
+    Ammo(u32),
+    /// Adds this many of an item to the inventory.
+    Item(Item, u32),
+    /// Worth this many points.
+    Collectible(u32),
+}
+
+/// A trigger volume giving the player touching it something, then disappearing for good or
+/// until it respawns.
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// How long the pickup takes to come back after being taken, in seconds. Taking it
+    /// despawns it if not set.
+    pub respawn_time: Option<f32>,
+}
+
+/// Counts down until a taken [`Pickup`] comes back. It's hidden and can't be taken until then.
+#[derive(Component)]
+struct Taken(Timer);
+
+/// The bobbing and spinning mesh of a [`Pickup`].
+#[derive(Component)]
+struct PickupVisual;
+
+#[derive(Bundle)]
+pub struct PickupBundle {
+    pickup: Pickup,
+    pub trigger_volume: TriggerVolumeBundle,
+}
+
+impl PickupBundle {
+    pub fn new(kind: PickupKind, respawn_time: Option<f32>, transform: Transform) -> Self {
+        Self {
+            pickup: Pickup { kind, respawn_time },
+            trigger_volume: TriggerVolumeBundle::new(Collider::sphere(PICKUP_RADIUS), transform),
+        }
+    }
+
+    /// The pickup's mesh, to be spawned as its child.
+    pub fn visual(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        kind: PickupKind,
+    ) -> impl Bundle + use<> {
+        let (mesh, color) = match kind {
+            PickupKind::Health(_) => (
+                meshes.add(Cuboid::new(0.4, 0.4, 0.15)),
+                Color::srgb(0.9, 0.15, 0.15),
+            ),
+            PickupKind::Ammo(_) => (
+                meshes.add(Cylinder::new(0.15, 0.4)),
+                Color::srgb(0.25, 0.25, 0.25),
+            ),
+            PickupKind::Item(item, _) => (meshes.add(Cuboid::from_length(0.35)), item.color()),
+            PickupKind::Collectible(_) => (
+                meshes.add(Torus::new(0.12, 0.25)),
+                Color::srgb(1.0, 0.8, 0.2),
+            ),
+        };
+
+        (
+            PickupVisual,
+            Mesh3d(mesh),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                emissive: LinearRgba::from(color) * 0.5,
+                ..default()
+            })),
+            Transform::default(),
+        )
+    }
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (animate_pickups, respawn_pickups))
+            .add_observer(take_pickup);
+    }
+}
+
+/// Gives the player what the pickup holds, unless they've got no use for it, like health
+/// while already at full health.
+#[allow(clippy::type_complexity)]
+fn take_pickup(
+    trigger: Trigger<TriggerEnter>,
+    mut commands: Commands,
+    mut add_score: EventWriter<AddScore>,
+    pickups: Query<&Pickup, Without<Taken>>,
+    mut players: Query<(&mut Health, &mut Ammo, &mut Inventory), (With<Player>, Without<Dead>)>,
+) {
+    let entity = trigger.target();
+    let Ok(pickup) = pickups.get(entity) else {
+        return;
+    };
+    let Ok((mut health, mut ammo, mut inventory)) = players.get_mut(trigger.entity) else {
+        return;
+    };
+
+    match pickup.kind {
+        PickupKind::Health(amount) => {
+            if health.0 >= PLAYER_HEALTH {
+                return;
+            }
+            health.0 = (health.0 + amount).min(PLAYER_HEALTH);
+        }
+        PickupKind::Ammo(amount) => ammo.add_reserve(amount),
+        PickupKind::Item(item, count) => {
+            // Whatever doesn't fit is lost, as long as some of it did
+            if inventory.add(item, count) == count {
+                return;
+            }
+        }
+        PickupKind::Collectible(points) => {
+            add_score.write(AddScore {
+                points,
+                reason: ScoreReason::Collectible,
+            });
+        }
+    }
+
+    match pickup.respawn_time {
+        Some(respawn_time) => {
+            commands.entity(entity).insert((
+                Taken(Timer::from_seconds(respawn_time, TimerMode::Once)),
+                Visibility::Hidden,
+            ));
+        }
+        None => commands.entity(entity).despawn(),
+    }
+}
+
+fn respawn_pickups(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut pickups: Query<(Entity, &mut Taken)>,
+) {
+    for (entity, mut taken) in &mut pickups {
+        if taken.0.tick(time.delta()).finished() {
+            commands
+                .entity(entity)
+                .remove::<Taken>()
+                .insert(Visibility::Inherited);
+        }
+    }
+}
+
+fn animate_pickups(time: Res<Time>, mut visuals: Query<&mut Transform, With<PickupVisual>>) {
+    let elapsed = time.elapsed_secs();
+    for mut transform in &mut visuals {
+        transform.translation.y = (elapsed * BOB_SPEED).sin() * BOB_HEIGHT;
+        transform.rotation = Quat::from_rotation_y(elapsed * SPIN_SPEED);
+    }
+}
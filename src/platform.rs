@@ -0,0 +1,247 @@
+use crate::player_movement::Player;
+use crate::wiring::Signal;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How close to a waypoint [`PlatformEasing::Smooth`] platforms start slowing down.
+const EASE_DISTANCE: Scalar = 1.5;
+/// The slowest eased platforms go, relative to their speed, so they still reach the waypoint.
+const MIN_EASE_FACTOR: Scalar = 0.15;
+
+/// How a platform goes through its waypoints.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum PlatformPath {
+    /// Back and forth, from the first waypoint to the last and back.
+    #[default]
+    PingPong,
+    /// Around in a loop, from the last waypoint straight back to the first.
+    Loop,
+}
+
+/// How a platform speeds up and slows down along its path.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum PlatformEasing {
+    /// Full speed all the way.
+    #[default]
+    Linear,
+    /// Slowing down near waypoints, so riders aren't jolted when the platform turns around.
+    Smooth,
+}
+
+/// What gets a platform moving.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum PlatformActivation {
+    /// Keeps moving all the time.
+    #[default]
+    Always,
+    /// Waits at each waypoint until a player steps on, like an elevator.
+    Ridden,
+    /// Waits at each waypoint until it's signalled, like by a pressure plate.
+    Signal,
+}
+
+/// A kinematic platform moving along a path of waypoints.
+///
+/// Platforms are moved through their [`LinearVelocity`] rather than their position, so Tnua
+/// sees the platform's velocity under a character standing on it and keeps the character's
+/// walking velocity relative to it. Tnua only catches up with that velocity at the walk's
+/// acceleration though, so riders are also given the platform's velocity under them directly,
+/// and aren't left behind when it speeds up, turns around or rotates.
+#[derive(Component)]
+pub struct MovingPlatform {
+    waypoints: Vec<Vector>,
+    speed: Scalar,
+    path: PlatformPath,
+    easing: PlatformEasing,
+    activation: PlatformActivation,
+    /// The index of the waypoint the platform is heading to.
+    target: usize,
+    /// Whether a ping-pong platform is going through its waypoints forward or backward.
+    reversed: bool,
+    /// Set while the platform is stopped at a waypoint, until it's activated.
+    waiting: bool,
+    /// Whether a player was standing on the platform last step, so stepping on can be told
+    /// apart from staying on.
+    ridden: bool,
+}
+
+impl MovingPlatform {
+    /// Creates a platform starting at the first of `waypoints` and moving through the rest at
+    /// `speed`. Platforms with fewer than two waypoints stay put.
+    pub fn along(waypoints: Vec<Vector>, speed: Scalar) -> Self {
+        Self {
+            waypoints,
+            speed,
+            path: PlatformPath::default(),
+            easing: PlatformEasing::default(),
+            activation: PlatformActivation::default(),
+            target: 1,
+            reversed: false,
+            waiting: false,
+            ridden: false,
+        }
+    }
+
+    pub fn with_path(mut self, path: PlatformPath) -> Self {
+        self.path = path;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: PlatformEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets what gets the platform moving. Platforms that wait start out waiting at their first
+    /// waypoint.
+    pub fn with_activation(mut self, activation: PlatformActivation) -> Self {
+        self.activation = activation;
+        self.waiting = activation != PlatformActivation::Always;
+        self
+    }
+
+    /// Sends a waiting platform on to its next waypoint. Waiting platforms are also sent on by
+    /// a [`Signal`] turning on, whatever their activation.
+    pub fn activate(&mut self) {
+        self.waiting = false;
+    }
+
+    /// The waypoint the platform is coming from.
+    fn previous(&self) -> usize {
+        let count = self.waypoints.len();
+        match self.path {
+            PlatformPath::Loop => (self.target + count - 1) % count,
+            PlatformPath::PingPong if self.reversed => self.target + 1,
+            PlatformPath::PingPong => self.target - 1,
+        }
+    }
+
+    /// Heads to the waypoint after the current target.
+    fn advance(&mut self) {
+        let last = self.waypoints.len() - 1;
+        match self.path {
+            PlatformPath::Loop => self.target = (self.target + 1) % (last + 1),
+            PlatformPath::PingPong => {
+                if self.target == last {
+                    self.reversed = true;
+                } else if self.target == 0 {
+                    self.reversed = false;
+                }
+                if self.reversed {
+                    self.target -= 1;
+                } else {
+                    self.target += 1;
+                }
+            }
+        }
+    }
+
+    /// The platform's speed at `position`, slowed down near waypoints if it's eased.
+    fn speed_at(&self, position: Vector) -> Scalar {
+        match self.easing {
+            PlatformEasing::Linear => self.speed,
+            PlatformEasing::Smooth => {
+                let distance = position
+                    .distance(self.waypoints[self.previous()])
+                    .min(position.distance(self.waypoints[self.target]));
+                self.speed * (distance / EASE_DISTANCE).clamp(MIN_EASE_FACTOR, 1.0)
+            }
+        }
+    }
+}
+
+/// The velocity a character was last given by the platform it's standing on.
+#[derive(Component, Default)]
+pub struct PlatformRider {
+    carried: Vector,
+}
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (move_platforms, carry_riders).chain())
+            .add_observer(signal_platform);
+    }
+}
+
+fn signal_platform(trigger: Trigger<Signal>, mut platforms: Query<&mut MovingPlatform>) {
+    if !trigger.on {
+        return;
+    }
+    if let Ok(mut platform) = platforms.get_mut(trigger.target()) {
+        platform.activate();
+    }
+}
+
+fn move_platforms(
+    time: Res<Time>,
+    riders: Query<&ShapeHits, With<Player>>,
+    mut query: Query<(Entity, &Position, &mut MovingPlatform, &mut LinearVelocity)>,
+) {
+    for (entity, position, mut platform, mut velocity) in &mut query {
+        if platform.waypoints.len() < 2 {
+            velocity.0 = Vector::ZERO;
+            continue;
+        }
+
+        // Characters float above the ground, so riders are found by what their ground caster
+        // hits rather than by contacts
+        let ridden = riders
+            .iter()
+            .any(|hits| hits.iter().any(|hit| hit.entity == entity));
+        let stepped_on = ridden && !platform.ridden;
+        platform.ridden = ridden;
+
+        if platform.waiting {
+            if stepped_on && platform.activation == PlatformActivation::Ridden {
+                platform.activate();
+            } else {
+                velocity.0 = Vector::ZERO;
+                continue;
+            }
+        }
+
+        let target = platform.waypoints[platform.target];
+        let offset = target - position.0;
+        let speed = platform.speed_at(position.0);
+
+        // Head on to the next waypoint once the next step would reach this one
+        if offset.length() <= speed * time.delta_secs() {
+            platform.advance();
+            platform.waiting = platform.activation != PlatformActivation::Always;
+        }
+        velocity.0 = offset.normalize_or_zero() * speed;
+    }
+}
+
+/// Gives characters the velocity of the platform at where they stand, found by what their ground
+/// caster hits. Only the change since the last step is added, so the character's own velocity is
+/// kept, and so is the platform's once they step or jump off.
+#[allow(clippy::type_complexity)]
+fn carry_riders(
+    platforms: Query<(&Position, &LinearVelocity, &AngularVelocity), With<MovingPlatform>>,
+    mut riders: Query<
+        (
+            &Position,
+            &ShapeHits,
+            &mut LinearVelocity,
+            &mut PlatformRider,
+        ),
+        (With<Player>, Without<MovingPlatform>),
+    >,
+) {
+    for (position, hits, mut velocity, mut rider) in &mut riders {
+        let Some((platform_position, linear, angular)) =
+            hits.iter().find_map(|hit| platforms.get(hit.entity).ok())
+        else {
+            rider.carried = Vector::ZERO;
+            continue;
+        };
+
+        let carried = linear.0 + angular.0.cross(position.0 - platform_position.0);
+        velocity.0 += carried - rider.carried;
+        rider.carried = carried;
+    }
+}
@@ -1,17 +1,89 @@
-use crate::ball::BallBundle;
-use crate::cube::CubeBundle;
+use crate::ammo::{Ammo, AmmoPlugin};
+use crate::camera::CameraSettings;
+use crate::carry::{Carry, CarryPlugin};
+use crate::checkpoint::LastCheckpoint;
+use crate::combat::{Damage, DamageKind, Health, Invulnerable};
+use crate::cursor::cursor_grabbed;
+use crate::grapple::{Grapple, GrapplePlugin};
+use crate::gravity_gun::{GravityGun, GravityGunPlugin};
+use crate::input_routing::LocalPlayer;
+use crate::inventory::{INVENTORY_SLOTS, Inventory};
+use crate::jump_pad::JumpPad;
+use crate::layers::GameLayer;
+use crate::mantle::{Mantle, MantlePlugin};
+use crate::melee::{MeleeCooldown, MeleePlugin};
+use crate::platform::PlatformRider;
+use crate::ragdoll::RagdollOnDeath;
+use crate::spectator::FlyCamera;
+use crate::surface::GroundSurface;
+use crate::tools::{Tool, ToolsPlugin};
+use crate::trigger::{TriggerEnter, TriggerExit, TriggerVolume};
+use crate::tuning::Tuning;
+use crate::wall_run::{WallRun, WallRunPlugin};
+use crate::water::{Medium, WaterPlugin};
 use avian3d::{math::*, prelude::*};
 use bevy::input::mouse::AccumulatedMouseMotion;
 use bevy::prelude::*;
 use bevy_enhanced_input::prelude::*;
-use bevy_tnua::prelude::{TnuaBuiltinJump, TnuaBuiltinWalk, TnuaController};
+use bevy_tnua::TnuaToggle;
+use bevy_tnua::builtins::{TnuaBuiltinDash, TnuaBuiltinKnockback};
+use bevy_tnua::prelude::{TnuaAction, TnuaBuiltinJump, TnuaBuiltinWalk, TnuaController};
+use serde::{Deserialize, Serialize};
 
 const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+/// Stick tilt past which the gamepad look starts speeding up.
+const LOOK_RAMP_TILT: f32 = 0.9;
+const STANDING_FLOAT_HEIGHT: Scalar = 1.0;
+const CROUCH_SPEED_FACTOR: Scalar = 0.5;
+const PRONE_SPEED_FACTOR: Scalar = 0.2;
+/// How much faster than the current sprint velocity a slide starts out.
+const SLIDE_BOOST: Scalar = 1.5;
+/// Exponential decay rate of slide velocity, per second.
+const SLIDE_DECAY: Scalar = 1.5;
+/// Below this speed a slide ends and the character settles into a crouch or stands up.
+const SLIDE_MIN_SPEED: Scalar = 4.0;
+const SLIDE_CAMERA_TILT: f32 = 0.08;
+/// How long after leaving the ground a character can still make a regular jump, in seconds.
+const COYOTE_TIME: f32 = 0.15;
+/// How long a jump press is remembered while the jump isn't possible yet, in seconds.
+const JUMP_BUFFER_TIME: f32 = 0.15;
+/// The health players start and respawn with.
+pub(crate) const PLAYER_HEALTH: f32 = 100.0;
+const DEFAULT_FIRE_RATE: f32 = 4.0;
+const DEFAULT_MAGAZINE_SIZE: u32 = 12;
+const DEFAULT_AMMO_RESERVE: u32 = 48;
+/// How long the throw button has to be held for a throw at full speed, in seconds.
+const THROW_CHARGE_TIME: f32 = 1.0;
+const DASH_DISTANCE: Scalar = 4.0;
+const DASH_SPEED: Scalar = 40.0;
+const DASH_COOLDOWN: f32 = 1.0;
+/// How long a dash keeps the character from taking damage, in seconds.
+const DASH_INVULNERABLE_TIME: f32 = 0.25;
+/// Landing faster than this hurts. Falling back down from a full jump on flat ground stays
+/// below it.
+const FALL_DAMAGE_MIN_SPEED: Scalar = 35.0;
+const FALL_DAMAGE_PER_SPEED: f32 = 5.0;
+/// How far ahead of the character steps are looked for.
+const STEP_PROBE_DISTANCE: Scalar = 0.3;
+/// How far past the front of a step its top is probed for.
+const STEP_PROBE_DEPTH: Scalar = 0.05;
+/// Step faces need a normal at most this far from horizontal, so slopes are left to Tnua.
+const MAX_STEP_NORMAL_Y: Scalar = 0.3;
+/// Roughly how long stepping up onto a step takes.
+const STEP_UP_TIME: Scalar = 0.1;
+const LADDER_CLIMB_SPEED: Scalar = 3.0;
+/// The speed a character pushes itself back and up with when jumping off a ladder.
+const LADDER_JUMP_OFF_SPEED: Scalar = 5.0;
 
 /// A marker component indicating that an entity is using a character controller.
 #[derive(Component)]
 pub struct Player;
 
+/// A marker component for a ladder volume. Ladders should be sensors on [`GameLayer::Ladder`].
+#[derive(Component)]
+#[require(TriggerVolume)]
+pub struct Ladder;
+
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct PlayerJump;
@@ -20,10 +92,27 @@ pub struct PlayerJump;
 #[action_output(bool)]
 pub struct PlayerSprint;
 
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerCrouch;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerProne;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayerDash;
+
 #[derive(InputAction)]
 #[action_output(Vec2)]
 pub struct PlayerMove;
 
+/// Gamepad camera look, on top of the mouse.
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct PlayerLook;
+
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct PlayerAction;
@@ -34,18 +123,274 @@ pub struct PlayerAltAction;
 
 /// The acceleration used for character movement.
 #[derive(Component)]
-pub struct MovementAcceleration(Scalar);
+pub struct MovementAcceleration(pub(crate) Scalar);
 
-/// The damping factor used for slowing down movement.
+/// The strength of a jump.
 #[derive(Component)]
-pub struct MovementDampingFactor(Scalar);
+pub struct JumpImpulse(pub(crate) Scalar);
 
-/// The strength of a jump.
+/// The number of extra jumps a character can make before touching the ground again.
+#[derive(Component)]
+pub struct MaxAirJumps(u32);
+
+/// The air jumps a character has left until it touches the ground again.
+#[derive(Component)]
+pub struct AirJumps(u32);
+
+/// When the character's ground caster last touched the ground outside of a jump, in elapsed
+/// seconds. Cleared once a jump uses up the coyote time.
+#[derive(Component)]
+pub struct LastGroundContact(Option<f32>);
+
+/// When the jump button was pressed, if that press hasn't been turned into a jump yet.
 #[derive(Component)]
-pub struct JumpImpulse(Scalar);
+pub struct BufferedJump(pub(crate) Option<f32>);
 
 #[derive(Component)]
-pub struct IsSprinting(bool);
+pub struct IsSprinting(pub(crate) bool);
+
+/// How upright a character is, ordered from lowest to tallest.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stance {
+    Prone,
+    Crouching,
+    #[default]
+    Standing,
+}
+
+/// The stance the character's input asks for. [`update_stance`] moves the character into it
+/// once there is room to.
+#[derive(Component, Default)]
+pub struct DesiredStance(Stance);
+
+/// The remaining ground-plane velocity of an ongoing slide, if any.
+#[derive(Component)]
+pub struct Slide(Option<Vector>);
+
+/// The character's stamina, spent on sprinting and jumping. See [`StaminaConfig`].
+#[derive(Component)]
+pub struct Stamina(pub(crate) f32);
+
+/// The fastest the character has fallen since it last left the ground or stopped falling.
+#[derive(Component)]
+pub struct FallSpeed(pub(crate) Scalar);
+
+/// Sent when a character touches the ground after falling.
+#[derive(Event)]
+pub struct Landed {
+    pub entity: Entity,
+    /// The downward speed the character hit the ground with.
+    pub impact_speed: Scalar,
+}
+
+/// Sent to shove a character, e.g. from explosions, hits or jump pads.
+#[derive(Event)]
+pub struct Knockback {
+    pub entity: Entity,
+    /// The change in velocity to apply.
+    pub impulse: Vector,
+}
+
+/// Set while the character is holding on to a ladder.
+#[derive(Component)]
+pub struct IsClimbing(pub(crate) bool);
+
+/// The ladders the character is overlapping, as they trigger [`TriggerEnter`] and
+/// [`TriggerExit`].
+#[derive(Component, Default)]
+pub struct LadderContacts(Vec<Entity>);
+
+/// How long the look stick has been held near full tilt, in seconds. Turning speeds up the
+/// longer it is held, see [`PlayerInputSettings::gamepad_look_boost`].
+#[derive(Component)]
+pub struct LookRamp(f32);
+
+/// When the throw button was pressed, while a throw is being charged up.
+#[derive(Component)]
+pub struct ThrowCharge(pub(crate) Option<f32>);
+
+impl ThrowCharge {
+    /// How far the throw being charged has charged up by `now`, from 0 to 1.
+    pub fn charge(&self, now: f32) -> Option<f32> {
+        self.0
+            .map(|start| ((now - start) / THROW_CHARGE_TIME).clamp(0.0, 1.0))
+    }
+}
+
+/// Limits how many balls the character can throw per second.
+#[derive(Component)]
+pub struct FireRate {
+    shots_per_second: f32,
+    /// When the next throw becomes available, in elapsed seconds.
+    next_shot: f32,
+    /// When the last throw was made, in elapsed seconds, if there was any.
+    last_shot: Option<f32>,
+}
+
+impl FireRate {
+    fn new(shots_per_second: f32) -> Self {
+        Self {
+            shots_per_second,
+            next_shot: 0.0,
+            last_shot: None,
+        }
+    }
+
+    pub fn last_shot(&self) -> Option<f32> {
+        self.last_shot
+    }
+
+    /// How far the next throw is from being available at `now`, from 0 right after a throw to
+    /// 1 once it's ready.
+    pub fn readiness(&self, now: f32) -> f32 {
+        (1.0 - (self.next_shot - now) * self.shots_per_second).clamp(0.0, 1.0)
+    }
+
+    /// Uses up the next shot if it's available by `now`, returning whether it was.
+    pub(crate) fn try_fire(&mut self, now: f32) -> bool {
+        if now < self.next_shot {
+            return false;
+        }
+
+        self.next_shot = now + self.shots_per_second.recip();
+        self.last_shot = Some(now);
+        true
+    }
+}
+
+/// When the character's next dash becomes available, in elapsed seconds.
+#[derive(Component)]
+pub struct DashCooldown(f32);
+
+/// How the sprint key switches sprinting on and off.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SprintMode {
+    /// Sprint while the key is held.
+    #[default]
+    Hold,
+    /// Each press switches sprinting on or off. Sprinting also ends when the character stops.
+    Toggle,
+}
+
+#[derive(Resource)]
+pub struct PlayerInputSettings {
+    pub sprint_mode: SprintMode,
+    /// Radians turned per pixel of mouse movement, horizontally and vertically.
+    pub mouse_sensitivity: Vec2,
+    /// Radians per second turned with the look stick fully tilted.
+    pub gamepad_look_sensitivity: Vec2,
+    /// The power the stick tilt is raised to. Above 1 small tilts turn slower, for finer aim.
+    pub gamepad_look_exponent: f32,
+    /// How much faster than the sensitivity the camera turns once the stick has been held at
+    /// full tilt for [`Self::gamepad_look_ramp_time`], e.g. 1.0 for twice as fast.
+    pub gamepad_look_boost: f32,
+    pub gamepad_look_ramp_time: f32,
+    /// Look down when the mouse or stick is pushed forward instead of up.
+    pub invert_y: bool,
+    /// Whether gamepads rumble on landings, throws and knockbacks.
+    pub rumble: bool,
+    /// Scales the strength of every rumble.
+    pub rumble_intensity: f32,
+}
+
+impl Default for PlayerInputSettings {
+    fn default() -> Self {
+        Self {
+            sprint_mode: SprintMode::default(),
+            mouse_sensitivity: Vec2::new(0.003, 0.002),
+            gamepad_look_sensitivity: Vec2::new(3.0, 2.0),
+            gamepad_look_exponent: 2.0,
+            gamepad_look_boost: 1.0,
+            gamepad_look_ramp_time: 0.5,
+            invert_y: false,
+            rumble: true,
+            rumble_intensity: 1.0,
+        }
+    }
+}
+
+impl PlayerInputSettings {
+    /// The yaw and pitch to turn by for a mouse movement in pixels.
+    pub fn mouse_look(&self, delta: Vec2) -> Vec2 {
+        self.apply_invert(-delta * self.mouse_sensitivity)
+    }
+
+    /// The yaw and pitch to turn by for holding the look stick for `delta_secs`, after it has
+    /// been at full tilt for `ramp` seconds.
+    pub fn gamepad_look(&self, stick: Vec2, ramp: f32, delta_secs: f32) -> Vec2 {
+        let tilt = stick.length();
+        if tilt == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let curved = stick / tilt * tilt.powf(self.gamepad_look_exponent);
+        let boost =
+            1.0 + self.gamepad_look_boost * (ramp / self.gamepad_look_ramp_time).clamp(0.0, 1.0);
+        self.apply_invert(
+            Vec2::new(-curved.x, curved.y) * self.gamepad_look_sensitivity * boost * delta_secs,
+        )
+    }
+
+    fn apply_invert(&self, look: Vec2) -> Vec2 {
+        if self.invert_y {
+            Vec2::new(look.x, -look.y)
+        } else {
+            look
+        }
+    }
+}
+
+/// Tunes how stamina is spent and recovered.
+#[derive(Resource)]
+pub struct StaminaConfig {
+    pub max: f32,
+    /// Stamina spent per second of sprinting.
+    pub sprint_drain: f32,
+    /// Stamina spent per jump, including air jumps.
+    pub jump_cost: f32,
+    /// Stamina recovered per second while not sprinting.
+    pub regen: f32,
+}
+
+impl Default for StaminaConfig {
+    fn default() -> Self {
+        Self {
+            max: 100.0,
+            sprint_drain: 25.0,
+            jump_cost: 15.0,
+            regen: 20.0,
+        }
+    }
+}
+
+/// The colliders and float heights a character switches between for each [`Stance`].
+#[derive(Component)]
+pub struct StanceShapes {
+    standing_collider: Collider,
+    standing_float_height: Scalar,
+    crouching_collider: Collider,
+    crouching_float_height: Scalar,
+    prone_collider: Collider,
+    prone_float_height: Scalar,
+}
+
+impl StanceShapes {
+    fn collider(&self, stance: Stance) -> &Collider {
+        match stance {
+            Stance::Prone => &self.prone_collider,
+            Stance::Crouching => &self.crouching_collider,
+            Stance::Standing => &self.standing_collider,
+        }
+    }
+
+    fn float_height(&self, stance: Stance) -> Scalar {
+        match stance {
+            Stance::Prone => self.prone_float_height,
+            Stance::Crouching => self.crouching_float_height,
+            Stance::Standing => self.standing_float_height,
+        }
+    }
+}
 
 /// The maximum angle a slope can have for a character controller
 /// to be able to climb and jump. If the slope is steeper than this angle,
@@ -53,12 +398,25 @@ pub struct IsSprinting(bool);
 #[derive(Component)]
 pub struct MaxSlopeAngle(Scalar);
 
+/// The tallest step the character walks up onto without jumping. The character also stays
+/// clinging to the ground when walking down steps up to this height.
+#[derive(Component)]
+pub struct StepHeight(Scalar);
+
+/// How quickly a change in movement input takes effect while the character is airborne, as an
+/// exponential rate per second. At 0 the direction held when leaving the ground is kept, and
+/// at infinity it steers like on the ground.
+#[derive(Component)]
+pub struct AirControl(Scalar);
+
 /// A bundle that contains components for character movement.
 #[derive(Bundle)]
 pub struct MovementBundle {
     acceleration: MovementAcceleration,
     jump_impulse: JumpImpulse,
+    max_air_jumps: MaxAirJumps,
     max_slope_angle: MaxSlopeAngle,
+    air_control: AirControl,
 }
 
 impl MovementBundle {
@@ -66,9 +424,21 @@ impl MovementBundle {
         Self {
             acceleration: MovementAcceleration(acceleration),
             jump_impulse: JumpImpulse(jump_impulse),
+            max_air_jumps: MaxAirJumps(1),
             max_slope_angle: MaxSlopeAngle(max_slope_angle),
+            air_control: AirControl(Scalar::INFINITY),
         }
     }
+
+    pub const fn with_air_jumps(mut self, max_air_jumps: u32) -> Self {
+        self.max_air_jumps = MaxAirJumps(max_air_jumps);
+        self
+    }
+
+    pub const fn with_air_control(mut self, air_control: Scalar) -> Self {
+        self.air_control = AirControl(air_control.max(0.0));
+        self
+    }
 }
 
 impl Default for MovementBundle {
@@ -84,32 +454,107 @@ pub struct PlayerBundle {
     player: Player,
     rigid_body: RigidBody,
     collider: Collider,
+    collision_layers: CollisionLayers,
     ground_caster: ShapeCaster,
     locked_axes: LockedAxes,
     movement: MovementBundle,
+    air_jumps: AirJumps,
+    last_ground_contact: LastGroundContact,
+    buffered_jump: BufferedJump,
     is_sprinting: IsSprinting,
+    stamina: Stamina,
+    dash_cooldown: DashCooldown,
+    stance: Stance,
+    desired_stance: DesiredStance,
+    slide: Slide,
+    is_climbing: IsClimbing,
+    ladder_contacts: LadderContacts,
+    platform_rider: PlatformRider,
+    health: Health,
+    last_checkpoint: LastCheckpoint,
+    ragdoll_on_death: RagdollOnDeath,
+    ground_surface: GroundSurface,
+    fall_speed: FallSpeed,
+    wall_run: WallRun,
+    mantle: Mantle,
+    grapple: Grapple,
+    medium: Medium,
+    stance_shapes: StanceShapes,
+    step_height: StepHeight,
+    look_ramp: LookRamp,
+    throw_charge: ThrowCharge,
+    fire_rate: FireRate,
+    ammo: Ammo,
+    inventory: Inventory,
+    tool: Tool,
+    gravity_gun: GravityGun,
+    carry: Carry,
+    melee_cooldown: MeleeCooldown,
+}
+
+/// Creates a shape caster shape as a slightly smaller version of `collider`.
+fn caster_shape(collider: &Collider) -> Collider {
+    let mut caster_shape = collider.clone();
+    caster_shape.set_scale(Vector::ONE * 0.99, 10);
+    caster_shape
 }
 
 impl PlayerBundle {
     pub fn new(collider: Collider) -> Self {
-        // Create shape caster as a slightly smaller version of collider
-        let mut caster_shape = collider.clone();
-        caster_shape.set_scale(Vector::ONE * 0.99, 10);
-
         Self {
             player: Player,
             rigid_body: RigidBody::Dynamic,
-            collider,
+            collider: collider.clone(),
+            collision_layers: CollisionLayers::new(GameLayer::Player, LayerMask::ALL),
             ground_caster: ShapeCaster::new(
-                caster_shape,
+                caster_shape(&collider),
                 Vector::ZERO,
                 Quaternion::default(),
                 Dir3::NEG_Y,
             )
-            .with_max_distance(0.2),
+            .with_max_distance(0.2)
+            .with_query_filter(SpatialQueryFilter::from_mask(GameLayer::solid())),
             locked_axes: LockedAxes::ROTATION_LOCKED,
             movement: MovementBundle::default(),
+            air_jumps: AirJumps(0),
+            last_ground_contact: LastGroundContact(None),
+            buffered_jump: BufferedJump(None),
             is_sprinting: IsSprinting(false),
+            stamina: Stamina(StaminaConfig::default().max),
+            dash_cooldown: DashCooldown(0.0),
+            stance: Stance::Standing,
+            desired_stance: DesiredStance(Stance::Standing),
+            slide: Slide(None),
+            look_ramp: LookRamp(0.0),
+            throw_charge: ThrowCharge(None),
+            fire_rate: FireRate::new(DEFAULT_FIRE_RATE),
+            ammo: Ammo::new(DEFAULT_MAGAZINE_SIZE, DEFAULT_AMMO_RESERVE),
+            inventory: Inventory::new(INVENTORY_SLOTS),
+            tool: Tool::default(),
+            gravity_gun: GravityGun::default(),
+            carry: Carry::default(),
+            melee_cooldown: MeleeCooldown::default(),
+            is_climbing: IsClimbing(false),
+            ladder_contacts: LadderContacts::default(),
+            platform_rider: PlatformRider::default(),
+            health: Health(PLAYER_HEALTH),
+            last_checkpoint: LastCheckpoint::default(),
+            ragdoll_on_death: RagdollOnDeath,
+            ground_surface: GroundSurface::default(),
+            fall_speed: FallSpeed(0.0),
+            wall_run: WallRun::default(),
+            mantle: Mantle::default(),
+            grapple: Grapple::default(),
+            medium: Medium::default(),
+            stance_shapes: StanceShapes {
+                standing_collider: collider.clone(),
+                standing_float_height: STANDING_FLOAT_HEIGHT,
+                crouching_collider: collider.clone(),
+                crouching_float_height: STANDING_FLOAT_HEIGHT,
+                prone_collider: collider,
+                prone_float_height: STANDING_FLOAT_HEIGHT,
+            },
+            step_height: StepHeight(0.3),
         }
     }
 
@@ -119,7 +564,57 @@ impl PlayerBundle {
         jump_impulse: Scalar,
         max_slope_angle: Scalar,
     ) -> Self {
-        self.movement = MovementBundle::new(acceleration, jump_impulse, max_slope_angle);
+        self.movement = MovementBundle::new(acceleration, jump_impulse, max_slope_angle)
+            .with_air_jumps(self.movement.max_air_jumps.0)
+            .with_air_control(self.movement.air_control.0);
+        self
+    }
+
+    pub fn with_air_jumps(mut self, max_air_jumps: u32) -> Self {
+        self.movement = self.movement.with_air_jumps(max_air_jumps);
+        self
+    }
+
+    pub fn with_air_control(mut self, air_control: Scalar) -> Self {
+        self.movement = self.movement.with_air_control(air_control);
+        self
+    }
+
+    /// Sets the collider and float height used while crouching. The collider should be
+    /// shorter than the standing one so the character fits under low geometry.
+    pub fn with_crouch(mut self, collider: Collider, float_height: Scalar) -> Self {
+        self.stance_shapes.crouching_collider = collider;
+        self.stance_shapes.crouching_float_height = float_height;
+        self
+    }
+
+    /// Sets the collider and float height used while prone, typically a capsule lying along
+    /// the character's forward axis.
+    pub fn with_prone(mut self, collider: Collider, float_height: Scalar) -> Self {
+        self.stance_shapes.prone_collider = collider;
+        self.stance_shapes.prone_float_height = float_height;
+        self
+    }
+
+    pub fn with_fire_rate(mut self, shots_per_second: f32) -> Self {
+        self.fire_rate = FireRate::new(shots_per_second);
+        self
+    }
+
+    /// Starts the character off with a full magazine of `magazine_size` balls and `reserve`
+    /// more to reload from.
+    pub fn with_ammo(mut self, magazine_size: u32, reserve: u32) -> Self {
+        self.ammo = Ammo::new(magazine_size, reserve);
+        self
+    }
+
+    pub fn with_inventory(mut self, inventory: Inventory) -> Self {
+        self.inventory = inventory;
+        self
+    }
+
+    pub fn with_step_height(mut self, step_height: Scalar) -> Self {
+        self.step_height = StepHeight(step_height);
         self
     }
 }
@@ -128,163 +623,958 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_input_context::<Player>();
+        app.add_input_context::<Player>()
+            .init_resource::<StaminaConfig>()
+            .init_resource::<PlayerInputSettings>()
+            .add_event::<Landed>()
+            .add_event::<Knockback>()
+            .add_plugins((
+                WallRunPlugin,
+                MantlePlugin,
+                WaterPlugin,
+                GrapplePlugin,
+                AmmoPlugin,
+                ToolsPlugin,
+                GravityGunPlugin,
+                CarryPlugin,
+                MeleePlugin,
+            ));
+
+        app.add_systems(
+            Update,
+            (
+                rotate_camera.run_if(not(any_with_component::<FlyCamera>).and(cursor_grabbed)),
+                track_ground_contact,
+                detect_landing,
+                apply_fall_damage,
+                climb_ladder,
+                climb_steps,
+                update_stamina,
+                perform_buffered_jump,
+                bounce_on_jump_pads,
+                apply_knockback,
+                update_slide,
+                update_stance,
+                tilt_camera,
+            )
+                .chain(),
+        )
+        .add_systems(Update, apply_tuning)
+        .add_observer(handle_player_jump)
+        .add_observer(handle_player_move)
+        .add_observer(handle_player_sprint)
+        .add_observer(handle_player_sprint_release)
+        .add_observer(handle_player_dash)
+        .add_observer(handle_player_crouch)
+        .add_observer(handle_player_uncrouch)
+        .add_observer(handle_player_prone)
+        .add_observer(handle_player_stop)
+        .add_observer(touch_ladder)
+        .add_observer(leave_ladder);
+    }
+}
 
-        app.add_systems(Update, (rotate_camera).chain())
-            .add_observer(handle_player_jump)
-            .add_observer(handle_player_move)
-            .add_observer(handle_player_sprint)
-            .add_observer(handle_player_stop)
-            .add_observer(handle_player_action)
-            .add_observer(handle_player_alt_action);
+/// Applies the [`Tuning`] to players as they spawn, and to all of them whenever it changes.
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    mut players: Query<(
+        Ref<Player>,
+        &mut MovementAcceleration,
+        &mut JumpImpulse,
+        &mut StanceShapes,
+    )>,
+) {
+    for (player, mut acceleration, mut jump_impulse, mut stance_shapes) in &mut players {
+        if !tuning.is_changed() && !player.is_added() {
+            continue;
+        }
+        acceleration.0 = tuning.speed;
+        jump_impulse.0 = tuning.jump_impulse;
+        stance_shapes.standing_float_height = tuning.float_height;
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_player_move(
     trigger: Trigger<Fired<PlayerMove>>,
+    time: Res<Time>,
+    tuning: Res<Tuning>,
     mut query: Query<
         (
             &MovementAcceleration,
+            &MaxSlopeAngle,
+            &StepHeight,
+            &AirControl,
+            &ShapeHits,
             &mut TnuaController,
             &Transform,
             &IsSprinting,
+            &Stance,
+            &StanceShapes,
+            &Slide,
+            &GroundSurface,
         ),
         With<Player>,
     >,
 ) {
     let movement = trigger.value;
 
-    let Ok(data) = query.single_mut() else {
+    let Ok(data) = query.get_mut(trigger.target()) else {
         return;
     };
-    let (acceleration, mut controller, transform, is_sprinting) = data;
+    let (
+        acceleration,
+        max_slope_angle,
+        step_height,
+        air_control,
+        ground_hits,
+        mut controller,
+        transform,
+        is_sprinting,
+        stance,
+        stance_shapes,
+        slide,
+        ground_surface,
+    ) = data;
 
-    let mut forward = transform.forward().as_vec3();
-    let mut right = transform.right().as_vec3();
-    forward.y = 0.0;
-    right.y = 0.0;
-    forward = forward.normalize();
-    right = right.normalize();
+    // The slide drives the basis until it runs out of speed, and steering is held off while
+    // being knocked back so the walk doesn't damp the shove away
+    if slide.0.is_some() || controller.action_name() == Some(TnuaBuiltinKnockback::NAME) {
+        return;
+    }
+
+    // The body only ever turns about its own up, so its axes already lie in the ground plane
+    let forward = transform.forward().as_vec3();
+    let right = transform.right().as_vec3();
 
     let relative_forward = movement.y * forward;
     let relative_right = movement.x * right;
 
     let mut velocity = relative_forward + relative_right;
 
-    let acceleration = if is_sprinting.0 {
-        acceleration.0 * 2.0
+    let acceleration = if *stance == Stance::Prone {
+        acceleration.0 * PRONE_SPEED_FACTOR
+    } else if *stance == Stance::Crouching {
+        acceleration.0 * CROUCH_SPEED_FACTOR
+    } else if is_sprinting.0 {
+        acceleration.0 * tuning.sprint_multiplier
     } else {
         acceleration.0
     };
 
-    velocity.x *= acceleration;
-    velocity.z *= acceleration;
+    velocity *= acceleration * ground_surface.speed_factor();
+
+    if ground_hits.is_empty() && air_control.0.is_finite() {
+        let previous_velocity = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .map_or(velocity, |(basis, _)| basis.desired_velocity);
+        // Eased by the frame's length, so steering in the air is the same at any frame rate
+        let blend = 1.0 - (-air_control.0 * time.delta_secs()).exp();
+        velocity = previous_velocity.lerp(velocity, blend);
+    }
 
     controller.basis(TnuaBuiltinWalk {
         desired_velocity: velocity,
-        float_height: 1.0,
+        float_height: stance_shapes.float_height(*stance),
+        cling_distance: step_height.0,
+        max_slope: max_slope_angle.0,
+        acceleration: tuning.walk_acceleration * ground_surface.acceleration_factor(),
         ..default()
     });
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_player_stop(
-    _trigger: Trigger<Completed<PlayerMove>>,
-    mut query: Query<(&mut TnuaController, &mut IsSprinting), With<Player>>,
+    trigger: Trigger<Completed<PlayerMove>>,
+    settings: Res<PlayerInputSettings>,
+    tuning: Res<Tuning>,
+    mut query: Query<
+        (
+            &MaxSlopeAngle,
+            &StepHeight,
+            &mut TnuaController,
+            &mut IsSprinting,
+            &Stance,
+            &StanceShapes,
+            &Slide,
+            &GroundSurface,
+        ),
+        With<Player>,
+    >,
 ) {
-    let Ok((mut controller, mut is_sprinting)) = query.single_mut() else {
+    let Ok(data) = query.get_mut(trigger.target()) else {
         return;
     };
+    let (
+        max_slope_angle,
+        step_height,
+        mut controller,
+        mut is_sprinting,
+        stance,
+        stance_shapes,
+        slide,
+        ground_surface,
+    ) = data;
 
+    if settings.sprint_mode == SprintMode::Toggle {
+        is_sprinting.0 = false;
+    }
+    if slide.0.is_some() {
+        return;
+    }
+
+    // On ice, the character slides on for a while before stopping
     controller.basis(TnuaBuiltinWalk {
         desired_velocity: Vec3::ZERO,
-        float_height: 1.0,
+        float_height: stance_shapes.float_height(*stance),
+        cling_distance: step_height.0,
+        max_slope: max_slope_angle.0,
+        acceleration: tuning.walk_acceleration * ground_surface.acceleration_factor(),
         ..default()
     });
-
-    is_sprinting.0 = false;
 }
 
 fn handle_player_jump(
-    _trigger: Trigger<Started<PlayerJump>>,
-    mut query: Query<(&JumpImpulse, &mut TnuaController), With<Player>>,
+    trigger: Trigger<Started<PlayerJump>>,
+    time: Res<Time>,
+    mut query: Query<&mut BufferedJump, With<Player>>,
 ) {
-    for (jump_impulse, mut controller) in &mut query {
+    if let Ok(mut buffered_jump) = query.get_mut(trigger.target()) {
+        buffered_jump.0 = Some(time.elapsed_secs());
+    }
+}
+
+/// Records ground contact for coyote time and refills air jumps. Contact while a jump is
+/// still taking off doesn't count, so the jump can't be repeated from the same ground.
+fn track_ground_contact(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            &ShapeHits,
+            &TnuaController,
+            &MaxAirJumps,
+            &mut AirJumps,
+            &mut LastGroundContact,
+        ),
+        With<Player>,
+    >,
+) {
+    for (ground_hits, controller, max_air_jumps, mut air_jumps, mut last_ground_contact) in
+        &mut query
+    {
+        let jumping = controller.action_name() == Some(TnuaBuiltinJump::NAME);
+        if !ground_hits.is_empty() && !jumping {
+            last_ground_contact.0 = Some(time.elapsed_secs());
+            air_jumps.0 = max_air_jumps.0;
+        }
+    }
+}
+
+/// Sends [`Landed`] when the ground caster touches ground after the character was falling.
+/// Movement while swimming doesn't count as falling.
+fn detect_landing(
+    mut landed: EventWriter<Landed>,
+    mut query: Query<(Entity, &ShapeHits, &LinearVelocity, &Medium, &mut FallSpeed), With<Player>>,
+) {
+    for (entity, ground_hits, velocity, medium, mut fall_speed) in &mut query {
+        let falling_speed = -velocity.y;
+
+        if ground_hits.is_empty() {
+            fall_speed.0 = if matches!(medium, Medium::Air) && falling_speed > 0.0 {
+                fall_speed.0.max(falling_speed)
+            } else {
+                0.0
+            };
+        } else if fall_speed.0 > 0.0 {
+            landed.write(Landed {
+                entity,
+                impact_speed: fall_speed.0,
+            });
+            fall_speed.0 = 0.0;
+        }
+    }
+}
+
+fn apply_fall_damage(mut landed: EventReader<Landed>, mut damage: EventWriter<Damage>) {
+    for event in landed.read() {
+        let excess_speed = event.impact_speed - FALL_DAMAGE_MIN_SPEED;
+        if excess_speed > 0.0 {
+            damage.write(Damage {
+                target: event.entity,
+                amount: excess_speed * FALL_DAMAGE_PER_SPEED,
+                kind: DamageKind::Fall,
+                source: None,
+            });
+        }
+    }
+}
+
+/// Turns a buffered jump press into a jump once the character is on the ground or within
+/// coyote time, or spends an air jump if it has one left.
+#[allow(clippy::type_complexity)]
+pub(crate) fn perform_buffered_jump(
+    time: Res<Time>,
+    stamina_config: Res<StaminaConfig>,
+    mut query: Query<
+        (
+            &JumpImpulse,
+            &mut Stamina,
+            &mut BufferedJump,
+            &mut LastGroundContact,
+            &mut AirJumps,
+            &mut TnuaController,
+        ),
+        With<Player>,
+    >,
+) {
+    let now = time.elapsed_secs();
+
+    for (
+        jump_impulse,
+        mut stamina,
+        mut buffered_jump,
+        mut last_ground_contact,
+        mut air_jumps,
+        mut controller,
+    ) in &mut query
+    {
+        let Some(pressed_at) = buffered_jump.0 else {
+            continue;
+        };
+        if now - pressed_at > JUMP_BUFFER_TIME {
+            buffered_jump.0 = None;
+            continue;
+        }
+
+        // Stay buffered in case enough stamina recovers before the press expires
+        if stamina.0 < stamina_config.jump_cost {
+            continue;
+        }
+
+        let can_ground_jump = last_ground_contact
+            .0
+            .is_some_and(|contact| now - contact <= COYOTE_TIME);
+        if !can_ground_jump {
+            if air_jumps.0 == 0 {
+                continue;
+            }
+            air_jumps.0 -= 1;
+        }
+
+        stamina.0 -= stamina_config.jump_cost;
+        buffered_jump.0 = None;
+        last_ground_contact.0 = None;
+        // Coyote time and air jumps are already decided above, so Tnua shouldn't second-guess it
         controller.action(TnuaBuiltinJump {
             height: jump_impulse.0,
+            allow_in_air: true,
+            ..default()
+        });
+    }
+}
+
+/// Climbs ladders: pushing forward while overlapping one grabs it, after which forward and back
+/// input move the character straight up and down. Climbing off the top, stepping down onto the
+/// ground or jumping lets go again.
+#[allow(clippy::type_complexity)]
+fn climb_ladder(
+    mut commands: Commands,
+    move_action: Query<&Action<PlayerMove>>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &Actions<Player>,
+            &LadderContacts,
+            &ShapeHits,
+            &mut IsClimbing,
+            &mut BufferedJump,
+            &mut LinearVelocity,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        entity,
+        transform,
+        actions,
+        ladder_contacts,
+        ground_hits,
+        mut is_climbing,
+        mut buffered_jump,
+        mut velocity,
+    ) in &mut query
+    {
+        let movement = move_action
+            .iter_many(actions)
+            .next()
+            .map_or(Vec2::ZERO, |action| **action);
+        let on_ladder = !ladder_contacts.0.is_empty();
+
+        if !is_climbing.0 {
+            if on_ladder && movement.y > 0.0 {
+                is_climbing.0 = true;
+                // Tnua would otherwise keep the character walking, so take over the body entirely
+                commands
+                    .entity(entity)
+                    .insert((RigidBody::Kinematic, TnuaToggle::Disabled));
+            } else {
+                continue;
+            }
+        }
+
+        let forward = transform
+            .forward()
+            .as_vec3()
+            .with_y(0.0)
+            .normalize_or_zero();
+        let let_go = if buffered_jump.0.take().is_some() {
+            velocity.0 = (Vector::Y - forward) * LADDER_JUMP_OFF_SPEED;
+            true
+        } else if !on_ladder {
+            // Carry on forward over the top, or just drop off the bottom
+            velocity.0 = forward * LADDER_CLIMB_SPEED * movement.y.max(0.0);
+            true
+        } else if movement.y < 0.0 && !ground_hits.is_empty() {
+            velocity.0 = Vector::ZERO;
+            true
+        } else {
+            velocity.0 = Vector::Y * LADDER_CLIMB_SPEED * movement.y;
+            false
+        };
+
+        if let_go {
+            is_climbing.0 = false;
+            commands
+                .entity(entity)
+                .insert((RigidBody::Dynamic, TnuaToggle::Enabled));
+        }
+    }
+}
+
+fn touch_ladder(
+    trigger: Trigger<TriggerEnter>,
+    ladders: Query<(), With<Ladder>>,
+    mut characters: Query<&mut LadderContacts>,
+) {
+    let ladder = trigger.target();
+    if !ladders.contains(ladder) {
+        return;
+    }
+    if let Ok(mut ladder_contacts) = characters.get_mut(trigger.entity) {
+        ladder_contacts.0.push(ladder);
+    }
+}
+
+fn leave_ladder(trigger: Trigger<TriggerExit>, mut characters: Query<&mut LadderContacts>) {
+    let ladder = trigger.target();
+    if let Ok(mut ladder_contacts) = characters.get_mut(trigger.entity) {
+        ladder_contacts.0.retain(|&contact| contact != ladder);
+    }
+}
+
+/// Lifts a walking character onto a step in front of it that is no taller than its
+/// [`StepHeight`], which the capsule would otherwise stop dead against.
+#[allow(clippy::type_complexity)]
+fn climb_steps(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &ShapeHits,
+            &TnuaController,
+            &StepHeight,
+            &Stance,
+            &StanceShapes,
+            &mut LinearVelocity,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        entity,
+        transform,
+        collider,
+        ground_hits,
+        controller,
+        step_height,
+        stance,
+        stance_shapes,
+        mut velocity,
+    ) in &mut query
+    {
+        if ground_hits.is_empty() {
+            continue;
+        }
+        let Some(direction) = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .and_then(|(basis, _)| Dir3::new(basis.desired_velocity.with_y(0.0)).ok())
+        else {
+            continue;
+        };
+
+        let position = transform.translation;
+        let shape = caster_shape(collider);
+        let filter =
+            SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+        let config = ShapeCastConfig::from_max_distance(STEP_PROBE_DISTANCE);
+
+        // Something is in the way at the current height...
+        let Some(hit) = spatial_query.cast_shape(
+            &shape,
+            position,
+            Quat::IDENTITY,
+            direction,
+            &config,
+            &filter,
+        ) else {
+            continue;
+        };
+        if hit.normal1.y.abs() > MAX_STEP_NORMAL_Y {
+            continue;
+        }
+
+        // ...but not when raised by the step height
+        let raised = position + Vector::Y * step_height.0;
+        if spatial_query
+            .cast_shape(&shape, raised, Quat::IDENTITY, direction, &config, &filter)
+            .is_some()
+        {
+            continue;
+        }
+
+        // Find the top of the step, relative to where the character floats above the ground
+        let foot = position.y - stance_shapes.float_height(*stance);
+        let origin = (hit.point1 + direction * STEP_PROBE_DEPTH).with_y(foot + step_height.0);
+        let Some(step) = spatial_query.cast_ray(origin, Dir3::NEG_Y, step_height.0, true, &filter)
+        else {
+            continue;
+        };
+        let rise = origin.y - step.distance - foot;
+        if rise > 0.0 {
+            velocity.y = velocity.y.max(rise / STEP_UP_TIME);
+        }
+    }
+}
+
+/// Launches characters standing on a [`JumpPad`] with a [`Knockback`] of its impulse.
+fn bounce_on_jump_pads(
+    mut knockback: EventWriter<Knockback>,
+    jump_pads: Query<&JumpPad>,
+    query: Query<(Entity, &ShapeHits, &TnuaController), With<Player>>,
+) {
+    for (entity, ground_hits, controller) in &query {
+        // Still on the pad for a moment after being launched
+        if controller.action_name() == Some(TnuaBuiltinKnockback::NAME) {
+            continue;
+        }
+
+        if let Some(jump_pad) = ground_hits
+            .iter()
+            .find_map(|hit| jump_pads.get(hit.entity).ok())
+        {
+            knockback.write(Knockback {
+                entity,
+                impulse: jump_pad.0,
+            });
+        }
+    }
+}
+
+/// Shoves characters with Tnua's knockback action, which keeps the walk basis from cancelling
+/// out the impulse until the character has slowed back down. See also [`handle_player_move`].
+fn apply_knockback(
+    mut knockback: EventReader<Knockback>,
+    mut query: Query<&mut TnuaController, With<Player>>,
+) {
+    for event in knockback.read() {
+        let Ok(mut controller) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        controller.action(TnuaBuiltinKnockback {
+            shove: event.impulse,
             ..default()
         });
     }
 }
 
 fn handle_player_sprint(
-    _trigger: Trigger<Started<PlayerSprint>>,
+    trigger: Trigger<Started<PlayerSprint>>,
+    settings: Res<PlayerInputSettings>,
+    mut query: Query<(&mut IsSprinting, &Stamina), With<Player>>,
+) {
+    let Ok((mut is_sprinting, stamina)) = query.get_mut(trigger.target()) else {
+        return;
+    };
+
+    if settings.sprint_mode == SprintMode::Toggle && is_sprinting.0 {
+        is_sprinting.0 = false;
+    } else if stamina.0 > 0.0 {
+        is_sprinting.0 = true;
+    }
+}
+
+fn handle_player_sprint_release(
+    trigger: Trigger<Completed<PlayerSprint>>,
+    settings: Res<PlayerInputSettings>,
     mut query: Query<&mut IsSprinting, With<Player>>,
 ) {
-    let Ok(mut is_sprinting) = query.single_mut() else {
+    let Ok(mut is_sprinting) = query.get_mut(trigger.target()) else {
         return;
     };
 
-    is_sprinting.0 = true;
+    if settings.sprint_mode == SprintMode::Hold {
+        is_sprinting.0 = false;
+    }
 }
 
-pub fn rotate_camera(
-    accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
-    mut query: Query<&mut Transform, With<Player>>,
+/// Drains stamina while sprinting, stopping the sprint once it runs out, and recovers it
+/// otherwise. Holding sprint while standing still doesn't count as sprinting.
+fn update_stamina(
+    time: Res<Time>,
+    config: Res<StaminaConfig>,
+    mut query: Query<(&TnuaController, &mut Stamina, &mut IsSprinting), With<Player>>,
+) {
+    for (controller, mut stamina, mut is_sprinting) in &mut query {
+        let moving = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .is_some_and(|(basis, _)| basis.desired_velocity != Vec3::ZERO);
+        if is_sprinting.0 && moving {
+            stamina.0 = (stamina.0 - config.sprint_drain * time.delta_secs()).max(0.0);
+            if stamina.0 == 0.0 {
+                is_sprinting.0 = false;
+            }
+        } else {
+            stamina.0 = (stamina.0 + config.regen * time.delta_secs()).min(config.max);
+        }
+    }
+}
+
+/// Dashes in the direction the character is moving, or where it is facing when standing still,
+/// untouchable for a moment.
+fn handle_player_dash(
+    trigger: Trigger<Started<PlayerDash>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut query: Query<(&Transform, &mut DashCooldown, &mut TnuaController), With<Player>>,
 ) {
-    let Ok(mut transform) = query.single_mut() else {
+    let Ok((transform, mut cooldown, mut controller)) = query.get_mut(trigger.target()) else {
         return;
     };
 
-    let sensitivity = Vec2::new(0.003, 0.002);
-    let delta = accumulated_mouse_motion.delta;
+    let now = time.elapsed_secs();
+    if now < cooldown.0 {
+        return;
+    }
 
-    if delta != Vec2::ZERO {
-        let delta_yaw = -delta.x * sensitivity.x;
-        let delta_pitch = -delta.y * sensitivity.y;
+    let moving = controller
+        .concrete_basis::<TnuaBuiltinWalk>()
+        .map_or(Vector::ZERO, |(basis, _)| {
+            basis.desired_velocity.with_y(0.0)
+        });
+    let facing = transform.forward().as_vec3().with_y(0.0);
+    let Ok(direction) = Dir3::new(moving).or_else(|_| Dir3::new(facing)) else {
+        return;
+    };
 
-        let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
-        let yaw = yaw + delta_yaw;
-        let pitch = (pitch + delta_pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    controller.action(TnuaBuiltinDash {
+        displacement: direction * DASH_DISTANCE,
+        allow_in_air: true,
+        speed: DASH_SPEED,
+        ..default()
+    });
+    cooldown.0 = now + DASH_COOLDOWN;
+    commands
+        .entity(trigger.target())
+        .insert(Invulnerable(now + DASH_INVULNERABLE_TIME));
+}
 
-        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+/// Re-feeds the current walk basis after applying `update` to a copy of its input.
+fn update_walk_basis(controller: &mut TnuaController, update: impl FnOnce(&mut TnuaBuiltinWalk)) {
+    let mut basis = controller
+        .concrete_basis::<TnuaBuiltinWalk>()
+        .map(|(basis, _)| basis.clone())
+        .unwrap_or_default();
+    update(&mut basis);
+
+    controller.basis(basis);
+}
+
+/// Switches the character to `new_stance`, swapping in that stance's collider, ground caster shape
+/// and float height.
+fn set_stance(
+    new_stance: Stance,
+    stance: &mut Stance,
+    stance_shapes: &StanceShapes,
+    collider: &mut Collider,
+    ground_caster: &mut ShapeCaster,
+    controller: &mut TnuaController,
+) {
+    *stance = new_stance;
+    *collider = stance_shapes.collider(new_stance).clone();
+    ground_caster.shape = caster_shape(collider);
+    let float_height = stance_shapes.float_height(new_stance);
+    update_walk_basis(controller, |basis| basis.float_height = float_height);
+}
+
+fn handle_player_crouch(
+    trigger: Trigger<Started<PlayerCrouch>>,
+    mut query: Query<
+        (
+            &Stance,
+            &mut DesiredStance,
+            &mut IsSprinting,
+            &mut Slide,
+            &TnuaController,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((stance, mut desired_stance, mut is_sprinting, mut slide, controller)) =
+        query.get_mut(trigger.target())
+    else {
+        return;
+    };
+
+    desired_stance.0 = Stance::Crouching;
+
+    // Crouching out of a sprint turns the sprint's momentum into a slide
+    if *stance == Stance::Standing && is_sprinting.0 {
+        let sprint_velocity = controller
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .map_or(Vector::ZERO, |(basis, _)| basis.desired_velocity);
+        let ground_velocity = sprint_velocity.with_y(0.0) * SLIDE_BOOST;
+        if ground_velocity.length() > SLIDE_MIN_SPEED {
+            slide.0 = Some(ground_velocity);
+            is_sprinting.0 = false;
+        }
     }
 }
 
-pub fn handle_player_action(
-    _trigger: Trigger<Fired<PlayerAction>>,
-    query: Query<&Transform, With<Player>>,
-    mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<StandardMaterial>>,
+/// Toggles between lying prone and standing. Crouch presses also get up out of prone.
+fn handle_player_prone(
+    trigger: Trigger<Started<PlayerProne>>,
+    mut query: Query<&mut DesiredStance, With<Player>>,
 ) {
-    let Ok(transform) = query.single() else {
+    let Ok(mut desired_stance) = query.get_mut(trigger.target()) else {
         return;
     };
 
-    let forward = transform.forward().normalize() * 1.0;
-    let mut transform = *transform;
-    transform.translation.y += 0.1;
-    transform.translation += forward;
-    let ball = BallBundle::new(meshes, materials, transform);
+    desired_stance.0 = if desired_stance.0 == Stance::Prone {
+        Stance::Standing
+    } else {
+        Stance::Prone
+    };
+}
+
+/// Decays an ongoing slide's velocity and ends the slide once it gets too slow, leaving the
+/// character crouched. [`update_stance`] stands it back up if crouch was released.
+fn update_slide(
+    time: Res<Time>,
+    mut query: Query<(&mut Slide, &mut TnuaController), With<Player>>,
+) {
+    for (mut slide, mut controller) in &mut query {
+        let Some(velocity) = slide.0 else {
+            continue;
+        };
+
+        let velocity = velocity * (-SLIDE_DECAY * time.delta_secs()).exp();
+        let desired_velocity = if velocity.length() < SLIDE_MIN_SPEED {
+            slide.0 = None;
+            Vector::ZERO
+        } else {
+            slide.0 = Some(velocity);
+            velocity
+        };
 
-    commands.spawn(ball);
+        update_walk_basis(&mut controller, |basis| {
+            basis.desired_velocity = desired_velocity;
+        });
+    }
 }
 
-pub fn handle_player_alt_action(
-    _trigger: Trigger<Started<PlayerAltAction>>,
-    query: Query<&Transform, With<Player>>,
-    mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<StandardMaterial>>,
+fn handle_player_uncrouch(
+    trigger: Trigger<Completed<PlayerCrouch>>,
+    mut query: Query<&mut DesiredStance, With<Player>>,
 ) {
-    let Ok(transform) = query.single() else {
+    let Ok(mut desired_stance) = query.get_mut(trigger.target()) else {
         return;
     };
 
-    let forward = transform.forward().as_vec3();
-    let mut transform = *transform;
-    transform.translation += forward;
-    let cube = CubeBundle::new(meshes, materials, transform);
+    if desired_stance.0 == Stance::Crouching {
+        desired_stance.0 = Stance::Standing;
+    }
+}
+
+/// Moves characters into their [`DesiredStance`]. Getting lower happens right away, while
+/// getting up waits until the taller collider would no longer overlap anything overhead,
+/// stopping at a crouch on the way up from prone if only that fits.
+#[allow(clippy::type_complexity)]
+fn update_stance(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Stance,
+            &DesiredStance,
+            &Slide,
+            &StanceShapes,
+            &mut Collider,
+            &mut ShapeCaster,
+            &mut TnuaController,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        entity,
+        transform,
+        mut stance,
+        desired_stance,
+        slide,
+        stance_shapes,
+        mut collider,
+        mut caster,
+        mut controller,
+    ) in &mut query
+    {
+        // Sliding keeps the character crouched even if crouch was already released
+        let desired = if slide.0.is_some() {
+            desired_stance.0.min(Stance::Crouching)
+        } else {
+            desired_stance.0
+        };
+        if desired == *stance {
+            continue;
+        }
 
-    commands.spawn(cube);
+        let filter =
+            SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+        let fits = |candidate: Stance| {
+            if candidate < *stance {
+                return true;
+            }
+
+            // Check the taller collider where it would end up once its float height is reached
+            let rise = stance_shapes.float_height(candidate) - stance_shapes.float_height(*stance);
+            let position = transform.translation + Vector::Y * rise;
+            spatial_query
+                .shape_intersections(
+                    stance_shapes.collider(candidate),
+                    position,
+                    transform.rotation,
+                    &filter,
+                )
+                .is_empty()
+        };
+
+        let new_stance = if fits(desired) {
+            desired
+        } else if *stance == Stance::Prone && fits(Stance::Crouching) {
+            Stance::Crouching
+        } else {
+            continue;
+        };
+
+        set_stance(
+            new_stance,
+            &mut stance,
+            stance_shapes,
+            &mut collider,
+            &mut caster,
+            &mut controller,
+        );
+    }
+}
+
+/// Rolls the player's camera slightly while sliding and eases it back afterwards.
+fn tilt_camera(
+    time: Res<Time>,
+    players: Query<(&Slide, &Children), With<Player>>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+) {
+    for (slide, children) in &players {
+        let target_roll = if slide.0.is_some() {
+            SLIDE_CAMERA_TILT
+        } else {
+            0.0
+        };
+
+        let mut iter = cameras.iter_many_mut(children);
+        while let Some(mut transform) = iter.fetch_next() {
+            let (yaw, pitch, mut roll) = transform.rotation.to_euler(EulerRot::YXZ);
+            roll.smooth_nudge(&target_roll, 10.0, time.delta_secs());
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+        }
+    }
+}
+
+/// Turns the player's body about its own up with horizontal mouse movement and pitches only its
+/// camera with vertical movement, so the collider and movement axes stay upright.
+#[allow(clippy::type_complexity)]
+pub fn rotate_camera(
+    time: Res<Time>,
+    accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
+    camera_settings: Res<CameraSettings>,
+    settings: Res<PlayerInputSettings>,
+    look_action: Query<&Action<PlayerLook>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &Children,
+            &Actions<Player>,
+            Option<&LocalPlayer>,
+            &mut LookRamp,
+        ),
+        With<Player>,
+    >,
+    mut cameras: Query<(&mut Transform, &Projection), (With<Camera3d>, Without<Player>)>,
+) {
+    for (mut transform, children, actions, local_player, mut look_ramp) in &mut query {
+        // Zooming in slows the camera down by the same amount so aiming stays precise
+        let zoom = cameras
+            .iter_many(children)
+            .find_map(|(_, projection)| match projection {
+                Projection::Perspective(perspective) => {
+                    Some(perspective.fov / camera_settings.base_fov)
+                }
+                _ => None,
+            })
+            .unwrap_or(1.0);
+        let stick = look_action
+            .iter_many(actions)
+            .next()
+            .map_or(Vec2::ZERO, |action| **action);
+        if stick.length() >= LOOK_RAMP_TILT {
+            look_ramp.0 += time.delta_secs();
+        } else {
+            look_ramp.0 = 0.0;
+        }
+        // The mouse only turns the player playing on it
+        let mouse = if local_player.is_none_or(|player| player.uses_keyboard_mouse()) {
+            accumulated_mouse_motion.delta
+        } else {
+            Vec2::ZERO
+        };
+        let delta = (settings.mouse_look(mouse)
+            + settings.gamepad_look(stick, look_ramp.0, time.delta_secs()))
+            * zoom;
+
+        if delta != Vec2::ZERO {
+            let (delta_yaw, delta_pitch) = (delta.x, delta.y);
+
+            transform.rotate_local_y(delta_yaw);
+
+            let mut iter = cameras.iter_many_mut(children);
+            while let Some((mut camera_transform, _)) = iter.fetch_next() {
+                let (yaw, pitch, roll) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+                let pitch = (pitch + delta_pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+                camera_transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+            }
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use crate::settings::config_dir;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// The folder holding a folder for each profile, in the folder from [`config_dir`].
+const PROFILES_DIR: &str = "profiles";
+/// Remembers the profile played last, in the folder from [`config_dir`].
+const LAST_PROFILE_FILE: &str = "last_profile.ron";
+/// The profile played before any other was made.
+const DEFAULT_PROFILE: &str = "Default";
+
+/// The save slot being played. Its folder holds its own settings, key bindings, stats and world
+/// saves, each reloaded by its plugin when the profile is switched.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Profile {
+    pub name: String,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.into(),
+        }
+    }
+}
+
+impl Profile {
+    /// Where this profile's files are kept.
+    pub fn dir(&self) -> PathBuf {
+        config_dir().join(PROFILES_DIR).join(&self.name)
+    }
+
+    /// The profile played last, or the default one if there's none or it can't be read.
+    fn last() -> Self {
+        let path = config_dir().join(LAST_PROFILE_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!(
+                    "Couldn't read the last profile from {}: {error}",
+                    path.display()
+                );
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(
+                "Couldn't parse the last profile from {}: {error}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+
+    /// Creates the profile's folder, so it's found by [`Profile::all`] before anything is
+    /// saved into it, and remembers it as the profile played last.
+    fn remember(&self) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize the profile: {error}");
+                return;
+            }
+        };
+
+        let path = config_dir().join(LAST_PROFILE_FILE);
+        if let Err(error) = fs::create_dir_all(self.dir()).and_then(|()| fs::write(&path, contents))
+        {
+            error!(
+                "Couldn't write the last profile to {}: {error}",
+                path.display()
+            );
+        }
+    }
+
+    /// Every profile with a folder, sorted by name, along with the default one.
+    pub fn all() -> Vec<Self> {
+        let dir = config_dir().join(PROFILES_DIR);
+        let mut names = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect(),
+            Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(error) => {
+                warn!("Couldn't list the profiles in {}: {error}", dir.display());
+                Vec::new()
+            }
+        };
+        if !names.iter().any(|name| name == DEFAULT_PROFILE) {
+            names.push(DEFAULT_PROFILE.into());
+        }
+        names.sort();
+        names.into_iter().map(|name| Self { name }).collect()
+    }
+
+    /// A profile named "Profile N", with the lowest N not taken by any of `existing`.
+    pub fn unused(existing: &[Self]) -> Self {
+        (2..)
+            .map(|number| Self {
+                name: format!("Profile {number}"),
+            })
+            .find(|profile| !existing.contains(profile))
+            .expect("there are fewer profiles than numbers")
+    }
+}
+
+/// Sent to switch to another profile, which is created if it doesn't exist yet.
+#[derive(Event)]
+pub struct SwitchProfile(pub Profile);
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        // Inserted right away, since the settings and key bindings are read from it as their
+        // plugins are built
+        app.insert_resource(Profile::last())
+            .add_event::<SwitchProfile>()
+            .add_systems(Update, switch_profile);
+    }
+}
+
+fn switch_profile(mut events: EventReader<SwitchProfile>, mut profile: ResMut<Profile>) {
+    // Only the latest request matters
+    let Some(SwitchProfile(next)) = events.read().last() else {
+        return;
+    };
+    if *profile != *next {
+        *profile = next.clone();
+        profile.remember();
+        info!("Switched to profile {:?}", profile.name);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::layers::GameLayer;
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// The hardest players push props without a [`PushLimit`], as the acceleration they give
+/// them. Keeps light props from being launched by a character walking into them.
+const MAX_PUSH_ACCELERATION: Scalar = 30.0;
+
+/// The most force, in newtons, players push this body with. Overrides the default of giving it
+/// no more than [`MAX_PUSH_ACCELERATION`].
+#[derive(Component, Clone, Copy)]
+pub struct PushLimit(pub Scalar);
+
+pub struct PushPlugin;
+
+impl Plugin for PushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedPostUpdate,
+            limit_pushing.after(PhysicsSet::StepSimulation),
+        );
+    }
+}
+
+/// Takes back whatever impulse players gave props this step beyond their limit. Other dynamic
+/// bodies, like projectiles and other characters, are left to the physics.
+#[allow(clippy::type_complexity)]
+fn limit_pushing(
+    time: Res<Time>,
+    collisions: Collisions,
+    players: Query<Entity, With<Player>>,
+    mut bodies: Query<
+        (
+            &RigidBody,
+            &CollisionLayers,
+            &ComputedMass,
+            &mut LinearVelocity,
+            Option<&PushLimit>,
+        ),
+        Without<Player>,
+    >,
+) {
+    for player in &players {
+        for contacts in collisions.collisions_with(player) {
+            // Normals point from the first collider to the second, which is pushed along them
+            let (body, impulse) = if contacts.collider1 == player {
+                (
+                    contacts.body2.unwrap_or(contacts.collider2),
+                    contacts.total_normal_impulse(),
+                )
+            } else {
+                (
+                    contacts.body1.unwrap_or(contacts.collider1),
+                    -contacts.total_normal_impulse(),
+                )
+            };
+            let Ok((rigid_body, layers, mass, mut velocity, limit)) = bodies.get_mut(body) else {
+                continue;
+            };
+            if !rigid_body.is_dynamic() || layers.memberships & GameLayer::Prop == LayerMask::NONE {
+                continue;
+            }
+
+            let max_force = limit.map_or(mass.value() * MAX_PUSH_ACCELERATION, |limit| limit.0);
+            let excess = impulse.length() - max_force * time.delta_secs();
+            if excess > 0.0 {
+                velocity.0 -= impulse.normalize() * excess * mass.inverse();
+            }
+        }
+    }
+}
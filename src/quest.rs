@@ -0,0 +1,201 @@
+use crate::dialog::DialogEvent;
+use crate::inventory::{Inventory, Item};
+use crate::level::LevelState;
+use crate::player_movement::Player;
+use crate::score::{AddScore, ScoreReason};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// A goal set by a level, made of objectives done one after another.
+#[derive(Clone, Deserialize)]
+pub struct Quest {
+    pub name: String,
+    /// The [`DialogEvent`] giving the quest, or `None` for it to be given along with the level.
+    #[serde(default)]
+    pub started_by: Option<String>,
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub reward: QuestReward,
+}
+
+#[derive(Clone, Deserialize)]
+pub enum Objective {
+    /// Get within `radius` of `position`.
+    Reach { position: Vec3, radius: f32 },
+    /// Hurt anything but a player this many times, counting hits scored while the objective is
+    /// the current one.
+    HitTargets(u32),
+    /// Carry at least `count` of `item`.
+    Collect { item: Item, count: u32 },
+}
+
+/// Handed out when a quest is completed.
+#[derive(Clone, Default, Deserialize)]
+pub struct QuestReward {
+    #[serde(default)]
+    pub points: u32,
+    /// Added to the player's inventory, as much as fits.
+    #[serde(default)]
+    pub items: Vec<(Item, u32)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuestState {
+    /// Waiting for its [`started_by`](Quest::started_by) dialog event.
+    NotStarted,
+    /// Working on the objective at this index.
+    Active(usize),
+    Completed,
+}
+
+/// A quest of the current level and how far along it is.
+pub struct QuestProgress {
+    pub quest: Quest,
+    pub state: QuestState,
+    /// Hits scored towards the current [`Objective::HitTargets`].
+    pub hits: u32,
+}
+
+impl QuestProgress {
+    /// The objective being worked on, if the quest is active.
+    pub fn objective(&self) -> Option<&Objective> {
+        match self.state {
+            QuestState::Active(index) => self.quest.objectives.get(index),
+            _ => None,
+        }
+    }
+}
+
+/// The quests of the current level, replaced whenever a level is spawned.
+#[derive(Resource, Default)]
+pub struct QuestLog {
+    pub quests: Vec<QuestProgress>,
+}
+
+impl QuestLog {
+    pub fn new(quests: &[Quest]) -> Self {
+        Self {
+            quests: quests
+                .iter()
+                .map(|quest| QuestProgress {
+                    quest: quest.clone(),
+                    state: if quest.started_by.is_some() {
+                        QuestState::NotStarted
+                    } else {
+                        QuestState::Active(0)
+                    },
+                    hits: 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Sent when the last objective of a quest is done, after its reward is handed out.
+#[derive(Event)]
+pub struct QuestCompleted {
+    pub name: String,
+}
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuestLog>()
+            .add_event::<QuestCompleted>()
+            .add_systems(
+                Update,
+                (start_quests, count_hits, advance_quests)
+                    .chain()
+                    .run_if(in_state(LevelState::Playing)),
+            );
+    }
+}
+
+fn start_quests(mut dialog_events: EventReader<DialogEvent>, mut quest_log: ResMut<QuestLog>) {
+    for event in dialog_events.read() {
+        for progress in &mut quest_log.quests {
+            if progress.state == QuestState::NotStarted
+                && progress.quest.started_by.as_ref() == Some(&event.event)
+            {
+                progress.state = QuestState::Active(0);
+            }
+        }
+    }
+}
+
+fn count_hits(mut add_score: EventReader<AddScore>, mut quest_log: ResMut<QuestLog>) {
+    let hits = add_score
+        .read()
+        .filter(|event| event.reason == ScoreReason::Hit)
+        .count() as u32;
+    if hits == 0 {
+        return;
+    }
+    for progress in &mut quest_log.quests {
+        if matches!(progress.objective(), Some(Objective::HitTargets(_))) {
+            progress.hits += hits;
+        }
+    }
+}
+
+/// Moves quests on past the objectives any player has done, handing out the reward of the
+/// quests they finish to the player who finished them.
+fn advance_quests(
+    mut quest_log: ResMut<QuestLog>,
+    mut players: Query<(Entity, &GlobalTransform, &mut Inventory), With<Player>>,
+    mut add_score: EventWriter<AddScore>,
+    mut completed: EventWriter<QuestCompleted>,
+) {
+    // The first player who has done the quest's current objective
+    let done_by = |progress: &QuestProgress| {
+        let objective = progress.objective()?;
+        players
+            .iter()
+            .find(|(_, transform, inventory)| match *objective {
+                Objective::Reach { position, radius } => {
+                    transform.translation().distance(position) <= radius
+                }
+                Objective::HitTargets(count) => progress.hits >= count,
+                Objective::Collect { item, count } => inventory.count(item) >= count,
+            })
+            .map(|(player, ..)| player)
+    };
+    // Only touch the log when something changes, for the HUD to redraw on change
+    let finished: Vec<_> = quest_log
+        .bypass_change_detection()
+        .quests
+        .iter()
+        .enumerate()
+        .filter_map(|(index, progress)| Some((index, done_by(progress)?)))
+        .collect();
+
+    for (index, player) in finished {
+        let progress = &mut quest_log.quests[index];
+        let QuestState::Active(objective) = progress.state else {
+            continue;
+        };
+        progress.hits = 0;
+        if objective + 1 < progress.quest.objectives.len() {
+            progress.state = QuestState::Active(objective + 1);
+            continue;
+        }
+
+        progress.state = QuestState::Completed;
+        let reward = &progress.quest.reward;
+        if reward.points > 0 {
+            add_score.write(AddScore {
+                points: reward.points,
+                reason: ScoreReason::Quest,
+            });
+        }
+        if let Ok((_, _, mut inventory)) = players.get_mut(player) {
+            for &(item, count) in &reward.items {
+                inventory.add(item, count);
+            }
+        }
+        completed.write(QuestCompleted {
+            name: progress.quest.name.clone(),
+        });
+    }
+}
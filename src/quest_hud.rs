@@ -0,0 +1,129 @@
+use crate::player_movement::Player;
+use crate::quest::{Objective, QuestCompleted, QuestLog, QuestProgress};
+use bevy::prelude::*;
+
+/// How far the quest list sits from the left of the screen, in pixels.
+const QUEST_HUD_MARGIN: f32 = 24.0;
+/// How long the name of a completed quest stays up, fading out, in seconds.
+const COMPLETED_FADE_TIME: f32 = 3.0;
+
+/// Lists the current objective of each active quest.
+#[derive(Component)]
+struct ObjectiveList;
+
+#[derive(Component, Default)]
+struct CompletedText {
+    shown_at: Option<f32>,
+}
+
+pub struct QuestHudPlugin;
+
+impl Plugin for QuestHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_quest_hud)
+            .add_systems(Update, (update_objectives, show_completed, fade_completed));
+    }
+}
+
+fn spawn_quest_hud(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(30.0),
+            left: Val::Px(QUEST_HUD_MARGIN),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![
+            (
+                ObjectiveList,
+                Text::default(),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+            ),
+            (
+                CompletedText::default(),
+                Text::default(),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 0.85, 0.3, 0.0)),
+            ),
+        ],
+    ));
+}
+
+/// Redraws the objectives when quests move on, and every frame while any is to reach a place,
+/// to keep the distance to it up to date.
+fn update_objectives(
+    quest_log: Res<QuestLog>,
+    player: Option<Single<&GlobalTransform, With<Player>>>,
+    mut lists: Query<&mut Text, With<ObjectiveList>>,
+) {
+    let reaching = quest_log
+        .quests
+        .iter()
+        .any(|progress| matches!(progress.objective(), Some(Objective::Reach { .. })));
+    if !quest_log.is_changed() && !reaching {
+        return;
+    }
+
+    let player_position = player.map(|player| player.translation());
+    let lines: Vec<_> = quest_log
+        .quests
+        .iter()
+        .filter_map(|progress| {
+            let objective = describe(progress, player_position)?;
+            Some(format!("{}\n  {objective}", progress.quest.name))
+        })
+        .collect();
+    for mut text in &mut lists {
+        text.0 = lines.join("\n");
+    }
+}
+
+fn describe(progress: &QuestProgress, player_position: Option<Vec3>) -> Option<String> {
+    Some(match *progress.objective()? {
+        Objective::Reach { position, .. } => match player_position {
+            Some(player_position) => {
+                format!(
+                    "Reach the spot ({:.0} m)",
+                    player_position.distance(position)
+                )
+            }
+            None => "Reach the spot".to_string(),
+        },
+        Objective::HitTargets(count) => {
+            format!("Hit targets ({}/{count})", progress.hits.min(count))
+        }
+        Objective::Collect { item, count } => format!("Collect {} x{count}", item.name()),
+    })
+}
+
+fn show_completed(
+    time: Res<Time>,
+    mut completed: EventReader<QuestCompleted>,
+    mut texts: Query<(&mut CompletedText, &mut Text)>,
+) {
+    for event in completed.read() {
+        for (mut completed_text, mut text) in &mut texts {
+            completed_text.shown_at = Some(time.elapsed_secs());
+            text.0 = format!("Quest complete: {}", event.name);
+        }
+    }
+}
+
+fn fade_completed(time: Res<Time>, mut texts: Query<(&CompletedText, &mut TextColor)>) {
+    let now = time.elapsed_secs();
+    for (completed_text, mut color) in &mut texts {
+        let alpha = completed_text.shown_at.map_or(0.0, |shown_at| {
+            (1.0 - (now - shown_at) / COMPLETED_FADE_TIME).clamp(0.0, 1.0)
+        });
+        color.0.set_alpha(alpha);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::combat::{Dead, Died};
+use crate::layers::GameLayer;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_tnua::TnuaToggle;
+
+/// A body part of a ragdoll: a capsule with its radius and length, centered at `offset` from
+/// the character's center, hanging off the part at `parent` by a joint at `joint`.
+struct PartShape {
+    radius: Scalar,
+    length: Scalar,
+    offset: Vector,
+    /// The index of the part this one hangs off, and where they're joined relative to the
+    /// character's center. The first part has none.
+    parent: Option<(usize, Vector)>,
+}
+
+const fn part(
+    radius: Scalar,
+    length: Scalar,
+    offset: [Scalar; 3],
+    parent: Option<(usize, [Scalar; 3])>,
+) -> PartShape {
+    let [x, y, z] = offset;
+    PartShape {
+        radius,
+        length,
+        offset: Vector::new(x, y, z),
+        parent: match parent {
+            Some((index, [x, y, z])) => Some((index, Vector::new(x, y, z))),
+            None => None,
+        },
+    }
+}
+
+/// The parts of a ragdoll sized for the player's capsule, with the pelvis first.
+const PARTS: [PartShape; 11] = [
+    // Pelvis, torso and head
+    part(0.18, 0.1, [0.0, -0.15, 0.0], None),
+    part(0.2, 0.35, [0.0, 0.25, 0.0], Some((0, [0.0, 0.0, 0.0]))),
+    part(0.15, 0.05, [0.0, 0.72, 0.0], Some((1, [0.0, 0.55, 0.0]))),
+    // Arms
+    part(0.07, 0.25, [-0.3, 0.3, 0.0], Some((1, [-0.3, 0.5, 0.0]))),
+    part(0.06, 0.25, [-0.3, -0.07, 0.0], Some((3, [-0.3, 0.12, 0.0]))),
+    part(0.07, 0.25, [0.3, 0.3, 0.0], Some((1, [0.3, 0.5, 0.0]))),
+    part(0.06, 0.25, [0.3, -0.07, 0.0], Some((5, [0.3, 0.12, 0.0]))),
+    // Legs
+    part(
+        0.09,
+        0.3,
+        [-0.12, -0.42, 0.0],
+        Some((0, [-0.12, -0.2, 0.0])),
+    ),
+    part(
+        0.08,
+        0.3,
+        [-0.12, -0.8, 0.0],
+        Some((7, [-0.12, -0.62, 0.0])),
+    ),
+    part(0.09, 0.3, [0.12, -0.42, 0.0], Some((0, [0.12, -0.2, 0.0]))),
+    part(0.08, 0.3, [0.12, -0.8, 0.0], Some((9, [0.12, -0.62, 0.0]))),
+];
+/// The total mass of a ragdoll, spread over its parts by their size.
+const RAGDOLL_MASS: Scalar = 70.0;
+/// How long a character takes to get back up out of its ragdoll, in seconds.
+const GET_UP_TIME: f32 = 0.4;
+
+/// Makes a character go limp as a jointed ragdoll when it dies, instead of staying upright.
+/// The character's own body is hidden and switched off meanwhile, following the ragdoll
+/// around. Once it's no longer [`Dead`], the ragdoll's parts are blended into its standing pose
+/// and its own body is switched back on in their place.
+#[derive(Component, Default)]
+pub struct RagdollOnDeath;
+
+/// Set on a character while it's ragdolled, with the ragdoll's pelvis for it to follow.
+#[derive(Component)]
+struct Ragdolled {
+    pelvis: Entity,
+}
+
+/// A body part or joint of the ragdoll standing in for `0`.
+#[derive(Component)]
+struct RagdollPart(Entity);
+
+/// Which of the [`PARTS`] a ragdoll's body part is.
+#[derive(Component)]
+struct PartIndex(usize);
+
+/// Set on a character getting back up out of its ragdoll, since `started_at` in elapsed
+/// seconds.
+#[derive(Component)]
+struct GettingUp {
+    started_at: f32,
+}
+
+/// Where a ragdoll's body part lay when its character started getting up, to blend from.
+#[derive(Component)]
+struct BlendFrom(Transform);
+
+pub struct RagdollPlugin;
+
+impl Plugin for RagdollPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, start_ragdolls).add_systems(
+            PostUpdate,
+            // After the characters' own systems have had their say on where they are
+            (
+                start_getting_up,
+                blend_to_standing,
+                end_ragdolls,
+                follow_ragdolls,
+                remove_orphaned_ragdolls,
+            )
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Swaps characters that just died for a ragdoll in the same pose and moving the same way.
+#[allow(clippy::type_complexity)]
+fn start_ragdolls(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut died: EventReader<Died>,
+    characters: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<RagdollOnDeath>, Without<Ragdolled>),
+    >,
+) {
+    for event in died.read() {
+        let Ok((transform, velocity, material)) = characters.get(event.entity) else {
+            continue;
+        };
+
+        let total_volume: Scalar = PARTS
+            .iter()
+            .map(|part| part.radius * part.radius * (part.length + part.radius))
+            .sum();
+        let mut parts: Vec<Entity> = Vec::with_capacity(PARTS.len());
+        for shape in &PARTS {
+            let volume = shape.radius * shape.radius * (shape.length + shape.radius);
+            let mut part = commands.spawn((
+                RagdollPart(event.entity),
+                PartIndex(parts.len()),
+                RigidBody::Dynamic,
+                Collider::capsule(shape.radius, shape.length),
+                // Parts don't collide with each other, so they don't fight the joints
+                CollisionLayers::new(GameLayer::Ragdoll, GameLayer::solid()),
+                Mass(RAGDOLL_MASS * volume / total_volume),
+                LinearVelocity(velocity.0),
+                Mesh3d(meshes.add(Capsule3d::new(shape.radius, shape.length))),
+                Transform::from_translation(transform.transform_point(shape.offset))
+                    .with_rotation(transform.rotation),
+            ));
+            if let Some(material) = material {
+                part.insert(material.clone());
+            }
+            let part = part.id();
+
+            if let Some((parent, joint)) = shape.parent {
+                commands.spawn((
+                    RagdollPart(event.entity),
+                    SphericalJoint::new(parts[parent], part)
+                        .with_local_anchor_1(joint - PARTS[parent].offset)
+                        .with_local_anchor_2(joint - shape.offset),
+                ));
+            }
+            parts.push(part);
+        }
+
+        commands.entity(event.entity).insert((
+            Ragdolled { pelvis: parts[0] },
+            TnuaToggle::Disabled,
+            RigidBodyDisabled,
+            ColliderDisabled,
+            Visibility::Hidden,
+        ));
+    }
+}
+
+/// Keeps ragdolled characters, and the cameras on them, with their ragdoll.
+fn follow_ragdolls(
+    parts: Query<&Position, With<RagdollPart>>,
+    mut characters: Query<(&Ragdolled, &mut Transform), With<Dead>>,
+) {
+    for (ragdolled, mut transform) in &mut characters {
+        if let Ok(pelvis) = parts.get(ragdolled.pelvis) {
+            transform.translation = pelvis.0 - PARTS[0].offset;
+        }
+    }
+}
+
+/// Starts characters that are no longer dead getting up, taking their ragdoll's joints away
+/// and switching its parts off for them to be blended into the standing pose.
+#[allow(clippy::type_complexity)]
+fn start_getting_up(
+    time: Res<Time>,
+    mut commands: Commands,
+    characters: Query<Entity, (With<Ragdolled>, Without<Dead>, Without<GettingUp>)>,
+    bodies: Query<(Entity, &RagdollPart, &Transform), With<PartIndex>>,
+    joints: Query<(Entity, &RagdollPart), Without<PartIndex>>,
+) {
+    for character in &characters {
+        commands.entity(character).insert(GettingUp {
+            started_at: time.elapsed_secs(),
+        });
+        for (entity, part, transform) in &bodies {
+            if part.0 == character {
+                commands.entity(entity).insert((
+                    BlendFrom(*transform),
+                    RigidBodyDisabled,
+                    ColliderDisabled,
+                ));
+            }
+        }
+        for (entity, part) in &joints {
+            if part.0 == character {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+/// Moves the parts of ragdolls whose characters are getting up from where they lay to where
+/// they'd be with the character standing where it respawned.
+fn blend_to_standing(
+    time: Res<Time>,
+    characters: Query<(&Transform, &GettingUp), Without<RagdollPart>>,
+    mut parts: Query<(&RagdollPart, &PartIndex, &BlendFrom, &mut Transform)>,
+) {
+    let now = time.elapsed_secs();
+    for (part, index, from, mut transform) in &mut parts {
+        let Ok((character, getting_up)) = characters.get(part.0) else {
+            continue;
+        };
+        let progress = ((now - getting_up.started_at) / GET_UP_TIME).clamp(0.0, 1.0);
+        let blend = progress * progress * (3.0 - 2.0 * progress);
+
+        let standing = character.transform_point(PARTS[index.0].offset);
+        transform.translation = from.0.translation.lerp(standing, blend);
+        transform.rotation = from.0.rotation.slerp(character.rotation, blend);
+    }
+}
+
+/// Takes the ragdolls of characters done getting up away, and gives them back their own body
+/// where they respawned.
+fn end_ragdolls(
+    time: Res<Time>,
+    mut commands: Commands,
+    characters: Query<(Entity, &GettingUp)>,
+    parts: Query<(Entity, &RagdollPart)>,
+) {
+    let now = time.elapsed_secs();
+    for (character, getting_up) in &characters {
+        if now - getting_up.started_at < GET_UP_TIME {
+            continue;
+        }
+        for (entity, part) in &parts {
+            if part.0 == character {
+                commands.entity(entity).despawn();
+            }
+        }
+        commands
+            .entity(character)
+            .remove::<(Ragdolled, GettingUp, RigidBodyDisabled, ColliderDisabled)>()
+            .insert((TnuaToggle::Enabled, Visibility::Inherited));
+    }
+}
+
+/// Takes away the ragdolls of characters that were despawned while ragdolled, like dead
+/// enemies.
+fn remove_orphaned_ragdolls(
+    mut commands: Commands,
+    characters: Query<(), With<RagdollOnDeath>>,
+    parts: Query<(Entity, &RagdollPart)>,
+) {
+    for (entity, part) in &parts {
+        if !characters.contains(part.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
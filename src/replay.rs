@@ -0,0 +1,391 @@
+use crate::ammo::PlayerReload;
+use crate::camera::{CameraRig, PlayerAim, PlayerToggleView};
+use crate::carry::PlayerInteract;
+use crate::grapple::PlayerGrapple;
+use crate::grenade::PlayerGrenade;
+use crate::inventory::PlayerInventory;
+use crate::melee::PlayerMelee;
+use crate::player_movement::{
+    Player, PlayerAction, PlayerAltAction, PlayerCrouch, PlayerDash, PlayerJump, PlayerLook,
+    PlayerMove, PlayerProne, PlayerSprint,
+};
+use crate::tools::{PlayerCycleTool, PlayerSelectTool};
+use avian3d::prelude::*;
+use bevy::input::InputSystem;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Where recordings are written to, and played back from.
+const REPLAY_PATH: &str = "replays/last.replay.ron";
+
+/// Starts recording the player's input as a macro, or stops and saves it.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleReplayRecording;
+
+/// Plays the last recorded macro back, or stops it.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct PlayReplay;
+
+/// An input macro: the player's input, frame by frame, from where they were when it started.
+/// Played back with the same frame lengths, on the world as it is at the time. Only the player is
+/// put back where they started, so anything else that moved, spawned or despawned since, and
+/// anything random, like loot drops, makes the playback play out differently.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub start: ReplayStart,
+    pub frames: Vec<ReplayFrame>,
+}
+
+/// Where the player was, and how they were moving and looking, when a recording started.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayStart {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub velocity: Vec3,
+    /// The rotation of the player's camera, for its pitch.
+    pub camera_rotation: Quat,
+}
+
+/// The input of one frame. Axes are the values of their actions, after modifiers.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayFrame {
+    /// How long the frame took, which is how far physics was stepped during it.
+    pub delta: Duration,
+    #[serde(default)]
+    pub movement: Vec2,
+    #[serde(default)]
+    pub look: Vec2,
+    #[serde(default)]
+    pub mouse_motion: Vec2,
+    #[serde(default)]
+    pub select_tool: f32,
+    #[serde(default)]
+    pub cycle_tool: f32,
+    /// The buttons held down.
+    #[serde(default)]
+    pub buttons: Vec<ReplayButton>,
+}
+
+/// A player action read as a button.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReplayButton {
+    Jump,
+    Sprint,
+    Crouch,
+    Prone,
+    Dash,
+    Reload,
+    Interact,
+    Melee,
+    Action,
+    AltAction,
+    Aim,
+    ToggleView,
+    Grapple,
+    Grenade,
+    Inventory,
+}
+
+impl ReplayButton {
+    const ALL: [Self; 15] = [
+        Self::Jump,
+        Self::Sprint,
+        Self::Crouch,
+        Self::Prone,
+        Self::Dash,
+        Self::Reload,
+        Self::Interact,
+        Self::Melee,
+        Self::Action,
+        Self::AltAction,
+        Self::Aim,
+        Self::ToggleView,
+        Self::Grapple,
+        Self::Grenade,
+        Self::Inventory,
+    ];
+
+    /// Reads whether the button is held, and mocks it held or released.
+    #[allow(clippy::type_complexity)]
+    fn hooks(self) -> (fn(&mut World) -> bool, fn(&mut World, bool, bool)) {
+        match self {
+            Self::Jump => (action_value::<PlayerJump>, mock_action::<PlayerJump>),
+            Self::Sprint => (action_value::<PlayerSprint>, mock_action::<PlayerSprint>),
+            Self::Crouch => (action_value::<PlayerCrouch>, mock_action::<PlayerCrouch>),
+            Self::Prone => (action_value::<PlayerProne>, mock_action::<PlayerProne>),
+            Self::Dash => (action_value::<PlayerDash>, mock_action::<PlayerDash>),
+            Self::Reload => (action_value::<PlayerReload>, mock_action::<PlayerReload>),
+            Self::Interact => (
+                action_value::<PlayerInteract>,
+                mock_action::<PlayerInteract>,
+            ),
+            Self::Melee => (action_value::<PlayerMelee>, mock_action::<PlayerMelee>),
+            Self::Action => (action_value::<PlayerAction>, mock_action::<PlayerAction>),
+            Self::AltAction => (
+                action_value::<PlayerAltAction>,
+                mock_action::<PlayerAltAction>,
+            ),
+            Self::Aim => (action_value::<PlayerAim>, mock_action::<PlayerAim>),
+            Self::ToggleView => (
+                action_value::<PlayerToggleView>,
+                mock_action::<PlayerToggleView>,
+            ),
+            Self::Grapple => (action_value::<PlayerGrapple>, mock_action::<PlayerGrapple>),
+            Self::Grenade => (action_value::<PlayerGrenade>, mock_action::<PlayerGrenade>),
+            Self::Inventory => (
+                action_value::<PlayerInventory>,
+                mock_action::<PlayerInventory>,
+            ),
+        }
+    }
+}
+
+/// Whether a recording is being made or played back.
+#[derive(Resource, Default)]
+enum ReplayState {
+    #[default]
+    Idle,
+    Recording(Replay),
+    Playing {
+        replay: Replay,
+        /// The next frame to play.
+        frame: usize,
+        /// The mouse motion of the frame being played, put in place of the real one.
+        mouse_motion: Vec2,
+    },
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayState>()
+            .add_systems(PreUpdate, replace_mouse_motion.after(InputSystem))
+            .add_systems(Update, (record_frame, play_frame))
+            .add_observer(toggle_recording)
+            .add_observer(play_replay);
+    }
+}
+
+fn toggle_recording(
+    _trigger: Trigger<Started<ToggleReplayRecording>>,
+    mut state: ResMut<ReplayState>,
+    player: Single<(&Transform, &LinearVelocity), With<Player>>,
+    camera: Single<&Transform, (With<CameraRig>, Without<Player>)>,
+) {
+    match &*state {
+        ReplayState::Idle => {
+            let (transform, velocity) = *player;
+            *state = ReplayState::Recording(Replay {
+                start: ReplayStart {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    velocity: velocity.0,
+                    camera_rotation: camera.rotation,
+                },
+                frames: Vec::new(),
+            });
+            info!("Recording an input macro");
+        }
+        ReplayState::Recording(replay) => {
+            replay.save(REPLAY_PATH);
+            info!(
+                "Saved an input macro of {} frames to {REPLAY_PATH}",
+                replay.frames.len()
+            );
+            *state = ReplayState::Idle;
+        }
+        ReplayState::Playing { .. } => {}
+    }
+}
+
+fn play_replay(
+    _trigger: Trigger<Started<PlayReplay>>,
+    mut commands: Commands,
+    mut state: ResMut<ReplayState>,
+) {
+    match &*state {
+        ReplayState::Idle => {
+            let Some(replay) = Replay::load(REPLAY_PATH) else {
+                return;
+            };
+            info!("Playing back the input macro in {REPLAY_PATH}");
+            *state = ReplayState::Playing {
+                replay,
+                frame: 0,
+                mouse_motion: Vec2::ZERO,
+            };
+        }
+        ReplayState::Playing { .. } => {
+            info!("Stopped playing back the input macro");
+            commands.insert_resource(TimeUpdateStrategy::Automatic);
+            *state = ReplayState::Idle;
+        }
+        ReplayState::Recording(_) => {}
+    }
+}
+
+impl Replay {
+    fn load(path: &str) -> Option<Self> {
+        let contents = fs::read_to_string(path)
+            .inspect_err(|error| warn!("Couldn't read a replay from {path}: {error}"))
+            .ok()?;
+        ron::from_str(&contents)
+            .inspect_err(|error| warn!("Couldn't parse a replay from {path}: {error}"))
+            .ok()
+    }
+
+    fn save(&self, path: &str) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize the replay: {error}");
+                return;
+            }
+        };
+
+        let result = match Path::new(path).parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|()| fs::write(path, contents)),
+            None => fs::write(path, contents),
+        };
+        if let Err(error) = result {
+            error!("Couldn't write the replay to {path}: {error}");
+        }
+    }
+}
+
+/// Reads the value of the first action of type `A`, so only the first local player is
+/// recorded.
+fn action_value<A: InputAction>(world: &mut World) -> A::Output
+where
+    A::Output: Default,
+{
+    world
+        .query::<&Action<A>>()
+        .iter(world)
+        .next()
+        .map_or_else(default, |action| **action)
+}
+
+/// Mocks every action of type `A` for the next input update, overriding what's really pressed.
+fn mock_action<A: InputAction>(world: &mut World, value: impl Into<ActionValue>, active: bool) {
+    let state = if active {
+        ActionState::Fired
+    } else {
+        ActionState::None
+    };
+    let value = value.into();
+    let actions: Vec<Entity> = world
+        .query_filtered::<Entity, With<Action<A>>>()
+        .iter(world)
+        .collect();
+    for action in actions {
+        world
+            .entity_mut(action)
+            .insert(ActionMock::once(state, value));
+    }
+}
+
+fn record_frame(world: &mut World) {
+    if !matches!(*world.resource::<ReplayState>(), ReplayState::Recording(_)) {
+        return;
+    }
+
+    let frame = ReplayFrame {
+        delta: world.resource::<Time<Real>>().delta(),
+        movement: action_value::<PlayerMove>(world),
+        look: action_value::<PlayerLook>(world),
+        mouse_motion: world.resource::<AccumulatedMouseMotion>().delta,
+        select_tool: action_value::<PlayerSelectTool>(world),
+        cycle_tool: action_value::<PlayerCycleTool>(world),
+        buttons: ReplayButton::ALL
+            .into_iter()
+            .filter(|button| (button.hooks().0)(world))
+            .collect(),
+    };
+    if let ReplayState::Recording(replay) = &mut *world.resource_mut::<ReplayState>() {
+        replay.frames.push(frame);
+    }
+}
+
+/// Puts the next frame's input in place of the real input, to be read at the start of the next
+/// frame along with its recorded length. Moves the player back to where the recording started
+/// first.
+fn play_frame(world: &mut World) {
+    let (index, start) = match world.resource::<ReplayState>() {
+        ReplayState::Playing { replay, frame, .. } => (
+            *frame,
+            (*frame == 0).then(|| {
+                let start = &replay.start;
+                (
+                    Transform::from_translation(start.translation).with_rotation(start.rotation),
+                    start.velocity,
+                    start.camera_rotation,
+                )
+            }),
+        ),
+        _ => return,
+    };
+
+    if let Some((start_transform, start_velocity, camera_rotation)) = start {
+        let mut players =
+            world.query_filtered::<(&mut Transform, &mut LinearVelocity), With<Player>>();
+        for (mut transform, mut velocity) in players.iter_mut(world) {
+            *transform = start_transform;
+            velocity.0 = start_velocity;
+        }
+        let mut cameras = world.query_filtered::<&mut Transform, With<CameraRig>>();
+        for mut transform in cameras.iter_mut(world) {
+            transform.rotation = camera_rotation;
+        }
+    }
+
+    let next = match &mut *world.resource_mut::<ReplayState>() {
+        ReplayState::Playing {
+            replay,
+            frame,
+            mouse_motion,
+        } => {
+            let next = replay.frames.get(index).cloned();
+            if let Some(next) = &next {
+                *frame += 1;
+                *mouse_motion = next.mouse_motion;
+            }
+            next
+        }
+        _ => None,
+    };
+    let Some(next) = next else {
+        info!("Finished playing back the input macro");
+        world.insert_resource(TimeUpdateStrategy::Automatic);
+        world.insert_resource(ReplayState::Idle);
+        return;
+    };
+
+    world.insert_resource(TimeUpdateStrategy::ManualDuration(next.delta));
+    mock_action::<PlayerMove>(world, next.movement, next.movement != Vec2::ZERO);
+    mock_action::<PlayerLook>(world, next.look, next.look != Vec2::ZERO);
+    mock_action::<PlayerSelectTool>(world, next.select_tool, next.select_tool != 0.0);
+    mock_action::<PlayerCycleTool>(world, next.cycle_tool, next.cycle_tool != 0.0);
+    for button in ReplayButton::ALL {
+        let held = next.buttons.contains(&button);
+        (button.hooks().1)(world, held, held);
+    }
+}
+
+fn replace_mouse_motion(
+    state: Res<ReplayState>,
+    mut accumulated_mouse_motion: ResMut<AccumulatedMouseMotion>,
+) {
+    if let ReplayState::Playing { mouse_motion, .. } = *state {
+        accumulated_mouse_motion.delta = mouse_motion;
+    }
+}
@@ -0,0 +1,286 @@
+use crate::layers::GameLayer;
+use avian3d::{math::*, prelude::*};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::view::NoFrustumCulling;
+use serde::Deserialize;
+
+/// How many points smooth ropes are drawn through between each pair of segment ends.
+const SMOOTH_SUBDIVISIONS: usize = 4;
+/// How many sides the tube of a smooth rope has.
+const SMOOTH_SIDES: usize = 6;
+/// The mass of each segment, per meter of rope.
+const ROPE_MASS_PER_METER: Scalar = 0.5;
+
+/// How a rope is drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum RopeLook {
+    /// A smooth tube curving through the segments, rebuilt every frame.
+    #[default]
+    Smooth,
+    /// Each segment as its own capsule, like the links of a chain.
+    Capsules,
+}
+
+/// What one end of a rope is tied to.
+#[derive(Clone, Copy, Debug)]
+pub enum RopeAnchor {
+    /// The point in the world the end starts at.
+    Fixed,
+    /// A point on a rigid body, relative to the body.
+    Body(Entity, Vector),
+}
+
+/// Where one end of a rope starts, and what it's tied to there.
+#[derive(Clone, Copy, Debug)]
+pub struct RopeEnd {
+    pub point: Vector,
+    pub anchor: RopeAnchor,
+}
+
+impl RopeEnd {
+    pub fn fixed(point: Vector) -> Self {
+        Self {
+            point,
+            anchor: RopeAnchor::Fixed,
+        }
+    }
+
+    /// An end tied to `body` at `offset` from its center, which is at `point` in the world.
+    pub fn body(point: Vector, body: Entity, offset: Vector) -> Self {
+        Self {
+            point,
+            anchor: RopeAnchor::Body(body, offset),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RopeSettings {
+    pub segments: u32,
+    pub radius: Scalar,
+    pub look: RopeLook,
+    pub color: Color,
+}
+
+impl Default for RopeSettings {
+    fn default() -> Self {
+        Self {
+            segments: 12,
+            radius: 0.04,
+            look: RopeLook::default(),
+            color: Color::srgb(0.55, 0.45, 0.3),
+        }
+    }
+}
+
+/// A rope or chain of capsule segments joined end to end, spawned with [`spawn_rope`]. Its
+/// segments, anchors and joints are its children, so despawning the rope despawns all of it.
+#[derive(Component)]
+pub struct Rope {
+    segments: Vec<Entity>,
+    segment_length: Scalar,
+    radius: Scalar,
+    look: RopeLook,
+}
+
+/// Spawns a rope from `start` to `end`, tied to the world or to the bodies there.
+/// The segments start out in a straight line between the two points, so the rope starts out
+/// taut and bodies it's tied to should already be in place.
+pub fn spawn_rope(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    start: RopeEnd,
+    end: RopeEnd,
+    settings: &RopeSettings,
+) -> Entity {
+    let (from, to) = (start.point, end.point);
+    let count = settings.segments.max(1);
+    let length = from.distance(to) / count as Scalar;
+    let direction = Dir3::new(to - from).unwrap_or(Dir3::NEG_Y);
+    // Segments are capsules along their Y axis, turned to point from start to end
+    let rotation = Quat::from_rotation_arc(Vec3::Y, *direction);
+    let half = Vector::Y * length / 2.0;
+    let material = materials.add(settings.color);
+
+    let mut rope = commands.spawn((Transform::default(), Visibility::default()));
+    let root = rope.id();
+    if settings.look == RopeLook::Smooth {
+        rope.insert((
+            Mesh3d(meshes.add(Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::default(),
+            ))),
+            MeshMaterial3d(material.clone()),
+            // The mesh changes every frame, but its bounds are only worked out once
+            NoFrustumCulling,
+        ));
+    }
+
+    let segment_mesh = meshes.add(Capsule3d::new(settings.radius, length));
+    let mut segments = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let center = from + direction * length * (index as Scalar + 0.5);
+        let mut segment = commands.spawn((
+            RigidBody::Dynamic,
+            Collider::capsule(settings.radius, length),
+            // Segments don't collide with each other, so they don't fight the joints
+            CollisionLayers::new(GameLayer::Rope, GameLayer::solid()),
+            Mass(ROPE_MASS_PER_METER * length),
+            Transform::from_translation(center).with_rotation(rotation),
+            ChildOf(root),
+        ));
+        if settings.look == RopeLook::Capsules {
+            segment.insert((
+                Mesh3d(segment_mesh.clone()),
+                MeshMaterial3d(material.clone()),
+            ));
+        }
+        let segment = segment.id();
+
+        if let Some(&previous) = segments.last() {
+            commands.spawn((
+                SphericalJoint::new(previous, segment)
+                    .with_local_anchor_1(half)
+                    .with_local_anchor_2(-half),
+                ChildOf(root),
+            ));
+        }
+        segments.push(segment);
+    }
+
+    for (end, segment, segment_anchor) in [
+        (start, segments[0], -half),
+        (end, segments[segments.len() - 1], half),
+    ] {
+        let (body, body_anchor) = match end.anchor {
+            RopeAnchor::Fixed => {
+                let body = commands
+                    .spawn((
+                        RigidBody::Static,
+                        Transform::from_translation(end.point),
+                        ChildOf(root),
+                    ))
+                    .id();
+                (body, Vector::ZERO)
+            }
+            RopeAnchor::Body(body, offset) => (body, offset),
+        };
+        commands.spawn((
+            SphericalJoint::new(body, segment)
+                .with_local_anchor_1(body_anchor)
+                .with_local_anchor_2(segment_anchor),
+            ChildOf(root),
+        ));
+    }
+
+    commands.entity(root).insert(Rope {
+        segments,
+        segment_length: length,
+        radius: settings.radius,
+        look: settings.look,
+    });
+    root
+}
+
+pub struct RopePlugin;
+
+impl Plugin for RopePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, draw_smooth_ropes);
+    }
+}
+
+/// Rebuilds the tubes of smooth ropes along where their segments are now.
+fn draw_smooth_ropes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    ropes: Query<(&Rope, &Mesh3d)>,
+    segments: Query<(&Position, &Rotation)>,
+) {
+    for (rope, mesh3d) in &ropes {
+        if rope.look != RopeLook::Smooth {
+            continue;
+        }
+
+        // The ends of every segment, which line up with the next segment's start
+        let mut points = Vec::with_capacity(rope.segments.len() + 1);
+        for (index, &segment) in rope.segments.iter().enumerate() {
+            let Ok((position, rotation)) = segments.get(segment) else {
+                continue;
+            };
+            let half = rotation.0 * Vector::Y * rope.segment_length / 2.0;
+            if index == 0 {
+                points.push(position.0 - half);
+            }
+            points.push(position.0 + half);
+        }
+        if points.len() < 2 {
+            continue;
+        }
+
+        meshes.insert(&mesh3d.0, tube(&smooth(&points), rope.radius));
+    }
+}
+
+/// Points along a Catmull-Rom spline through `points`, [`SMOOTH_SUBDIVISIONS`] per span.
+fn smooth(points: &[Vec3]) -> Vec<Vec3> {
+    let last = points.len() - 1;
+    let mut smoothed = Vec::with_capacity(last * SMOOTH_SUBDIVISIONS + 1);
+    for span in 0..last {
+        let p0 = points[span.saturating_sub(1)];
+        let p1 = points[span];
+        let p2 = points[span + 1];
+        let p3 = points[(span + 2).min(last)];
+        for step in 0..SMOOTH_SUBDIVISIONS {
+            let t = step as f32 / SMOOTH_SUBDIVISIONS as f32;
+            smoothed.push(
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t),
+            );
+        }
+    }
+    smoothed.push(points[last]);
+    smoothed
+}
+
+/// A tube of the given radius along `points`, open at the ends.
+fn tube(points: &[Vec3], radius: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(points.len() * SMOOTH_SIDES);
+    let mut normals = Vec::with_capacity(points.len() * SMOOTH_SIDES);
+    for (index, &point) in points.iter().enumerate() {
+        let previous = points[index.saturating_sub(1)];
+        let next = points[(index + 1).min(points.len() - 1)];
+        let tangent = (next - previous).normalize_or(Vec3::Y);
+        let (normal, binormal) = tangent.any_orthonormal_pair();
+        for side in 0..SMOOTH_SIDES {
+            let angle = side as f32 / SMOOTH_SIDES as f32 * std::f32::consts::TAU;
+            let out = normal * angle.cos() + binormal * angle.sin();
+            positions.push(point + out * radius);
+            normals.push(out);
+        }
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 1) * SMOOTH_SIDES * 6);
+    for ring in 0..points.len() as u32 - 1 {
+        for side in 0..SMOOTH_SIDES as u32 {
+            let next_side = (side + 1) % SMOOTH_SIDES as u32;
+            let a = ring * SMOOTH_SIDES as u32 + side;
+            let b = ring * SMOOTH_SIDES as u32 + next_side;
+            let c = a + SMOOTH_SIDES as u32;
+            let d = b + SMOOTH_SIDES as u32;
+            indices.extend([a, c, b, b, c, d]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_indices(Indices::U32(indices))
+}
@@ -0,0 +1,88 @@
+use crate::combat::{Damage, Died};
+use crate::main_menu::GameState;
+use crate::player_movement::Player;
+use bevy::prelude::*;
+
+/// Points for hurting something with a throw, melee swing or explosion.
+const HIT_POINTS: u32 = 10;
+/// Points for finishing something off, on top of the hit that did it.
+const KILL_POINTS: u32 = 50;
+
+/// The player's points this game, reset when a new game starts.
+#[derive(Resource, Default)]
+pub struct Score(pub u32);
+
+/// What some points were awarded for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScoreReason {
+    Hit,
+    Kill,
+    Collectible,
+    /// Clearing a wave of a survival run.
+    Wave,
+    /// Completing a [`Quest`](crate::quest::Quest).
+    Quest,
+}
+
+/// Sent to award points. Anything handing out points sends these rather than touching the
+/// [`Score`] itself, so the HUD and game modes see every award.
+#[derive(Event)]
+pub struct AddScore {
+    pub points: u32,
+    pub reason: ScoreReason,
+}
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_event::<AddScore>()
+            .add_systems(OnEnter(GameState::InGame), reset_score)
+            .add_systems(Update, (score_hits, score_kills, add_score).chain());
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    score.0 = 0;
+}
+
+/// Awards points for damage a player dealt to anything but a player.
+fn score_hits(
+    mut damage: EventReader<Damage>,
+    mut add_score: EventWriter<AddScore>,
+    players: Query<(), With<Player>>,
+) {
+    for event in damage.read() {
+        let by_player = event.source.is_some_and(|source| players.contains(source));
+        if by_player && !players.contains(event.target) {
+            add_score.write(AddScore {
+                points: HIT_POINTS,
+                reason: ScoreReason::Hit,
+            });
+        }
+    }
+}
+
+/// Awards points for anything but a player that a player finished off.
+fn score_kills(
+    mut died: EventReader<Died>,
+    mut add_score: EventWriter<AddScore>,
+    players: Query<(), With<Player>>,
+) {
+    for event in died.read() {
+        let by_player = event.source.is_some_and(|source| players.contains(source));
+        if by_player && !players.contains(event.entity) {
+            add_score.write(AddScore {
+                points: KILL_POINTS,
+                reason: ScoreReason::Kill,
+            });
+        }
+    }
+}
+
+fn add_score(mut add_score: EventReader<AddScore>, mut score: ResMut<Score>) {
+    for event in add_score.read() {
+        score.0 += event.points;
+    }
+}
@@ -0,0 +1,97 @@
+use crate::score::{AddScore, Score, ScoreReason};
+use bevy::prelude::*;
+
+/// How far the score sits from the top of the screen, in pixels.
+const SCORE_MARGIN: f32 = 24.0;
+/// How long the points of the latest award stay up, fading out, in seconds.
+const AWARD_FADE_TIME: f32 = 1.0;
+
+#[derive(Component)]
+struct ScoreText;
+
+/// The points awarded lately, adding up while awards keep coming in before it fades.
+#[derive(Component, Default)]
+struct AwardText {
+    points: u32,
+    shown_at: f32,
+}
+
+pub struct ScoreHudPlugin;
+
+impl Plugin for ScoreHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_score)
+            .add_systems(Update, (update_score, show_awards, fade_awards));
+    }
+}
+
+fn spawn_score(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(SCORE_MARGIN),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![
+            (
+                ScoreText,
+                Text::new("0"),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+            ),
+            (
+                AwardText::default(),
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 0.85, 0.3, 0.0)),
+            ),
+        ],
+    ));
+}
+
+fn update_score(score: Res<Score>, mut texts: Query<&mut Text, With<ScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+    for mut text in &mut texts {
+        text.0 = score.0.to_string();
+    }
+}
+
+fn show_awards(
+    time: Res<Time>,
+    mut add_score: EventReader<AddScore>,
+    mut awards: Query<(&mut AwardText, &mut Text)>,
+) {
+    let now = time.elapsed_secs();
+    for event in add_score.read() {
+        for (mut award, mut text) in &mut awards {
+            if now - award.shown_at > AWARD_FADE_TIME {
+                award.points = 0;
+            }
+            award.points += event.points;
+            award.shown_at = now;
+            text.0 = match event.reason {
+                ScoreReason::Kill => format!("Kill! +{}", award.points),
+                _ => format!("+{}", award.points),
+            };
+        }
+    }
+}
+
+fn fade_awards(time: Res<Time>, mut awards: Query<(&AwardText, &mut TextColor)>) {
+    let now = time.elapsed_secs();
+    for (award, mut color) in &mut awards {
+        let alpha = (1.0 - (now - award.shown_at) / AWARD_FADE_TIME).clamp(0.0, 1.0);
+        color.0.set_alpha(alpha);
+    }
+}
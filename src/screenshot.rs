@@ -0,0 +1,188 @@
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk};
+use bevy_enhanced_input::prelude::*;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where screenshots are saved, named after when they were taken.
+const SCREENSHOT_DIR: &str = "screenshots";
+/// How long the message saying where a screenshot was saved stays up, fading out, in seconds.
+const TOAST_TIME: f32 = 2.0;
+/// How far the message sits from the bottom of the screen, above the hotbar, in pixels.
+const TOAST_MARGIN: f32 = 120.0;
+
+/// Saves the next frame to a PNG, without the HUD unless the settings say to keep it.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct TakeScreenshot;
+
+/// Where a screenshot being taken is saved.
+#[derive(Component)]
+struct ScreenshotPath(String);
+
+/// UI hidden while a screenshot is taken, with its visibility from before to restore after.
+#[derive(Component)]
+struct HiddenForScreenshot(Visibility);
+
+#[derive(Component)]
+struct ScreenshotToast {
+    shown_at: f32,
+}
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, fade_toasts)
+            .add_observer(take_screenshot);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn take_screenshot(
+    _trigger: Trigger<Started<TakeScreenshot>>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    // Left alone while still hidden for the last screenshot, so its visibility from before
+    // isn't lost
+    ui_roots: Query<
+        (Entity, &Visibility),
+        (With<Node>, Without<ChildOf>, Without<HiddenForScreenshot>),
+    >,
+) {
+    if let Err(error) = fs::create_dir_all(SCREENSHOT_DIR) {
+        error!("Couldn't create {SCREENSHOT_DIR}: {error}");
+        return;
+    }
+    let path = format!("{SCREENSHOT_DIR}/screenshot-{}.png", timestamp());
+
+    if !settings.video.screenshot_hud {
+        for (entity, visibility) in &ui_roots {
+            commands
+                .entity(entity)
+                .insert((HiddenForScreenshot(*visibility), Visibility::Hidden));
+        }
+    }
+
+    commands
+        .spawn((Screenshot::primary_window(), ScreenshotPath(path.clone())))
+        .observe(save_to_disk(path))
+        .observe(finish_screenshot);
+}
+
+/// Shows the hidden UI again and says where the screenshot was saved.
+fn finish_screenshot(
+    trigger: Trigger<ScreenshotCaptured>,
+    time: Res<Time>,
+    mut commands: Commands,
+    screenshots: Query<&ScreenshotPath>,
+    hidden: Query<(Entity, &HiddenForScreenshot)>,
+    toasts: Query<Entity, With<ScreenshotToast>>,
+) {
+    for (entity, hidden) in &hidden {
+        commands
+            .entity(entity)
+            .remove::<HiddenForScreenshot>()
+            .insert(hidden.0);
+    }
+    let Ok(path) = screenshots.get(trigger.target()) else {
+        return;
+    };
+
+    for toast in &toasts {
+        commands.entity(toast).despawn();
+    }
+    commands.spawn((
+        ScreenshotToast {
+            shown_at: time.elapsed_secs(),
+        },
+        Text::new(format!("Saved {}", path.0)),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(TOAST_MARGIN),
+            width: Val::Percent(100.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+}
+
+fn fade_toasts(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut toasts: Query<(Entity, &ScreenshotToast, &mut TextColor)>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, toast, mut color) in &mut toasts {
+        let progress = (now - toast.shown_at) / TOAST_TIME;
+        if progress >= 1.0 {
+            commands.entity(entity).despawn();
+        } else {
+            color.0.set_alpha(1.0 - progress * progress);
+        }
+    }
+}
+
+/// The current UTC time as `YYYY-MM-DD_HH-MM-SS-mmm`, for file names that sort by date.
+fn timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format_timestamp(since_epoch)
+}
+
+fn format_timestamp(since_epoch: Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let (hour, minute, second) = (secs / 3600 % 24, secs / 60 % 60, secs % 60);
+
+    // Days since the epoch to a civil date, from Howard Hinnant's `civil_from_days`
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}_{hour:02}-{minute:02}-{second:02}-{:03}",
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn epoch_is_the_first_of_january_1970() {
+        assert_eq!(format_timestamp(Duration::ZERO), "1970-01-01_00-00-00-000");
+    }
+
+    #[test]
+    fn formats_dates_and_times_of_day() {
+        let time = Duration::from_secs(19_723 * DAY + 3_723) + Duration::from_millis(456);
+        assert_eq!(format_timestamp(time), "2024-01-01_01-02-03-456");
+    }
+
+    #[test]
+    fn counts_leap_days() {
+        let time = Duration::from_secs(19_782 * DAY);
+        assert_eq!(format_timestamp(time), "2024-02-29_00-00-00-000");
+    }
+}
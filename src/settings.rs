@@ -0,0 +1,255 @@
+use crate::camera::CameraSettings;
+use crate::crosshair::{CrosshairSettings, CrosshairStyle};
+use crate::key_bindings::{MoveStickSettings, ResponseCurve};
+use crate::launch::LaunchOptions;
+use crate::player_movement::{PlayerInputSettings, SprintMode};
+use crate::profile::Profile;
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, VideoModeSelection, WindowMode};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The settings file's name, in the profile's folder.
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// The player's preferences, read from the current [`Profile`]'s settings file at startup and
+/// written back to it whenever they change. Changing them here is what changes the resources
+/// they're applied to, like [`PlayerInputSettings`], so the changes last across restarts.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub video: VideoSettings,
+    pub audio: AudioSettings,
+    pub input: InputSettings,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub window_mode: WindowModeSetting,
+    pub vsync: bool,
+    pub head_bob: bool,
+    pub camera_shake: bool,
+    pub crosshair: CrosshairStyle,
+    /// Whether screenshots keep the HUD and other UI in them.
+    pub screenshot_hud: bool,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::BorderlessFullscreen,
+            vsync: true,
+            head_bob: true,
+            camera_shake: true,
+            crosshair: CrosshairStyle::default(),
+            screenshot_hud: true,
+        }
+    }
+}
+
+/// How the window is shown, always on the primary monitor when fullscreen.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowModeSetting {
+    Windowed,
+    BorderlessFullscreen,
+    /// Exclusive fullscreen, at the monitor's current video mode.
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Windowed => Self::BorderlessFullscreen,
+            Self::BorderlessFullscreen => Self::Fullscreen,
+            Self::Fullscreen => Self::Windowed,
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::BorderlessFullscreen => {
+                WindowMode::BorderlessFullscreen(MonitorSelection::Primary)
+            }
+            Self::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Primary, VideoModeSelection::Current)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Scales the volume of every sound, from 0 to 1.
+    pub master_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { master_volume: 1.0 }
+    }
+}
+
+/// The persisted part of [`PlayerInputSettings`] and [`MoveStickSettings`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct InputSettings {
+    pub sprint_mode: SprintMode,
+    /// Radians turned per pixel of mouse movement, horizontally and vertically.
+    pub mouse_sensitivity: Vec2,
+    /// Radians per second turned with the look stick fully tilted.
+    pub gamepad_look_sensitivity: Vec2,
+    pub invert_y: bool,
+    pub rumble: bool,
+    pub rumble_intensity: f32,
+    /// The power the movement stick's tilt is raised to, 1 for a linear response.
+    pub move_stick_exponent: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        let input = PlayerInputSettings::default();
+        Self {
+            sprint_mode: input.sprint_mode,
+            mouse_sensitivity: input.mouse_sensitivity,
+            gamepad_look_sensitivity: input.gamepad_look_sensitivity,
+            invert_y: input.invert_y,
+            rumble: input.rumble,
+            rumble_intensity: input.rumble_intensity,
+            move_stick_exponent: MoveStickSettings::default().response_curve.exponent(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings from the profile folder `dir`, falling back to the defaults if
+    /// they're missing or can't be parsed.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(SETTINGS_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("Couldn't read settings from {}: {error}", path.display());
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Couldn't parse settings from {}: {error}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize settings: {error}");
+                return;
+            }
+        };
+
+        let path = dir.join(SETTINGS_FILE);
+        if let Err(error) = fs::create_dir_all(dir).and_then(|()| fs::write(&path, contents)) {
+            error!("Couldn't write settings to {}: {error}", path.display());
+        }
+    }
+}
+
+/// The game's folder in the platform's per-user config folder: under `%APPDATA%` on Windows,
+/// `~/Library/Application Support` on macOS, and `$XDG_CONFIG_HOME` or `~/.config` elsewhere.
+/// Falls back to the working directory if the platform's folder can't be found.
+pub fn config_dir() -> PathBuf {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    };
+    base.map_or_else(PathBuf::new, |base| base.join("playground"))
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // A window mode given on the command line wins over the saved one, though it's only
+        // saved itself if the settings are changed later in the run
+        let mut settings = Settings::load(&app.world().resource::<Profile>().dir());
+        let launch_options = app.world().get_resource::<LaunchOptions>();
+        if let Some(window_mode) = launch_options.and_then(|options| options.window_mode) {
+            settings.video.window_mode = window_mode;
+        }
+        app.insert_resource(settings).add_systems(
+            Update,
+            (
+                load_profile_settings
+                    .run_if(resource_changed::<Profile>.and(not(resource_added::<Profile>))),
+                apply_settings.run_if(resource_changed::<Settings>),
+                // Only what changed after loading needs writing back
+                save_settings
+                    .run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Swaps in the settings of the profile switched to.
+fn load_profile_settings(mut commands: Commands, profile: Res<Profile>) {
+    commands.insert_resource(Settings::load(&profile.dir()));
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut window: Single<&mut Window>,
+    // Missing when running headless, without audio
+    global_volume: Option<ResMut<GlobalVolume>>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut crosshair_settings: ResMut<CrosshairSettings>,
+    mut input_settings: ResMut<PlayerInputSettings>,
+    mut move_stick: ResMut<MoveStickSettings>,
+) {
+    let Settings {
+        video,
+        audio,
+        input,
+    } = &*settings;
+
+    window.mode = video.window_mode.window_mode();
+    window.present_mode = if video.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    camera_settings.head_bob = video.head_bob;
+    camera_settings.camera_shake = video.camera_shake;
+    crosshair_settings.style = video.crosshair;
+
+    if let Some(mut global_volume) = global_volume {
+        global_volume.volume = Volume::Linear(audio.master_volume.clamp(0.0, 1.0));
+    }
+
+    input_settings.sprint_mode = input.sprint_mode;
+    input_settings.mouse_sensitivity = input.mouse_sensitivity;
+    input_settings.gamepad_look_sensitivity = input.gamepad_look_sensitivity;
+    input_settings.invert_y = input.invert_y;
+    input_settings.rumble = input.rumble;
+    input_settings.rumble_intensity = input.rumble_intensity;
+    move_stick.response_curve = ResponseCurve::Exponential(input.move_stick_exponent);
+}
+
+fn save_settings(settings: Res<Settings>, profile: Res<Profile>) {
+    settings.save(&profile.dir());
+}
@@ -0,0 +1,88 @@
+use crate::player_movement::Player;
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use serde::Deserialize;
+
+/// What can spawn at a [`SpawnPoint`].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Deserialize)]
+pub enum SpawnKind {
+    #[default]
+    Player,
+    Enemy,
+}
+
+/// A place characters of a [`SpawnKind`] spawn, facing the way the point faces.
+#[derive(Component, Clone, Copy, Default)]
+#[require(Transform)]
+pub struct SpawnPoint {
+    pub kind: SpawnKind,
+    /// The team allowed to spawn here, or `None` for every team.
+    pub team: Option<u8>,
+}
+
+/// Picks spawn points for characters.
+#[derive(SystemParam)]
+pub struct SpawnPoints<'w, 's> {
+    points: Query<'w, 's, (&'static SpawnPoint, &'static GlobalTransform)>,
+}
+
+impl SpawnPoints<'_, '_> {
+    /// Where a character of `kind` on `team` should spawn, picked at random among the
+    /// matching spawn points. Characters without a team can use any point of their kind.
+    pub fn pick(&self, kind: SpawnKind, team: Option<u8>) -> Option<Transform> {
+        let candidates: Vec<_> = self
+            .points
+            .iter()
+            .filter(|(point, _)| {
+                point.kind == kind && (point.team.is_none() || team.is_none() || point.team == team)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let (_, transform) = candidates[rand::random_range(0..candidates.len())];
+        Some(transform.compute_transform())
+    }
+}
+
+pub struct SpawnPointPlugin;
+
+impl Plugin for SpawnPointPlugin {
+    fn build(&self, app: &mut App) {
+        // After transform propagation, so newly spawned points are already in place
+        app.add_systems(
+            PostUpdate,
+            spawn_player.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Moves the players to a player spawn point whenever new ones appear, such as when a level is
+/// spawned, and players joining later to one of the points already there.
+fn spawn_player(
+    added: Query<&SpawnPoint, Added<SpawnPoint>>,
+    spawn_points: SpawnPoints,
+    mut players: Query<(Ref<Player>, &mut Transform, &mut LinearVelocity)>,
+) {
+    let new_points = added.iter().any(|point| point.kind == SpawnKind::Player);
+    if !new_points && !players.iter().any(|(player, ..)| player.is_added()) {
+        return;
+    }
+    let Some(spawn) = spawn_points.pick(SpawnKind::Player, None) else {
+        return;
+    };
+
+    for (player, mut transform, mut velocity) in &mut players {
+        if !new_points && !player.is_added() {
+            continue;
+        }
+        transform.translation = spawn.translation;
+        // Only turn the body around its vertical axis, like looking around does
+        let (yaw, _, _) = spawn.rotation.to_euler(EulerRot::YXZ);
+        transform.rotation = Quat::from_rotation_y(yaw);
+        velocity.0 = Vec3::ZERO;
+    }
+}
@@ -0,0 +1,209 @@
+use crate::blueprint::{LoadBlueprint, SaveBlueprint};
+use crate::camera::CameraRig;
+use crate::crosshair::CycleCrosshair;
+use crate::cursor::cursor_grabbed;
+use crate::day_night::ShiftTimeOfDay;
+use crate::diagnostics_overlay::ToggleDiagnostics;
+use crate::key_bindings::StartRebind;
+use crate::level::NextLevel;
+use crate::player_movement::{Player, PlayerInputSettings};
+use crate::replay::{PlayReplay, ToggleReplayRecording};
+use crate::screenshot::TakeScreenshot;
+use crate::terrain::RegenerateTerrain;
+use crate::weather::CycleWeather;
+use avian3d::prelude::*;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use bevy_tnua::TnuaToggle;
+use std::f32::consts::FRAC_PI_2;
+
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+const FLY_SPEED: f32 = 10.0;
+const MIN_FLY_SPEED: f32 = 1.0;
+const MAX_FLY_SPEED: f32 = 100.0;
+/// How much each mouse wheel step scales the fly speed by.
+const FLY_SPEED_STEP: f32 = 1.2;
+
+/// Debug controls that are always available, regardless of what is being controlled.
+#[derive(Component)]
+pub struct DebugControls;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleSpectator;
+
+/// A free-flying debug camera that ignores collisions. The player is frozen while it exists.
+#[derive(Component)]
+pub struct FlyCamera {
+    speed: f32,
+    /// The player's Tnua toggle from before it was frozen, to restore afterwards.
+    player_toggle: TnuaToggle,
+}
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct FlyMove;
+
+/// Moving straight up or down.
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct FlyLift;
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct FlySpeed;
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_input_context::<DebugControls>()
+            .add_input_context::<FlyCamera>()
+            .add_systems(Startup, spawn_debug_controls)
+            .add_systems(
+                Update,
+                (rotate_fly_camera.run_if(cursor_grabbed), fly).chain(),
+            )
+            .add_observer(toggle_spectator)
+            .add_observer(change_fly_speed);
+    }
+}
+
+fn spawn_debug_controls(mut commands: Commands) {
+    // `actions!` takes at most 12 actions, so the blueprint keys are added after the fact.
+    commands
+        .spawn((
+            DebugControls,
+            actions!(DebugControls[
+                (Action::<ToggleSpectator>::new(), bindings![KeyCode::F1]),
+                (Action::<StartRebind>::new(), bindings![KeyCode::F2]),
+                (Action::<RegenerateTerrain>::new(), bindings![KeyCode::F3]),
+                (Action::<NextLevel>::new(), bindings![KeyCode::F4]),
+                (
+                    Action::<ShiftTimeOfDay>::new(),
+                    bindings![(KeyCode::F5, Negate::all()), KeyCode::F6],
+                ),
+                (Action::<CycleWeather>::new(), bindings![KeyCode::F7]),
+                (Action::<CycleCrosshair>::new(), bindings![KeyCode::F8]),
+                (Action::<ToggleDiagnostics>::new(), bindings![KeyCode::F9]),
+                (Action::<ToggleReplayRecording>::new(), bindings![KeyCode::F10]),
+                (Action::<PlayReplay>::new(), bindings![KeyCode::F11]),
+                (Action::<TakeScreenshot>::new(), bindings![KeyCode::F12]),
+            ]),
+        ))
+        .with_related::<ActionOf<DebugControls>>((
+            Action::<SaveBlueprint>::new(),
+            bindings![KeyCode::KeyK],
+        ))
+        .with_related::<ActionOf<DebugControls>>((
+            Action::<LoadBlueprint>::new(),
+            bindings![KeyCode::KeyL],
+        ));
+}
+
+/// Detaches a fly camera where the player camera is, freezing the player in place, or
+/// despawns it and hands control back to the player.
+fn toggle_spectator(
+    _trigger: Trigger<Started<ToggleSpectator>>,
+    mut commands: Commands,
+    fly_cameras: Query<(Entity, &FlyCamera)>,
+    mut player_cameras: Query<(&mut Camera, &GlobalTransform), With<CameraRig>>,
+    player: Single<(Entity, Option<&TnuaToggle>), With<Player>>,
+) {
+    let (player, player_toggle) = *player;
+
+    if let Ok((fly_camera, fly)) = fly_cameras.single() {
+        commands.entity(fly_camera).despawn();
+        commands
+            .entity(player)
+            .remove::<RigidBodyDisabled>()
+            .insert(fly.player_toggle);
+        for (mut camera, _) in &mut player_cameras {
+            camera.is_active = true;
+        }
+        return;
+    }
+
+    let Ok((mut camera, camera_transform)) = player_cameras.single_mut() else {
+        return;
+    };
+    camera.is_active = false;
+
+    commands
+        .entity(player)
+        .insert((RigidBodyDisabled, TnuaToggle::Disabled));
+    commands.spawn((
+        FlyCamera {
+            speed: FLY_SPEED,
+            player_toggle: player_toggle.copied().unwrap_or_default(),
+        },
+        Camera3d::default(),
+        camera_transform.compute_transform(),
+        actions!(FlyCamera[
+            (
+                Action::<FlyMove>::new(),
+                DeadZone::default(),
+                Bindings::spawn((Cardinal::wasd_keys(), Axial::left_stick())),
+            ),
+            (
+                Action::<FlyLift>::new(),
+                Bindings::spawn(Bidirectional {
+                    positive: Binding::from(KeyCode::Space),
+                    negative: Binding::from(KeyCode::ControlLeft),
+                }),
+            ),
+            (
+                Action::<FlySpeed>::new(),
+                bindings![Binding::mouse_wheel()],
+            ),
+        ]),
+    ));
+}
+
+fn change_fly_speed(trigger: Trigger<Fired<FlySpeed>>, mut query: Query<&mut FlyCamera>) {
+    let Ok(mut fly) = query.single_mut() else {
+        return;
+    };
+
+    fly.speed = (fly.speed * FLY_SPEED_STEP.powf(trigger.value.y.signum()))
+        .clamp(MIN_FLY_SPEED, MAX_FLY_SPEED);
+}
+
+fn rotate_fly_camera(
+    accumulated_mouse_motion: Res<AccumulatedMouseMotion>,
+    settings: Res<PlayerInputSettings>,
+    mut query: Query<&mut Transform, With<FlyCamera>>,
+) {
+    let delta = settings.mouse_look(accumulated_mouse_motion.delta);
+    if delta == Vec2::ZERO {
+        return;
+    }
+
+    for mut transform in &mut query {
+        let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+        let yaw = yaw + delta.x;
+        let pitch = (pitch + delta.y).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+    }
+}
+
+/// Flies along the camera's view, with lift moving straight up and down.
+fn fly(
+    time: Res<Time>,
+    move_action: Query<&Action<FlyMove>>,
+    lift_action: Query<&Action<FlyLift>>,
+    mut query: Query<(&FlyCamera, &mut Transform)>,
+) {
+    let movement = move_action
+        .iter()
+        .next()
+        .map_or(Vec2::ZERO, |action| **action);
+    let lift = lift_action.iter().next().map_or(0.0, |action| **action);
+
+    for (fly, mut transform) in &mut query {
+        let direction =
+            transform.forward() * movement.y + transform.right() * movement.x + Vec3::Y * lift;
+        transform.translation += direction * fly.speed * time.delta_secs();
+    }
+}
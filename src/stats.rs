@@ -0,0 +1,132 @@
+use crate::combat::Died;
+use crate::enemy::Enemy;
+use crate::main_menu::GameState;
+use crate::player_movement::Player;
+use crate::profile::Profile;
+use crate::score::Score;
+use crate::survival::Survival;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// The stats file's name, in the profile's folder.
+const STATS_FILE: &str = "stats.ron";
+
+/// Lifetime records of the current [`Profile`], read from its folder and written back to it
+/// whenever they change.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub games_played: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub best_score: u32,
+    /// The furthest wave reached in survival.
+    pub best_wave: u32,
+}
+
+impl Stats {
+    /// Reads the stats from the profile folder `dir`, starting over if they're missing or
+    /// can't be parsed.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(STATS_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("Couldn't read stats from {}: {error}", path.display());
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Couldn't parse stats from {}: {error}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) {
+        let contents = match ron::ser::to_string_pretty(self, default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                error!("Couldn't serialize stats: {error}");
+                return;
+            }
+        };
+
+        let path = dir.join(STATS_FILE);
+        if let Err(error) = fs::create_dir_all(dir).and_then(|()| fs::write(&path, contents)) {
+            error!("Couldn't write stats to {}: {error}", path.display());
+        }
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        let stats = Stats::load(&app.world().resource::<Profile>().dir());
+        app.insert_resource(stats)
+            .add_systems(OnEnter(GameState::InGame), count_game)
+            .add_systems(
+                Update,
+                (
+                    load_profile_stats
+                        .run_if(resource_changed::<Profile>.and(not(resource_added::<Profile>))),
+                    (
+                        count_deaths,
+                        record_score.run_if(resource_changed::<Score>),
+                        record_wave.run_if(resource_exists_and_changed::<Survival>),
+                    ),
+                    // Only what changed after loading needs writing back
+                    save_stats.run_if(resource_changed::<Stats>.and(not(resource_added::<Stats>))),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Swaps in the stats of the profile switched to.
+fn load_profile_stats(mut commands: Commands, profile: Res<Profile>) {
+    commands.insert_resource(Stats::load(&profile.dir()));
+}
+
+fn count_game(mut stats: ResMut<Stats>) {
+    stats.games_played += 1;
+}
+
+/// Counts the player's deaths, and the enemies a player killed.
+fn count_deaths(
+    mut died: EventReader<Died>,
+    mut stats: ResMut<Stats>,
+    players: Query<(), With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    for event in died.read() {
+        if players.contains(event.entity) {
+            stats.deaths += 1;
+        } else if enemies.contains(event.entity)
+            && event.source.is_some_and(|source| players.contains(source))
+        {
+            stats.kills += 1;
+        }
+    }
+}
+
+fn record_score(score: Res<Score>, mut stats: ResMut<Stats>) {
+    if score.0 > stats.best_score {
+        stats.best_score = score.0;
+    }
+}
+
+fn record_wave(survival: Res<Survival>, mut stats: ResMut<Stats>) {
+    if survival.wave > stats.best_wave {
+        stats.best_wave = survival.wave;
+    }
+}
+
+fn save_stats(stats: Res<Stats>, profile: Res<Profile>) {
+    stats.save(&profile.dir());
+}
@@ -0,0 +1,132 @@
+use crate::level::LevelState;
+use crate::player_movement::Player;
+use avian3d::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// The width of the square chunks level scenery is streamed in.
+const CHUNK_SIZE: f32 = 32.0;
+/// Things closer than this to a player, horizontally, are loaded.
+pub const LOAD_DISTANCE: f32 = 96.0;
+/// Loaded things further than this from every player are unloaded. It's further than the load
+/// distance, so things at the edge aren't loaded and unloaded over and over as players move.
+pub const UNLOAD_DISTANCE: f32 = 128.0;
+
+/// The chunk `point` is in, on the horizontal plane.
+pub fn chunk_at(point: Vec2) -> IVec2 {
+    (point / CHUNK_SIZE).floor().as_ivec2()
+}
+
+fn chunk_center(chunk: IVec2) -> Vec2 {
+    (chunk.as_vec2() + 0.5) * CHUNK_SIZE
+}
+
+/// Triggered when a chunk comes within [`LOAD_DISTANCE`] of a player, for what's in it to be
+/// spawned. Tag what's spawned with [`InChunk`] to have it despawned when the chunk unloads.
+#[derive(Event)]
+pub struct ChunkLoaded(pub IVec2);
+
+/// Despawned when the chunk it's in unloads.
+#[derive(Component)]
+pub struct InChunk(pub IVec2);
+
+/// Set on a dynamic body over a chunk that unloaded, switched off so it doesn't fall through
+/// the missing scenery, until the chunk loads again.
+#[derive(Component)]
+struct FrozenInChunk(IVec2);
+
+/// The chunks currently loaded. Reset when a level is spawned, so the new level's chunks load
+/// from scratch.
+#[derive(Resource, Default)]
+pub struct LoadedChunks(HashSet<IVec2>);
+
+/// Finds how far things are from the players that stream the world in around them.
+#[derive(SystemParam)]
+pub struct Streamers<'w, 's> {
+    players: Query<'w, 's, &'static GlobalTransform, With<Player>>,
+}
+
+impl Streamers<'_, '_> {
+    /// The horizontal distance from `point` to the closest player, or infinity without any.
+    pub fn distance(&self, point: Vec2) -> f32 {
+        self.players
+            .iter()
+            .map(|transform| transform.translation().xz().distance(point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Vec2> {
+        self.players
+            .iter()
+            .map(|transform| transform.translation().xz())
+    }
+}
+
+pub struct StreamingPlugin;
+
+impl Plugin for StreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadedChunks>()
+            .add_systems(Update, stream_chunks.run_if(in_state(LevelState::Playing)));
+    }
+}
+
+/// Unloads chunks that players have moved away from, and loads those they've come close to.
+/// Dynamic bodies are frozen over unloaded chunks, and thawed once they load again.
+#[allow(clippy::type_complexity)]
+fn stream_chunks(
+    mut commands: Commands,
+    streamers: Streamers,
+    mut loaded: ResMut<LoadedChunks>,
+    in_chunks: Query<(Entity, &InChunk)>,
+    bodies: Query<
+        (Entity, &RigidBody, &GlobalTransform),
+        (Without<InChunk>, Without<RigidBodyDisabled>),
+    >,
+    frozen: Query<(Entity, &FrozenInChunk)>,
+) {
+    let unloaded: Vec<IVec2> = loaded
+        .0
+        .iter()
+        .copied()
+        .filter(|&chunk| streamers.distance(chunk_center(chunk)) > UNLOAD_DISTANCE)
+        .collect();
+    if !unloaded.is_empty() {
+        for chunk in &unloaded {
+            loaded.0.remove(chunk);
+        }
+        for (entity, in_chunk) in &in_chunks {
+            if unloaded.contains(&in_chunk.0) {
+                commands.entity(entity).despawn();
+            }
+        }
+        for (entity, rigid_body, transform) in &bodies {
+            let chunk = chunk_at(transform.translation().xz());
+            if rigid_body.is_dynamic() && unloaded.contains(&chunk) {
+                commands
+                    .entity(entity)
+                    .insert((FrozenInChunk(chunk), RigidBodyDisabled));
+            }
+        }
+    }
+
+    let reach = (LOAD_DISTANCE / CHUNK_SIZE).ceil() as i32;
+    for position in streamers.positions() {
+        let center = chunk_at(position);
+        for x in -reach..=reach {
+            for z in -reach..=reach {
+                let chunk = center + IVec2::new(x, z);
+                if chunk_center(chunk).distance(position) <= LOAD_DISTANCE && loaded.0.insert(chunk)
+                {
+                    commands.trigger(ChunkLoaded(chunk));
+                    for (entity, _) in frozen.iter().filter(|(_, frozen)| frozen.0 == chunk) {
+                        commands
+                            .entity(entity)
+                            .remove::<(FrozenInChunk, RigidBodyDisabled)>();
+                    }
+                }
+            }
+        }
+    }
+}
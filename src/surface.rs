@@ -0,0 +1,102 @@
+use crate::player_movement::Player;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// What some ground geometry is made of, changing how characters move on it and how bodies
+/// slide and bounce on it. Geometry without one uses the weather's default friction.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum SurfaceMaterial {
+    /// Slippery: characters are slow to speed up, turn and stop, and bodies slide far.
+    Ice,
+    /// Sticky: characters wade through slowly, and bodies stop dead without bouncing.
+    Mud,
+    /// Bouncy: bodies dropped on it bounce back up most of the way.
+    Rubber,
+}
+
+impl SurfaceMaterial {
+    /// Characters' top speed on the surface, relative to their normal one.
+    pub fn speed_factor(self) -> Scalar {
+        match self {
+            Self::Ice | Self::Rubber => 1.0,
+            Self::Mud => 0.5,
+        }
+    }
+
+    /// How quickly characters reach the speed they want on the surface, relative to normal.
+    pub fn acceleration_factor(self) -> Scalar {
+        match self {
+            Self::Ice => 0.08,
+            Self::Mud => 0.6,
+            Self::Rubber => 1.0,
+        }
+    }
+
+    /// The surface's friction, which wins over whatever touches it.
+    fn friction(self) -> Friction {
+        match self {
+            Self::Ice => Friction::new(0.02).with_combine_rule(CoefficientCombine::Min),
+            Self::Mud => Friction::new(1.0).with_combine_rule(CoefficientCombine::Max),
+            Self::Rubber => Friction::new(0.9),
+        }
+    }
+
+    /// The surface's restitution, which wins over whatever touches it.
+    fn restitution(self) -> Restitution {
+        match self {
+            Self::Ice => Restitution::new(0.05),
+            Self::Mud => Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
+            Self::Rubber => Restitution::new(0.85).with_combine_rule(CoefficientCombine::Max),
+        }
+    }
+}
+
+/// The material of the ground a character is standing on, if any. Sampled from what the
+/// character's ground caster hits, since characters float above the ground without touching it.
+#[derive(Component, Default)]
+pub struct GroundSurface(pub Option<SurfaceMaterial>);
+
+impl GroundSurface {
+    pub fn speed_factor(&self) -> Scalar {
+        self.0.map_or(1.0, SurfaceMaterial::speed_factor)
+    }
+
+    pub fn acceleration_factor(&self) -> Scalar {
+        self.0.map_or(1.0, SurfaceMaterial::acceleration_factor)
+    }
+}
+
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (apply_surface_materials, sample_ground_surface));
+    }
+}
+
+fn apply_surface_materials(
+    mut commands: Commands,
+    query: Query<(Entity, &SurfaceMaterial), Changed<SurfaceMaterial>>,
+) {
+    for (entity, surface) in &query {
+        commands
+            .entity(entity)
+            .insert((surface.friction(), surface.restitution()));
+    }
+}
+
+/// Records the material of the closest ground under each character.
+fn sample_ground_surface(
+    surfaces: Query<&SurfaceMaterial>,
+    mut query: Query<(&ShapeHits, &mut GroundSurface), With<Player>>,
+) {
+    for (ground_hits, mut ground_surface) in &mut query {
+        let surface = ground_hits
+            .iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+            .and_then(|hit| surfaces.get(hit.entity).ok())
+            .copied();
+        ground_surface.0 = surface;
+    }
+}
@@ -0,0 +1,334 @@
+use crate::checkpoint::Respawning;
+use crate::combat::{Dead, Died};
+use crate::cursor::{HoldsCursor, set_cursor_grab};
+use crate::enemy::{Enemy, EnemyBundle};
+use crate::game_assets::GameAssets;
+use crate::level::{LevelEntity, LevelState};
+use crate::main_menu::{GameMode, GameState};
+use crate::player_movement::Player;
+use crate::score::{AddScore, Score, ScoreReason};
+use crate::spawn_point::{SpawnKind, SpawnPoints};
+use crate::ui::ReleasedButtons;
+use avian3d::math::*;
+use bevy::prelude::*;
+
+const FIRST_WAVE_SIZE: u32 = 3;
+/// How many more enemies each wave has than the one before.
+const WAVE_GROWTH: u32 = 2;
+/// How much faster each wave's enemies are than the one before's, as a fraction of their
+/// default speed.
+const WAVE_SPEEDUP: Scalar = 0.05;
+/// The time between enemies of a wave spawning, in seconds.
+const SPAWN_INTERVAL: f32 = 1.5;
+/// The break before each wave, in seconds.
+const WAVE_BREAK: f32 = 5.0;
+/// Points for clearing a wave, times the wave's number.
+const WAVE_POINTS: u32 = 100;
+
+/// A run of the survival game mode: waves of enemies growing bigger and faster, spawning at
+/// the level's enemy spawn points, until the player dies.
+#[derive(Resource)]
+pub struct Survival {
+    /// The wave being fought, counting from 1, or 0 before the first.
+    pub wave: u32,
+    pub kills: u32,
+    /// How many enemies of the wave are still to spawn.
+    to_spawn: u32,
+    spawn_timer: Timer,
+    /// Counts down the break before the next wave, while there is one.
+    break_timer: Option<Timer>,
+    /// When the run started, and ended once the player died, in elapsed seconds.
+    started: f32,
+    ended: Option<f32>,
+}
+
+impl Survival {
+    fn new(now: f32) -> Self {
+        Self {
+            wave: 0,
+            kills: 0,
+            to_spawn: 0,
+            spawn_timer: Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating),
+            break_timer: Some(Timer::from_seconds(WAVE_BREAK, TimerMode::Once)),
+            started: now,
+            ended: None,
+        }
+    }
+
+    fn wave_size(wave: u32) -> u32 {
+        FIRST_WAVE_SIZE + WAVE_GROWTH * wave.saturating_sub(1)
+    }
+}
+
+/// An enemy spawned by a survival wave.
+#[derive(Component)]
+struct WaveEnemy;
+
+#[derive(Component)]
+struct WaveText;
+
+/// Shown once the player dies, with how far they got.
+#[derive(Component)]
+struct ResultsScreen;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum ResultsButton {
+    PlayAgain,
+    MainMenu,
+}
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            start_survival.run_if(resource_equals(GameMode::Survival)),
+        )
+        .add_systems(
+            OnExit(GameState::InGame),
+            stop_survival.run_if(resource_exists::<Survival>),
+        )
+        .add_systems(
+            Update,
+            (
+                (
+                    count_kills,
+                    end_survival,
+                    run_waves.run_if(in_state(LevelState::Playing)),
+                )
+                    .chain(),
+                update_wave_text,
+                press_results_buttons,
+            )
+                .run_if(resource_exists::<Survival>),
+        );
+    }
+}
+
+fn start_survival(time: Res<Time>, mut commands: Commands) {
+    commands.insert_resource(Survival::new(time.elapsed_secs()));
+    commands.spawn((
+        WaveText,
+        Text::default(),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(24.0),
+            left: Val::Px(24.0),
+            ..default()
+        },
+    ));
+}
+
+fn count_kills(
+    mut died: EventReader<Died>,
+    mut survival: ResMut<Survival>,
+    enemies: Query<(), With<WaveEnemy>>,
+) {
+    for event in died.read() {
+        if enemies.contains(event.entity) {
+            survival.kills += 1;
+        }
+    }
+}
+
+/// Spawns the enemies of the current wave one after another, and starts the break before the
+/// next wave once they're all dead.
+fn run_waves(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut survival: ResMut<Survival>,
+    mut add_score: EventWriter<AddScore>,
+    assets: Res<GameAssets>,
+    spawn_points: SpawnPoints,
+    alive: Query<(), (With<WaveEnemy>, Without<Dead>)>,
+) {
+    if survival.ended.is_some() {
+        return;
+    }
+
+    if let Some(break_timer) = &mut survival.break_timer {
+        if !break_timer.tick(time.delta()).finished() {
+            return;
+        }
+        survival.break_timer = None;
+        survival.wave += 1;
+        survival.to_spawn = Survival::wave_size(survival.wave);
+        survival.spawn_timer.reset();
+        return;
+    }
+
+    if survival.to_spawn > 0 {
+        if !survival.spawn_timer.tick(time.delta()).just_finished() {
+            return;
+        }
+        let Some(transform) = spawn_points.pick(SpawnKind::Enemy, None) else {
+            warn_once!("Survival needs a level with enemy spawn points");
+            return;
+        };
+
+        let default = Enemy::default();
+        let speedup = 1.0 + WAVE_SPEEDUP * (survival.wave - 1) as Scalar;
+        commands.spawn((
+            WaveEnemy,
+            LevelEntity,
+            EnemyBundle::new(&assets, transform).with_enemy(Enemy {
+                speed: default.speed * speedup,
+                ..default
+            }),
+        ));
+        survival.to_spawn -= 1;
+        return;
+    }
+
+    if alive.is_empty() {
+        add_score.write(AddScore {
+            points: WAVE_POINTS * survival.wave,
+            reason: ScoreReason::Wave,
+        });
+        survival.break_timer = Some(Timer::from_seconds(WAVE_BREAK, TimerMode::Once));
+    }
+}
+
+/// Ends the survival run when the game is left for the main menu. Its enemies go with the level.
+#[allow(clippy::type_complexity)]
+fn stop_survival(
+    mut commands: Commands,
+    texts: Query<Entity, Or<(With<WaveText>, With<ResultsScreen>)>>,
+) {
+    commands.remove_resource::<Survival>();
+    for entity in &texts {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_wave_text(survival: Res<Survival>, mut texts: Query<&mut Text, With<WaveText>>) {
+    let text = match &survival.break_timer {
+        Some(break_timer) => format!(
+            "Wave {} in {:.0}\nKills: {}",
+            survival.wave + 1,
+            break_timer.remaining_secs().ceil(),
+            survival.kills
+        ),
+        None => format!("Wave {}\nKills: {}", survival.wave, survival.kills),
+    };
+    for mut wave_text in &mut texts {
+        if wave_text.0 != text {
+            wave_text.0 = text.clone();
+        }
+    }
+}
+
+/// Ends the run when the player dies, showing the results over the game as it carries on
+/// without them.
+fn end_survival(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut died: EventReader<Died>,
+    mut survival: ResMut<Survival>,
+    mut window: Single<&mut Window>,
+    score: Res<Score>,
+    players: Query<(), With<Player>>,
+) {
+    if !died.read().any(|event| players.contains(event.entity)) || survival.ended.is_some() {
+        return;
+    }
+    let now = time.elapsed_secs();
+    survival.ended = Some(now);
+    set_cursor_grab(&mut window, false);
+
+    let survived = now - survival.started;
+    let lines = [
+        format!("Reached wave {}", survival.wave),
+        format!("Kills: {}", survival.kills),
+        format!("Score: {}", score.0),
+        format!(
+            "Survived {}:{:02}",
+            (survived / 60.0) as u32,
+            (survived % 60.0) as u32
+        ),
+    ];
+
+    commands
+        .spawn((
+            ResultsScreen,
+            HoldsCursor,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Game over"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+            ));
+            for line in lines {
+                parent.spawn(Text::new(line));
+            }
+            for (button, label) in [
+                (ResultsButton::PlayAgain, "Play again"),
+                (ResultsButton::MainMenu, "Main menu"),
+            ] {
+                parent
+                    .spawn((
+                        button,
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(24.0), Val::Px(12.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                    ))
+                    .with_child(Text::new(label));
+            }
+        });
+}
+
+/// Playing again respawns the player and starts over from the first wave.
+#[allow(clippy::too_many_arguments)]
+fn press_results_buttons(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut buttons: ReleasedButtons<ResultsButton>,
+    screens: Query<Entity, With<ResultsScreen>>,
+    enemies: Query<Entity, With<WaveEnemy>>,
+    players: Query<Entity, (With<Player>, Without<Respawning>)>,
+    mut score: ResMut<Score>,
+    mut window: Single<&mut Window>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(button) = buttons.released() else {
+        return;
+    };
+
+    match button {
+        ResultsButton::PlayAgain => {
+            let now = time.elapsed_secs();
+            for entity in screens.iter().chain(&enemies) {
+                commands.entity(entity).despawn();
+            }
+            for player in &players {
+                commands.entity(player).insert(Respawning::new(now));
+            }
+            commands.insert_resource(Survival::new(now));
+            score.0 = 0;
+            set_cursor_grab(&mut window, true);
+        }
+        ResultsButton::MainMenu => next_game_state.set(GameState::MainMenu),
+    }
+}
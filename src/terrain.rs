@@ -0,0 +1,239 @@
+use crate::game_assets::GameAssets;
+use crate::launch::LaunchOptions;
+use crate::streaming::{LOAD_DISTANCE, Streamers, UNLOAD_DISTANCE};
+use avian3d::prelude::*;
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy_enhanced_input::prelude::*;
+use std::collections::HashMap;
+
+/// How many quads each chunk is split into along each side.
+const CHUNK_RESOLUTION: u32 = 32;
+/// The size of the largest hills, in meters.
+const FEATURE_SIZE: f32 = 24.0;
+/// Each layer of noise adds details half the size and half the height of the previous one.
+const NOISE_OCTAVES: u32 = 4;
+/// The most chunks generated in one frame, so coming close to many at once doesn't hitch.
+const MAX_CHUNKS_PER_FRAME: usize = 2;
+
+/// Regenerates all terrain with a new random seed.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct RegenerateTerrain;
+
+/// Square, seeded, noise-generated hills centered on the entity, split into chunks with their
+/// own mesh and heightfield collider. Chunks are generated as players come close to them and
+/// despawned as they move away, so terrain can be far larger than what's around the players.
+/// The chunks are regenerated whenever this changes.
+#[derive(Component)]
+#[require(Transform, Visibility, TerrainChunks)]
+pub struct Terrain {
+    pub size: f32,
+    /// How many chunks the terrain is split into along each side.
+    pub chunks: u32,
+    /// The height of the highest possible point.
+    pub height: f32,
+    pub seed: u64,
+}
+
+impl Terrain {
+    /// The height of the terrain at `point`, relative to the terrain's center.
+    fn height_at(&self, point: Vec2) -> f32 {
+        let mut height = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = 1.0 / FEATURE_SIZE;
+        for octave in 0..NOISE_OCTAVES {
+            height +=
+                amplitude * value_noise(point * frequency, self.seed.wrapping_add(octave as u64));
+            amplitude /= 2.0;
+            frequency *= 2.0;
+        }
+        // The octaves add up to just under one
+        height * self.height
+    }
+
+    /// The center of the chunk at `index` on the chunk grid, relative to the terrain's center.
+    fn chunk_center(&self, index: UVec2) -> Vec2 {
+        let chunk_size = self.size / self.chunks as f32;
+        (index.as_vec2() + 0.5) * chunk_size - self.size / 2.0
+    }
+
+    /// The mesh and collider of the chunk centered at `center`.
+    fn chunk(&self, center: Vec2) -> (Mesh, Collider) {
+        let chunk_size = self.size / self.chunks as f32;
+        let spacing = chunk_size / CHUNK_RESOLUTION as f32;
+        let vertices = CHUNK_RESOLUTION + 1;
+
+        // Heightfield rows go along X, columns along Z
+        let heights: Vec<Vec<f32>> = (0..vertices)
+            .map(|x| {
+                (0..vertices)
+                    .map(|z| {
+                        let offset = Vec2::new(x as f32, z as f32) * spacing - chunk_size / 2.0;
+                        self.height_at(center + offset)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for (x, row) in heights.iter().enumerate() {
+            for (z, height) in row.iter().enumerate() {
+                let offset = Vec2::new(x as f32, z as f32) * spacing - chunk_size / 2.0;
+                positions.push([offset.x, *height, offset.y]);
+                uvs.push([
+                    x as f32 / CHUNK_RESOLUTION as f32,
+                    z as f32 / CHUNK_RESOLUTION as f32,
+                ]);
+            }
+        }
+
+        let mut indices = Vec::new();
+        for x in 0..CHUNK_RESOLUTION {
+            for z in 0..CHUNK_RESOLUTION {
+                let corner = x * vertices + z;
+                indices.extend([corner, corner + 1, corner + vertices]);
+                indices.extend([corner + vertices, corner + 1, corner + vertices + 1]);
+            }
+        }
+
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+        .with_computed_normals();
+        let collider = Collider::heightfield(heights, Vec3::new(chunk_size, 1.0, chunk_size));
+        (mesh, collider)
+    }
+}
+
+/// Smoothly interpolated random values between the points of a unit grid, from 0 to 1.
+fn value_noise(point: Vec2, seed: u64) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (3.0 - 2.0 * t);
+    let (x, z) = (cell.x as i32, cell.y as i32);
+
+    let top = hash(x, z, seed).lerp(hash(x + 1, z, seed), t.x);
+    let bottom = hash(x, z + 1, seed).lerp(hash(x + 1, z + 1, seed), t.x);
+    top.lerp(bottom, t.y)
+}
+
+/// A random value from 0 to 1 for a grid point.
+fn hash(x: i32, z: i32, seed: u64) -> f32 {
+    let mut hash = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    hash ^= hash >> 33;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Marks the chunks of a [`Terrain`], which are its children.
+#[derive(Component)]
+struct TerrainChunk;
+
+/// The chunks of a [`Terrain`] that are currently spawned, by their index on its chunk grid.
+#[derive(Component, Default)]
+struct TerrainChunks(HashMap<UVec2, Entity>);
+
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (seed_new_terrain, reset_terrain, stream_terrain).chain(),
+        )
+        .add_observer(regenerate_terrain);
+    }
+}
+
+/// Gives new terrain the seed from the command line, if there is one, in place of its own.
+fn seed_new_terrain(options: Res<LaunchOptions>, mut query: Query<&mut Terrain, Added<Terrain>>) {
+    let Some(seed) = options.seed else {
+        return;
+    };
+    for mut terrain in &mut query {
+        terrain.seed = seed;
+    }
+}
+
+/// Despawns the chunks of changed terrain, so they're generated again.
+fn reset_terrain(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TerrainChunks), Changed<Terrain>>,
+) {
+    for (entity, mut chunks) in &mut query {
+        commands.entity(entity).despawn_related::<Children>();
+        chunks.0.clear();
+    }
+}
+
+/// Generates the chunks closest to players first, and despawns those players have moved away
+/// from.
+fn stream_terrain(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    assets: Res<GameAssets>,
+    streamers: Streamers,
+    mut query: Query<(Entity, &Terrain, &GlobalTransform, &mut TerrainChunks)>,
+) {
+    for (entity, terrain, transform, mut chunks) in &mut query {
+        let mut missing = Vec::new();
+        for x in 0..terrain.chunks {
+            for z in 0..terrain.chunks {
+                let index = UVec2::new(x, z);
+                let center = terrain.chunk_center(index);
+                let distance = streamers.distance(
+                    transform
+                        .transform_point(Vec3::new(center.x, 0.0, center.y))
+                        .xz(),
+                );
+
+                if distance > UNLOAD_DISTANCE {
+                    if let Some(chunk) = chunks.0.remove(&index) {
+                        commands.entity(chunk).despawn();
+                    }
+                } else if distance <= LOAD_DISTANCE && !chunks.0.contains_key(&index) {
+                    missing.push((index, distance));
+                }
+            }
+        }
+
+        missing.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        for (index, _) in missing.into_iter().take(MAX_CHUNKS_PER_FRAME) {
+            let center = terrain.chunk_center(index);
+            let (mesh, collider) = terrain.chunk(center);
+            let chunk = commands
+                .spawn((
+                    TerrainChunk,
+                    RigidBody::Static,
+                    collider,
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(assets.terrain_material.clone()),
+                    Transform::from_xyz(center.x, 0.0, center.y),
+                    ChildOf(entity),
+                ))
+                .id();
+            chunks.0.insert(index, chunk);
+        }
+    }
+}
+
+/// Gives every terrain a new random seed, logging it so it can be put in a level.
+fn regenerate_terrain(
+    _trigger: Trigger<Started<RegenerateTerrain>>,
+    mut query: Query<&mut Terrain>,
+) {
+    for mut terrain in &mut query {
+        terrain.seed = rand::random();
+        info!("Regenerated terrain with seed {}", terrain.seed);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::ammo::Ammo;
+use crate::player_movement::{FireRate, Player};
+use crate::tools::Tool;
+use bevy::prelude::*;
+
+/// How far the tool panel sits from the top right corner of the screen, in pixels.
+const TOOL_HUD_MARGIN: f32 = 24.0;
+const COOLDOWN_BAR_WIDTH: f32 = 120.0;
+const COOLDOWN_BAR_HEIGHT: f32 = 4.0;
+
+/// The text naming the player's tool.
+#[derive(Component)]
+struct ToolName;
+
+/// The text showing the player's ammo, for tools that use it.
+#[derive(Component)]
+struct AmmoCounter;
+
+/// The bar filling up as the next throw or a reload becomes ready.
+#[derive(Component)]
+struct CooldownBar;
+
+#[derive(Component)]
+struct CooldownBarFill;
+
+pub struct ToolHudPlugin;
+
+impl Plugin for ToolHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_tool_hud)
+            .add_systems(Update, update_tool_hud);
+    }
+}
+
+fn spawn_tool_hud(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(TOOL_HUD_MARGIN),
+            right: Val::Px(TOOL_HUD_MARGIN),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::End,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        children![
+            (
+                ToolName,
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+            ),
+            (
+                AmmoCounter,
+                Text::default(),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+            ),
+            (
+                CooldownBar,
+                Node {
+                    width: Val::Px(COOLDOWN_BAR_WIDTH),
+                    height: Val::Px(COOLDOWN_BAR_HEIGHT),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE.with_alpha(0.3)),
+                children![(
+                    CooldownBarFill,
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.8)),
+                )],
+            ),
+        ],
+    ));
+}
+
+/// Shows the player's tool, and for tools throwing balls their ammo and how soon they can
+/// throw again. Reloads show on the bar too.
+#[allow(clippy::type_complexity)]
+fn update_tool_hud(
+    time: Res<Time>,
+    players: Query<(&Tool, &Ammo, &FireRate), With<Player>>,
+    mut names: Query<&mut Text, (With<ToolName>, Without<AmmoCounter>)>,
+    mut counters: Query<(&mut Text, &mut Visibility), (With<AmmoCounter>, Without<ToolName>)>,
+    mut bars: Query<&mut Visibility, (With<CooldownBar>, Without<AmmoCounter>)>,
+    mut fills: Query<&mut Node, With<CooldownBarFill>>,
+) {
+    let Some((tool, ammo, fire_rate)) = players.iter().next() else {
+        return;
+    };
+    let now = time.elapsed_secs();
+
+    for mut name in &mut names {
+        if name.0 != tool.name() {
+            name.0 = tool.name().to_string();
+        }
+    }
+
+    let visibility = if tool.uses_ammo() {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    let reload = ammo.reload_progress(now);
+    let text = if reload.is_some() {
+        "Reloading...".to_string()
+    } else {
+        format!("{} / {}", ammo.magazine(), ammo.reserve())
+    };
+    for (mut counter, mut counter_visibility) in &mut counters {
+        if counter.0 != text {
+            counter.0 = text.clone();
+        }
+        counter_visibility.set_if_neq(visibility);
+    }
+
+    for mut bar in &mut bars {
+        bar.set_if_neq(visibility);
+    }
+    let progress = reload.unwrap_or_else(|| fire_rate.readiness(now));
+    for mut fill in &mut fills {
+        fill.width = Val::Percent(progress * 100.0);
+    }
+}
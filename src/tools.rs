@@ -0,0 +1,419 @@
+use crate::ammo::Ammo;
+use crate::ball::{BallKind, BallPool, BallSettings};
+use crate::camera::CharacterCameras;
+use crate::carry::Carry;
+use crate::cube::CubeBundle;
+use crate::game_assets::{GameAssets, LAMP_RADIUS, RAMP_SIZE};
+use crate::grenade::GrenadeBundle;
+use crate::inventory::{Inventory, Item};
+use crate::layers::GameLayer;
+use crate::player_movement::{FireRate, Player, PlayerAction, PlayerAltAction, ThrowCharge};
+use crate::tuning::Tuning;
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use std::f32::consts::PI;
+
+const BOUNCY_THROW_SPEED: f32 = 30.0;
+const HOMING_THROW_SPEED: f32 = 25.0;
+/// How far away spawners can place things, and take back what they spawned.
+const PLACE_RANGE: Scalar = 10.0;
+/// How steeply placed ramps rise.
+const RAMP_ANGLE: f32 = PI / 9.0;
+/// How steep ground can be for a ramp to be placed on it.
+const MAX_RAMP_GROUND_ANGLE: f32 = PI / 6.0;
+const LAMP_INTENSITY: f32 = 200_000.0;
+const LAMP_RANGE: f32 = 15.0;
+
+/// Selects the tool in the slot given by the action's value, counting from 1.
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct PlayerSelectTool;
+
+/// Selects the next tool for positive values and the previous one for negative values.
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct PlayerCycleTool;
+
+/// The tool a character is holding, which decides what [`PlayerAction`] and [`PlayerAltAction`]
+/// do.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    /// Throws balls, charged up by holding the action. The alt action throws a bouncy ball.
+    #[default]
+    BallThrower,
+    /// Spawns cubes. The alt action takes back the cube being looked at.
+    CubeSpawner,
+    /// Places a ramp on the ground being looked at, rising away from the character. The alt
+    /// action takes back the ramp being looked at.
+    RampSpawner,
+    /// Places a light in front of the character. The alt action takes back the light being
+    /// looked at.
+    LightSpawner,
+    /// Throws grenades, like [`PlayerGrenade`](crate::grenade::PlayerGrenade) does with any
+    /// tool. Has no alt action.
+    GrenadeThrower,
+    /// Grabs and launches dynamic bodies, see [`GravityGun`](crate::gravity_gun::GravityGun).
+    GravityGun,
+    /// Throws homing balls. Has no alt action.
+    HomingThrower,
+}
+
+impl Tool {
+    /// Every tool, in slot order.
+    pub const ALL: [Self; 7] = [
+        Self::BallThrower,
+        Self::CubeSpawner,
+        Self::RampSpawner,
+        Self::LightSpawner,
+        Self::GrenadeThrower,
+        Self::GravityGun,
+        Self::HomingThrower,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BallThrower => "Ball thrower",
+            Self::CubeSpawner => "Cube spawner",
+            Self::RampSpawner => "Ramp spawner",
+            Self::LightSpawner => "Light spawner",
+            Self::GrenadeThrower => "Grenades",
+            Self::GravityGun => "Gravity gun",
+            Self::HomingThrower => "Homing thrower",
+        }
+    }
+
+    /// Whether the tool throws balls, using up [`Ammo`] at the [`FireRate`].
+    pub fn uses_ammo(self) -> bool {
+        matches!(self, Self::BallThrower | Self::HomingThrower)
+    }
+
+    /// The [`Item`] the tool spends from the character's [`Inventory`] on every use.
+    pub fn item(self) -> Option<Item> {
+        match self {
+            Self::CubeSpawner => Some(Item::Cube),
+            Self::RampSpawner => Some(Item::Ramp),
+            Self::LightSpawner => Some(Item::Lamp),
+            Self::GrenadeThrower => Some(Item::Grenade),
+            Self::BallThrower | Self::HomingThrower | Self::GravityGun => None,
+        }
+    }
+
+    /// The tool `steps` slots after this one, wrapping around.
+    fn cycled(self, steps: i32) -> Self {
+        let slot = Self::ALL.iter().position(|tool| *tool == self).unwrap_or(0);
+        let len = Self::ALL.len() as i32;
+        Self::ALL[(slot as i32 + steps).rem_euclid(len) as usize]
+    }
+}
+
+/// Something placed by the [`Tool`] it holds, which can take it back. Cubes from the level or a
+/// blueprint don't have it, so they can't be turned into inventory items.
+#[derive(Component)]
+pub struct Placed(pub Tool);
+
+pub struct ToolsPlugin;
+
+impl Plugin for ToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(select_tool)
+            .add_observer(cycle_tool)
+            .add_observer(start_primary)
+            .add_observer(release_primary)
+            .add_observer(use_secondary);
+    }
+}
+
+/// Switching tools drops a throw being charged.
+fn switch_tool(new_tool: Tool, tool: &mut Tool, throw_charge: &mut ThrowCharge) {
+    if new_tool != *tool {
+        *tool = new_tool;
+        throw_charge.0 = None;
+    }
+}
+
+fn select_tool(
+    trigger: Trigger<Started<PlayerSelectTool>>,
+    mut query: Query<(&mut Tool, &mut ThrowCharge), With<Player>>,
+) {
+    let Ok((mut tool, mut throw_charge)) = query.get_mut(trigger.target()) else {
+        return;
+    };
+    let Some(&new_tool) = (trigger.value as usize)
+        .checked_sub(1)
+        .and_then(|slot| Tool::ALL.get(slot))
+    else {
+        return;
+    };
+
+    switch_tool(new_tool, &mut tool, &mut throw_charge);
+}
+
+fn cycle_tool(
+    trigger: Trigger<Started<PlayerCycleTool>>,
+    mut query: Query<(&mut Tool, &mut ThrowCharge), With<Player>>,
+) {
+    let Ok((mut tool, mut throw_charge)) = query.get_mut(trigger.target()) else {
+        return;
+    };
+
+    let new_tool = tool.cycled(trigger.value.signum() as i32);
+    switch_tool(new_tool, &mut tool, &mut throw_charge);
+}
+
+/// A transform one meter in front of the camera, facing where it looks, to throw and spawn
+/// things from.
+fn in_front_of(camera: &GlobalTransform) -> Transform {
+    let mut transform = camera.compute_transform();
+    let forward = transform.forward().as_vec3();
+    transform.translation += forward;
+    transform
+}
+
+/// The closest solid thing the camera looks at within [`PLACE_RANGE`], ignoring the character.
+fn looked_at(
+    spatial_query: &SpatialQuery,
+    camera: &GlobalTransform,
+    character: Entity,
+) -> Option<RayHitData> {
+    let filter =
+        SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([character]);
+    spatial_query.cast_ray(
+        camera.translation(),
+        camera.forward(),
+        PLACE_RANGE,
+        true,
+        &filter,
+    )
+}
+
+/// A ramp with its low end at `point`, rising away from the camera.
+fn ramp_transform(camera: &GlobalTransform, point: Vector) -> Transform {
+    let forward = camera.forward().with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let rotation = Transform::default().looking_to(forward, Vec3::Y).rotation
+        * Quat::from_rotation_x(RAMP_ANGLE);
+    // The box's center is half its length up along its slope, and half its thickness under it
+    let center = rotation * Vec3::new(0.0, -RAMP_SIZE.y / 2.0, -RAMP_SIZE.z / 2.0);
+    Transform::from_translation(point + center).with_rotation(rotation)
+}
+
+/// Starts charging a throw, or spawns or throws something right away, spending its [`Item`]. The
+/// gravity gun handles its own actions, and nothing is used while the character's hands are
+/// full.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn start_primary(
+    trigger: Trigger<Started<PlayerAction>>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+    mut query: Query<
+        (
+            &Tool,
+            &Carry,
+            &mut ThrowCharge,
+            &mut FireRate,
+            &mut Ammo,
+            &mut Inventory,
+        ),
+        With<Player>,
+    >,
+    mut commands: Commands,
+    mut ball_pool: ResMut<BallPool>,
+    ball_settings: Res<BallSettings>,
+    assets: Res<GameAssets>,
+) {
+    let entity = trigger.target();
+    let Ok((tool, carry, mut throw_charge, mut fire_rate, mut ammo, mut inventory)) =
+        query.get_mut(entity)
+    else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+    if carry.is_carrying() {
+        return;
+    }
+    // Checked up front, but only spent once the tool is actually used
+    if tool.item().is_some_and(|item| inventory.count(item) == 0) {
+        return;
+    }
+
+    match tool {
+        Tool::BallThrower => throw_charge.0 = Some(time.elapsed_secs()),
+        Tool::CubeSpawner => {
+            inventory.take(Item::Cube, 1);
+            commands.spawn((
+                CubeBundle::new(&assets, in_front_of(camera)),
+                Placed(Tool::CubeSpawner),
+            ));
+        }
+        Tool::RampSpawner => {
+            let Some(hit) = looked_at(&spatial_query, camera, entity) else {
+                return;
+            };
+            if hit.normal.angle_between(Vector::Y) > MAX_RAMP_GROUND_ANGLE {
+                return;
+            }
+
+            inventory.take(Item::Ramp, 1);
+            let point = camera.translation() + camera.forward() * hit.distance;
+            commands.spawn((
+                Placed(Tool::RampSpawner),
+                RigidBody::Static,
+                Collider::cuboid(RAMP_SIZE.x, RAMP_SIZE.y, RAMP_SIZE.z),
+                Mesh3d(assets.ramp_mesh.clone()),
+                MeshMaterial3d(assets.ramp_material.clone()),
+                ramp_transform(camera, point),
+            ));
+        }
+        Tool::LightSpawner => {
+            inventory.take(Item::Lamp, 1);
+            commands.spawn((
+                Placed(Tool::LightSpawner),
+                RigidBody::Static,
+                Collider::sphere(LAMP_RADIUS),
+                Mesh3d(assets.lamp_mesh.clone()),
+                MeshMaterial3d(assets.lamp_material.clone()),
+                Transform::from_translation(in_front_of(camera).translation),
+                PointLight {
+                    color: Color::srgb(1.0, 0.9, 0.7),
+                    intensity: LAMP_INTENSITY,
+                    range: LAMP_RANGE,
+                    radius: LAMP_RADIUS,
+                    shadows_enabled: true,
+                    ..default()
+                },
+            ));
+        }
+        Tool::GrenadeThrower => {
+            if !fire_rate.try_fire(time.elapsed_secs()) {
+                return;
+            }
+            inventory.take(Item::Grenade, 1);
+            commands.spawn(GrenadeBundle::new(&assets, in_front_of(camera), entity));
+        }
+        // Handled by the gravity gun itself
+        Tool::GravityGun => {}
+        Tool::HomingThrower => {
+            if !ammo.can_fire() || !fire_rate.try_fire(time.elapsed_secs()) {
+                return;
+            }
+            ammo.consume();
+
+            ball_pool.throw(
+                &mut commands,
+                entity,
+                &assets,
+                in_front_of(camera),
+                HOMING_THROW_SPEED,
+                BallKind::Homing,
+                &ball_settings,
+            );
+        }
+    }
+}
+
+/// Throws a ball on release, faster the longer the throw was charged. Releases before the
+/// [`FireRate`] allows another throw, or without [`Ammo`] to throw, are dropped.
+#[allow(clippy::too_many_arguments)]
+fn release_primary(
+    trigger: Trigger<Completed<PlayerAction>>,
+    time: Res<Time>,
+    cameras: CharacterCameras,
+    mut query: Query<(&mut ThrowCharge, &mut FireRate, &mut Ammo), With<Player>>,
+    mut commands: Commands,
+    mut ball_pool: ResMut<BallPool>,
+    ball_settings: Res<BallSettings>,
+    tuning: Res<Tuning>,
+    assets: Res<GameAssets>,
+) {
+    let entity = trigger.target();
+    let Ok((mut throw_charge, mut fire_rate, mut ammo)) = query.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+    let now = time.elapsed_secs();
+    let Some(charge) = throw_charge.charge(now) else {
+        return;
+    };
+    throw_charge.0 = None;
+    if !ammo.can_fire() || !fire_rate.try_fire(now) {
+        return;
+    }
+    ammo.consume();
+
+    let speed = tuning.min_throw_speed.lerp(tuning.max_throw_speed, charge);
+    ball_pool.throw(
+        &mut commands,
+        entity,
+        &assets,
+        in_front_of(camera),
+        speed,
+        BallKind::Regular,
+        &ball_settings,
+    );
+}
+
+/// Throws a bouncy ball, which isn't charged up but shares the [`FireRate`] and [`Ammo`] of
+/// regular throws, or takes back the spawned thing being looked at, returning its [`Item`].
+#[allow(clippy::too_many_arguments)]
+fn use_secondary(
+    trigger: Trigger<Started<PlayerAltAction>>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    cameras: CharacterCameras,
+    placed: Query<&Placed>,
+    mut query: Query<(&Tool, &Carry, &mut FireRate, &mut Ammo, &mut Inventory), With<Player>>,
+    mut commands: Commands,
+    mut ball_pool: ResMut<BallPool>,
+    ball_settings: Res<BallSettings>,
+    assets: Res<GameAssets>,
+) {
+    let entity = trigger.target();
+    let Ok((tool, carry, mut fire_rate, mut ammo, mut inventory)) = query.get_mut(entity) else {
+        return;
+    };
+    let Some(camera) = cameras.transform(entity) else {
+        return;
+    };
+    if carry.is_carrying() {
+        return;
+    }
+
+    match tool {
+        Tool::BallThrower => {
+            if !ammo.can_fire() || !fire_rate.try_fire(time.elapsed_secs()) {
+                return;
+            }
+            ammo.consume();
+
+            ball_pool.throw(
+                &mut commands,
+                entity,
+                &assets,
+                in_front_of(camera),
+                BOUNCY_THROW_SPEED,
+                BallKind::Bouncy,
+                &ball_settings,
+            );
+        }
+        Tool::CubeSpawner | Tool::RampSpawner | Tool::LightSpawner => {
+            let Some(hit) = looked_at(&spatial_query, camera, entity) else {
+                return;
+            };
+
+            if !placed.get(hit.entity).is_ok_and(|placed| placed.0 == *tool) {
+                return;
+            }
+            // Left where it is when there's no room to take it back
+            let taken_back = tool.item().is_none_or(|item| inventory.add(item, 1) == 0);
+            if taken_back {
+                commands.entity(hit.entity).despawn();
+            }
+        }
+        Tool::HomingThrower | Tool::GrenadeThrower | Tool::GravityGun => {}
+    }
+}
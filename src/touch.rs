@@ -0,0 +1,236 @@
+use crate::player_movement::{PlayerAction, PlayerJump, PlayerMove, PlayerSprint};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+const TOUCH_MARGIN: f32 = 40.0;
+const JOYSTICK_RADIUS: f32 = 80.0;
+const JOYSTICK_KNOB_RADIUS: f32 = 32.0;
+const BUTTON_RADIUS: f32 = 48.0;
+const BUTTON_SPACING: f32 = 20.0;
+
+/// The root of the on-screen controls. Hidden until the screen is first touched.
+#[derive(Component)]
+struct TouchControls;
+
+#[derive(Component)]
+struct JoystickKnob;
+
+/// An on-screen button standing in for one of the player's actions.
+#[derive(Component, Clone, Copy)]
+enum TouchButton {
+    Jump,
+    Throw,
+    Sprint,
+}
+
+impl TouchButton {
+    const ALL: [Self; 3] = [Self::Jump, Self::Throw, Self::Sprint];
+
+    /// Where the button's center is, measured from the bottom right corner of the screen.
+    fn corner_offset(self) -> Vec2 {
+        let first = TOUCH_MARGIN + BUTTON_RADIUS;
+        let second = first + BUTTON_RADIUS * 2.0 + BUTTON_SPACING;
+        match self {
+            Self::Jump => Vec2::new(first, first),
+            Self::Throw => Vec2::new(first, second),
+            Self::Sprint => Vec2::new(second, first),
+        }
+    }
+
+    fn center(self, screen_size: Vec2) -> Vec2 {
+        screen_size - self.corner_offset()
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Jump => "Jump",
+            Self::Throw => "Throw",
+            Self::Sprint => "Sprint",
+        }
+    }
+}
+
+fn joystick_center(screen_size: Vec2) -> Vec2 {
+    Vec2::new(
+        TOUCH_MARGIN + JOYSTICK_RADIUS,
+        screen_size.y - TOUCH_MARGIN - JOYSTICK_RADIUS,
+    )
+}
+
+/// The touch currently steering with the joystick, if any.
+#[derive(Resource, Default)]
+struct JoystickTouch(Option<u64>);
+
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JoystickTouch>()
+            .add_systems(Startup, spawn_touch_controls)
+            .add_systems(
+                Update,
+                (
+                    show_touch_controls.run_if(any_touch_started),
+                    read_touch_controls,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_touch_controls(mut commands: Commands) {
+    commands
+        .spawn((
+            TouchControls,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(TOUCH_MARGIN),
+                        bottom: Val::Px(TOUCH_MARGIN),
+                        width: Val::Px(JOYSTICK_RADIUS * 2.0),
+                        height: Val::Px(JOYSTICK_RADIUS * 2.0),
+                        ..default()
+                    },
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::WHITE.with_alpha(0.2)),
+                ))
+                .with_child((
+                    JoystickKnob,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(JOYSTICK_RADIUS - JOYSTICK_KNOB_RADIUS),
+                        top: Val::Px(JOYSTICK_RADIUS - JOYSTICK_KNOB_RADIUS),
+                        width: Val::Px(JOYSTICK_KNOB_RADIUS * 2.0),
+                        height: Val::Px(JOYSTICK_KNOB_RADIUS * 2.0),
+                        ..default()
+                    },
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::WHITE.with_alpha(0.5)),
+                ));
+
+            for button in TouchButton::ALL {
+                let offset = button.corner_offset();
+                parent
+                    .spawn((
+                        button,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(offset.x - BUTTON_RADIUS),
+                            bottom: Val::Px(offset.y - BUTTON_RADIUS),
+                            width: Val::Px(BUTTON_RADIUS * 2.0),
+                            height: Val::Px(BUTTON_RADIUS * 2.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderRadius::MAX,
+                        BackgroundColor(Color::WHITE.with_alpha(0.2)),
+                    ))
+                    .with_child((
+                        Text::new(button.label()),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                    ));
+            }
+        });
+}
+
+fn any_touch_started(touches: Res<Touches>) -> bool {
+    touches.any_just_pressed()
+}
+
+/// Shows the on-screen controls once the game is played on a touch screen.
+fn show_touch_controls(mut controls: Query<&mut Visibility, With<TouchControls>>) {
+    for mut visibility in &mut controls {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// Turns touches on the joystick and buttons into the actions they stand in for, by mocking
+/// those actions for the next input update.
+#[allow(clippy::too_many_arguments)]
+fn read_touch_controls(
+    mut commands: Commands,
+    touches: Res<Touches>,
+    window: Single<&Window>,
+    mut joystick_touch: ResMut<JoystickTouch>,
+    mut knobs: Query<&mut Node, With<JoystickKnob>>,
+    mut buttons: Query<(&TouchButton, &mut BackgroundColor)>,
+    move_actions: Query<Entity, With<Action<PlayerMove>>>,
+    jump_actions: Query<Entity, With<Action<PlayerJump>>>,
+    throw_actions: Query<Entity, With<Action<PlayerAction>>>,
+    sprint_actions: Query<Entity, With<Action<PlayerSprint>>>,
+) {
+    let screen_size = window.size();
+    let joystick_center = joystick_center(screen_size);
+
+    if joystick_touch
+        .0
+        .is_some_and(|id| touches.get_pressed(id).is_none())
+    {
+        joystick_touch.0 = None;
+    }
+    if joystick_touch.0.is_none() {
+        joystick_touch.0 = touches
+            .iter_just_pressed()
+            .find(|touch| touch.position().distance(joystick_center) <= JOYSTICK_RADIUS)
+            .map(|touch| touch.id());
+    }
+
+    // Screen space points down, movement forward
+    let stick = joystick_touch
+        .0
+        .and_then(|id| touches.get_pressed(id))
+        .map_or(Vec2::ZERO, |touch| {
+            let offset =
+                ((touch.position() - joystick_center) / JOYSTICK_RADIUS).clamp_length_max(1.0);
+            Vec2::new(offset.x, -offset.y)
+        });
+
+    for mut knob in &mut knobs {
+        knob.left = Val::Px(JOYSTICK_RADIUS - JOYSTICK_KNOB_RADIUS + stick.x * JOYSTICK_RADIUS);
+        knob.top = Val::Px(JOYSTICK_RADIUS - JOYSTICK_KNOB_RADIUS - stick.y * JOYSTICK_RADIUS);
+    }
+
+    if joystick_touch.0.is_some() {
+        for action in &move_actions {
+            commands
+                .entity(action)
+                .insert(ActionMock::once(ActionState::Fired, stick));
+        }
+    }
+
+    for (&button, mut color) in &mut buttons {
+        let center = button.center(screen_size);
+        let pressed = touches.iter().any(|touch| {
+            Some(touch.id()) != joystick_touch.0
+                && touch.position().distance(center) <= BUTTON_RADIUS
+        });
+        color.0 = Color::WHITE.with_alpha(if pressed { 0.5 } else { 0.2 });
+        if !pressed {
+            continue;
+        }
+
+        let actions: Vec<Entity> = match button {
+            TouchButton::Jump => jump_actions.iter().collect(),
+            TouchButton::Throw => throw_actions.iter().collect(),
+            TouchButton::Sprint => sprint_actions.iter().collect(),
+        };
+        for action in actions {
+            commands
+                .entity(action)
+                .insert(ActionMock::once(ActionState::Fired, true));
+        }
+    }
+}
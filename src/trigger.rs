@@ -0,0 +1,80 @@
+use crate::layers::GameLayer;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+/// A sensor volume triggering [`TriggerEnter`] and [`TriggerExit`] on itself as bodies come and
+/// go. Things reacting to what walks into them, like checkpoints and kill zones, observe those
+/// instead of tracking overlaps themselves. What's inside right now is in its
+/// [`CollidingEntities`].
+#[derive(Component, Default)]
+#[require(CollidingEntities, CollisionEventsEnabled)]
+pub struct TriggerVolume;
+
+/// Triggered on a [`TriggerVolume`] when something starts overlapping it.
+#[derive(Event)]
+pub struct TriggerEnter {
+    /// The body that entered, or the collider itself if it isn't attached to one.
+    pub entity: Entity,
+}
+
+/// Triggered on a [`TriggerVolume`] when something stops overlapping it.
+#[derive(Event)]
+pub struct TriggerExit {
+    /// The body that left, or the collider itself if it isn't attached to one.
+    pub entity: Entity,
+}
+
+#[derive(Bundle)]
+pub struct TriggerVolumeBundle {
+    trigger_volume: TriggerVolume,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_layers: CollisionLayers,
+    pub transform: Transform,
+}
+
+impl TriggerVolumeBundle {
+    pub fn new(collider: Collider, transform: Transform) -> Self {
+        Self {
+            trigger_volume: TriggerVolume,
+            rigid_body: RigidBody::Static,
+            collider,
+            sensor: Sensor,
+            collision_layers: CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+            transform,
+        }
+    }
+}
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(enter_trigger).add_observer(exit_trigger);
+    }
+}
+
+fn enter_trigger(
+    trigger: Trigger<OnCollisionStart>,
+    mut commands: Commands,
+    volumes: Query<(), With<TriggerVolume>>,
+) {
+    let volume = trigger.target();
+    if volumes.contains(volume) {
+        let entity = trigger.body.unwrap_or(trigger.collider);
+        commands.trigger_targets(TriggerEnter { entity }, volume);
+    }
+}
+
+fn exit_trigger(
+    trigger: Trigger<OnCollisionEnd>,
+    mut commands: Commands,
+    volumes: Query<(), With<TriggerVolume>>,
+) {
+    let volume = trigger.target();
+    if volumes.contains(volume) {
+        let entity = trigger.body.unwrap_or(trigger.collider);
+        commands.trigger_targets(TriggerExit { entity }, volume);
+    }
+}
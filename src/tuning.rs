@@ -0,0 +1,105 @@
+use avian3d::math::*;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::error::Error;
+
+const TUNING_PATH: &str = "player.tuning.ron";
+
+/// Movement and throwing numbers read from a `.tuning.ron` file, so they can be tweaked while
+/// the game runs. Copied into a resource of the same type whenever the file loads or changes,
+/// and until then holding the built-in values.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone)]
+#[serde(default)]
+pub struct Tuning {
+    /// The top walking speed, in meters per second.
+    pub speed: Scalar,
+    /// How fast the walk reaches its speed, scaled by the material of the ground.
+    pub walk_acceleration: Scalar,
+    pub jump_impulse: Scalar,
+    /// How much faster sprinting is than walking.
+    pub sprint_multiplier: Scalar,
+    /// How high above the ground the character floats while standing.
+    pub float_height: Scalar,
+    /// The speed of a ball thrown without, and with full, charge.
+    pub min_throw_speed: Scalar,
+    pub max_throw_speed: Scalar,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            speed: 10.0,
+            walk_acceleration: 60.0,
+            jump_impulse: 15.0,
+            sprint_multiplier: 2.0,
+            float_height: 1.0,
+            min_throw_speed: 20.0,
+            max_throw_speed: 100.0,
+        }
+    }
+}
+
+/// Keeps the tuning file loaded, and identifies its events.
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Tuning, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .init_resource::<Tuning>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(PreUpdate, apply_loaded_tuning);
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+/// Copies the tuning file into the [`Tuning`] resource once it loads, and again whenever it's
+/// modified.
+fn apply_loaded_tuning(
+    mut events: EventReader<AssetEvent<Tuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&handle.0) {
+            *tuning = loaded.clone();
+            info!("Applied {TUNING_PATH}");
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Menu buttons of type `B`, read as they're clicked. A button acts once the click on it is
+/// released, so a button that hands control back to the game isn't still held down once the
+/// gameplay contexts are back on, which would throw a ball.
+#[derive(SystemParam)]
+pub struct ReleasedButtons<'w, 's, B: Component + Copy + Eq> {
+    /// The button the click started on.
+    pressed: Local<'s, Option<B>>,
+    buttons: Query<'w, 's, (&'static Interaction, &'static B), Changed<Interaction>>,
+}
+
+impl<B: Component + Copy + Eq> ReleasedButtons<'_, '_, B> {
+    /// The button a click was released on this frame, if it also started on it.
+    pub fn released(&mut self) -> Option<B> {
+        let mut released = None;
+        for (interaction, &button) in &self.buttons {
+            match interaction {
+                Interaction::Pressed => *self.pressed = Some(button),
+                Interaction::Hovered if *self.pressed == Some(button) => {
+                    *self.pressed = None;
+                    released = Some(button);
+                }
+                _ => *self.pressed = None,
+            }
+        }
+        released
+    }
+}
@@ -0,0 +1,251 @@
+use crate::layers::GameLayer;
+use crate::player_movement::{BufferedJump, JumpImpulse, Player, perform_buffered_jump};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_tnua::builtins::TnuaBuiltinWallSlide;
+use bevy_tnua::prelude::{TnuaAction, TnuaBuiltinJump, TnuaController};
+
+/// The radius of the sphere cast sideways to look for walls.
+const WALL_PROBE_RADIUS: Scalar = 0.3;
+/// How far the wall probe is cast to either side of the character.
+const WALL_REACH: Scalar = 0.4;
+/// Surfaces whose normal points further up or down than this aren't walls.
+const MAX_WALL_NORMAL_Y: Scalar = 0.3;
+/// The horizontal speed a character needs to start and keep running along a wall.
+const WALL_RUN_MIN_SPEED: Scalar = 4.0;
+const WALL_RUN_MAX_SPEED: Scalar = 25.0;
+/// How fast a character is allowed to fall while running along a wall.
+const WALL_RUN_MAX_FALL_SPEED: Scalar = 1.0;
+/// The speed a wall jump pushes the character away from the wall with.
+const WALL_JUMP_PUSH_SPEED: Scalar = 6.0;
+
+/// The wall a character is currently running along, if any.
+#[derive(Component, Default)]
+pub struct WallRun(Option<WallContact>);
+
+#[derive(Clone, Copy)]
+pub struct WallContact {
+    entity: Entity,
+    point: Vector,
+    normal: Dir3,
+}
+
+pub struct WallRunPlugin;
+
+impl Plugin for WallRunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (detect_wall, wall_jump, run_along_wall)
+                .chain()
+                .before(perform_buffered_jump),
+        );
+    }
+}
+
+/// Looks for a wall to either side of an airborne character that is moving fast enough.
+fn detect_wall(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &LinearVelocity,
+            &ShapeHits,
+            &mut WallRun,
+        ),
+        With<Player>,
+    >,
+) {
+    let probe = Collider::sphere(WALL_PROBE_RADIUS);
+
+    for (entity, transform, velocity, ground_hits, mut wall_run) in &mut query {
+        let speed = velocity.0.with_y(0.0).length();
+        if !ground_hits.is_empty() || speed < WALL_RUN_MIN_SPEED {
+            wall_run.0 = None;
+            continue;
+        }
+
+        let Ok(right) = Dir3::new(transform.right().as_vec3().with_y(0.0)) else {
+            wall_run.0 = None;
+            continue;
+        };
+        let filter =
+            SpatialQueryFilter::from_mask(GameLayer::solid()).with_excluded_entities([entity]);
+        let config = ShapeCastConfig::from_max_distance(WALL_REACH);
+
+        wall_run.0 = [right, -right].into_iter().find_map(|side| {
+            let hit = spatial_query.cast_shape(
+                &probe,
+                transform.translation,
+                Quat::IDENTITY,
+                side,
+                &config,
+                &filter,
+            )?;
+            let normal = Dir3::new(hit.normal1).ok()?;
+
+            (normal.y.abs() <= MAX_WALL_NORMAL_Y).then_some(WallContact {
+                entity: hit.entity,
+                point: hit.point1,
+                normal,
+            })
+        });
+    }
+}
+
+/// Spends a buffered jump on jumping away from the wall, before the regular jump handling
+/// gets to use it as an air jump. Lets go of the wall, so the wall slide doesn't replace the
+/// jump in the same frame.
+///
+/// The push away is set on the velocity directly, since Tnua only applies a jump's
+/// horizontal displacement to jumps taking off from the ground.
+fn wall_jump(
+    mut query: Query<
+        (
+            &mut WallRun,
+            &JumpImpulse,
+            &mut BufferedJump,
+            &mut LinearVelocity,
+            &mut TnuaController,
+        ),
+        With<Player>,
+    >,
+) {
+    for (mut wall_run, jump_impulse, mut buffered_jump, mut velocity, mut controller) in &mut query
+    {
+        let Some(contact) = wall_run.0 else {
+            continue;
+        };
+        if buffered_jump.0.take().is_none() {
+            continue;
+        }
+        wall_run.0 = None;
+
+        let away = velocity.dot(*contact.normal);
+        if away < WALL_JUMP_PUSH_SPEED {
+            velocity.0 += contact.normal * (WALL_JUMP_PUSH_SPEED - away);
+        }
+        controller.action(TnuaBuiltinJump {
+            height: jump_impulse.0,
+            allow_in_air: true,
+            ..default()
+        });
+    }
+}
+
+/// Keeps feeding Tnua's wall slide action while there is a wall, which limits the fall speed
+/// without cancelling the character's momentum along the wall. A wall jump still going on
+/// isn't cut short by it, since Tnua only keeps one action at a time.
+fn run_along_wall(mut query: Query<(&WallRun, &mut TnuaController), With<Player>>) {
+    for (wall_run, mut controller) in &mut query {
+        let Some(contact) = wall_run.0 else {
+            continue;
+        };
+        if controller.action_name() == Some(TnuaBuiltinJump::NAME) {
+            continue;
+        }
+
+        controller.action(TnuaBuiltinWallSlide {
+            wall_entity: Some(contact.entity),
+            contact_point_with_wall: contact.point,
+            normal: contact.normal,
+            max_fall_speed: WALL_RUN_MAX_FALL_SPEED,
+            max_sideways_speed: WALL_RUN_MAX_SPEED,
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_tnua::prelude::{TnuaBuiltinWalk, TnuaControllerPlugin};
+    use bevy_tnua_avian3d::TnuaAvian3dPlugin;
+    use std::time::Duration;
+
+    /// Keeps a walk basis fed, since Tnua doesn't perform actions without one.
+    fn feed_basis(mut query: Query<&mut TnuaController>) {
+        for mut controller in &mut query {
+            controller.basis(TnuaBuiltinWalk {
+                float_height: 1.0,
+                ..default()
+            });
+        }
+    }
+
+    #[test]
+    fn wall_jump_pushes_up_and_away_from_the_wall() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+            PhysicsPlugins::default(),
+            TnuaControllerPlugin::new(PhysicsSchedule),
+            TnuaAvian3dPlugin::new(PhysicsSchedule),
+        ))
+        .init_resource::<Assets<Mesh>>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 60.0,
+        )))
+        .add_systems(
+            Update,
+            (feed_basis, detect_wall, wall_jump, run_along_wall).chain(),
+        );
+        // Avian registers some of its resources when plugins finish, which `update` skips
+        app.finish();
+        app.cleanup();
+
+        // A wall to the right, with its face half a meter from the character
+        app.world_mut().spawn((
+            RigidBody::Static,
+            Collider::cuboid(1.0, 20.0, 20.0),
+            Transform::from_xyz(1.0, 0.0, 0.0),
+        ));
+        // Airborne high above anything to stand on, running along the wall
+        let player = app
+            .world_mut()
+            .spawn((
+                Player,
+                RigidBody::Dynamic,
+                Collider::capsule(0.4, 1.0),
+                LockedAxes::ROTATION_LOCKED,
+                LinearVelocity(Vector::new(0.0, 0.0, -8.0)),
+                Transform::default(),
+                ShapeCaster::new(
+                    Collider::sphere(0.39),
+                    Vector::ZERO,
+                    Quat::IDENTITY,
+                    Dir3::NEG_Y,
+                )
+                .with_max_distance(0.2),
+                TnuaController::default(),
+                WallRun::default(),
+                JumpImpulse(4.0),
+                BufferedJump(None),
+            ))
+            .id();
+
+        app.update();
+        app.update();
+        assert!(
+            app.world().get::<WallRun>(player).unwrap().0.is_some(),
+            "the wall wasn't found"
+        );
+
+        app.world_mut().get_mut::<BufferedJump>(player).unwrap().0 = Some(0.0);
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let velocity = app.world().get::<LinearVelocity>(player).unwrap().0;
+        assert!(velocity.y > 0.0, "no upward velocity: {velocity}");
+        assert!(
+            velocity.x < 0.0,
+            "no velocity away from the wall: {velocity}"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use crate::layers::GameLayer;
+use crate::player_movement::{
+    BufferedJump, MovementAcceleration, Player, PlayerCrouch, PlayerJump, PlayerMove,
+    perform_buffered_jump,
+};
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use bevy_tnua::TnuaToggle;
+
+/// Swimming speed relative to the character's walking speed.
+const SWIM_SPEED_FACTOR: Scalar = 0.5;
+const SWIM_VERTICAL_SPEED: Scalar = 3.0;
+/// How far below the surface a floating character's center rests.
+const SWIM_FLOAT_DEPTH: Scalar = 0.3;
+/// How strongly a floating character is pulled toward its resting depth.
+const BUOYANCY: Scalar = 4.0;
+/// How quickly swimming velocity approaches the desired velocity, acting as water drag.
+const SWIM_RESPONSIVENESS: Scalar = 4.0;
+/// The upward push on a fully submerged body, relative to its weight. Above one, bodies float
+/// with part of them above the surface.
+const BODY_BUOYANCY: Scalar = 1.5;
+/// Exponential decay rate of a fully submerged body's velocity and spin, per second.
+const BODY_WATER_DRAG: Scalar = 2.0;
+
+/// A region of water. The top of its collider is the water surface.
+#[derive(Component)]
+pub struct WaterVolume;
+
+#[derive(Bundle)]
+pub struct WaterVolumeBundle {
+    water_volume: WaterVolume,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_layers: CollisionLayers,
+    pub mesh3d: Mesh3d,
+    pub mesh_material3d: MeshMaterial3d<StandardMaterial>,
+    pub transform: Transform,
+}
+
+impl WaterVolumeBundle {
+    pub fn new(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        size: Vec3,
+        transform: Transform,
+    ) -> Self {
+        Self {
+            water_volume: WaterVolume,
+            rigid_body: RigidBody::Static,
+            collider: Collider::cuboid(size.x, size.y, size.z),
+            sensor: Sensor,
+            collision_layers: CollisionLayers::new(GameLayer::Water, LayerMask::ALL),
+            mesh3d: Mesh3d(meshes.add(Cuboid::from_size(size))),
+            mesh_material3d: MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.1, 0.3, 0.8, 0.4),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })),
+            transform,
+        }
+    }
+
+    /// A glossy plane at the top of a water volume of the given size, to be spawned as its child.
+    /// Makes the surface visible from above and below, where the volume itself is barely seen.
+    pub fn surface(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        size: Vec3,
+    ) -> impl Bundle + use<> {
+        (
+            Mesh3d(meshes.add(Plane3d::default().mesh().size(size.x, size.z))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.2, 0.5, 0.9, 0.6),
+                perceptual_roughness: 0.05,
+                reflectance: 0.8,
+                alpha_mode: AlphaMode::Blend,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            })),
+            Transform::from_xyz(0.0, size.y / 2.0, 0.0),
+        )
+    }
+}
+
+/// The medium a character is currently moving through.
+#[derive(Component, Default)]
+pub enum Medium {
+    #[default]
+    Air,
+    /// Swimming in water whose surface is at `surface`. The character's gravity is switched
+    /// off while swimming and `gravity_scale` holds the value to restore afterwards.
+    Water {
+        surface: Scalar,
+        gravity_scale: Scalar,
+    },
+}
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_medium, swim).chain().before(perform_buffered_jump),
+        )
+        .add_systems(FixedUpdate, float_bodies);
+    }
+}
+
+/// Switches characters between walking and swimming as they enter and leave water, handing
+/// control from Tnua to [`swim`] and back.
+fn update_medium(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    waters: Query<&ColliderAabb, With<WaterVolume>>,
+    mut query: Query<(Entity, &Transform, &mut Medium, &mut GravityScale), With<Player>>,
+) {
+    let filter = SpatialQueryFilter::from_mask(GameLayer::Water);
+
+    for (entity, transform, mut medium, mut gravity_scale) in &mut query {
+        let surface = spatial_query
+            .point_intersections(transform.translation, &filter)
+            .into_iter()
+            .filter_map(|water| waters.get(water).ok())
+            .map(|aabb| aabb.max.y)
+            .reduce(Scalar::max);
+
+        match (&mut *medium, surface) {
+            (Medium::Air, Some(surface)) => {
+                *medium = Medium::Water {
+                    surface,
+                    gravity_scale: gravity_scale.0,
+                };
+                gravity_scale.0 = 0.0;
+                commands.entity(entity).insert(TnuaToggle::Disabled);
+            }
+            (
+                Medium::Water {
+                    surface: current, ..
+                },
+                Some(surface),
+            ) => *current = surface,
+            (
+                Medium::Water {
+                    gravity_scale: restore,
+                    ..
+                },
+                None,
+            ) => {
+                gravity_scale.0 = *restore;
+                *medium = Medium::Air;
+                commands.entity(entity).insert(TnuaToggle::Enabled);
+            }
+            (Medium::Air, None) => {}
+        }
+    }
+}
+
+/// Moves swimming characters: slower horizontal movement, jump and crouch to swim up and
+/// down, and otherwise floating back up to just below the surface.
+#[allow(clippy::type_complexity)]
+fn swim(
+    time: Res<Time>,
+    move_action: Query<&Action<PlayerMove>>,
+    jump_action: Query<&Action<PlayerJump>>,
+    crouch_action: Query<&Action<PlayerCrouch>>,
+    mut query: Query<
+        (
+            &Transform,
+            &Actions<Player>,
+            &Medium,
+            &MovementAcceleration,
+            &mut LinearVelocity,
+            &mut BufferedJump,
+        ),
+        With<Player>,
+    >,
+) {
+    for (transform, actions, medium, acceleration, mut velocity, mut buffered_jump) in &mut query {
+        let Medium::Water { surface, .. } = *medium else {
+            continue;
+        };
+        let movement = move_action
+            .iter_many(actions)
+            .next()
+            .map_or(Vec2::ZERO, |action| **action);
+        let swim_up = jump_action.iter_many(actions).any(|action| **action);
+        let swim_down = crouch_action.iter_many(actions).any(|action| **action);
+
+        // Jump presses are for swimming up here, not for jumping once back on land
+        buffered_jump.0 = None;
+
+        let forward = transform
+            .forward()
+            .as_vec3()
+            .with_y(0.0)
+            .normalize_or_zero();
+        let right = transform.right().as_vec3().with_y(0.0).normalize_or_zero();
+        let mut desired_velocity =
+            (movement.y * forward + movement.x * right) * acceleration.0 * SWIM_SPEED_FACTOR;
+
+        desired_velocity.y = if swim_up {
+            SWIM_VERTICAL_SPEED
+        } else if swim_down {
+            -SWIM_VERTICAL_SPEED
+        } else {
+            let float_height = surface - SWIM_FLOAT_DEPTH;
+            ((float_height - transform.translation.y) * BUOYANCY)
+                .clamp(-SWIM_VERTICAL_SPEED, SWIM_VERTICAL_SPEED)
+        };
+
+        let blend = 1.0 - (-SWIM_RESPONSIVENESS * time.delta_secs()).exp();
+        velocity.0 = velocity.0.lerp(desired_velocity, blend);
+    }
+}
+
+/// Pushes dynamic bodies in water up and slows them down, both by how much of them is below the
+/// surface. Characters swim instead.
+#[allow(clippy::type_complexity)]
+fn float_bodies(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    spatial_query: SpatialQuery,
+    waters: Query<&ColliderAabb, With<WaterVolume>>,
+    mut bodies: Query<
+        (
+            &RigidBody,
+            &ColliderAabb,
+            Option<&GravityScale>,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+        ),
+        (Without<Player>, Without<RigidBodyDisabled>),
+    >,
+) {
+    for (rigid_body, aabb, gravity_scale, mut velocity, mut angular_velocity) in &mut bodies {
+        if !rigid_body.is_dynamic() {
+            continue;
+        }
+
+        let Some(surface) = spatial_query
+            .aabb_intersections_with_aabb(*aabb)
+            .into_iter()
+            .filter_map(|water| waters.get(water).ok())
+            .map(|water| water.max.y)
+            .reduce(Scalar::max)
+        else {
+            continue;
+        };
+
+        let height = (aabb.max.y - aabb.min.y).max(Scalar::EPSILON);
+        let submerged = ((surface - aabb.min.y) / height).clamp(0.0, 1.0);
+        let gravity_scale = gravity_scale.map_or(1.0, |scale| scale.0);
+
+        velocity.0 -= gravity.0 * gravity_scale * BODY_BUOYANCY * submerged * time.delta_secs();
+        let drag = (-BODY_WATER_DRAG * submerged * time.delta_secs()).exp();
+        velocity.0 *= drag;
+        angular_velocity.0 *= drag;
+    }
+}
@@ -0,0 +1,220 @@
+use crate::camera::CameraRig;
+use crate::game_assets::GameAssets;
+use avian3d::prelude::*;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+/// How many raindrops or snowflakes fall around the camera at once. Each is an entity whose
+/// transform is moved every frame, so this is kept low enough for that to stay cheap.
+const PARTICLE_COUNT: usize = 2000;
+/// How far out from the camera particles fall, horizontally.
+const PARTICLE_RANGE: f32 = 15.0;
+/// How far above and below the camera particles fall from and to.
+const PARTICLE_HEIGHT: f32 = 10.0;
+const RAIN_FALL_SPEED: f32 = 14.0;
+const SNOW_FALL_SPEED: f32 = 1.5;
+/// How far snowflakes sway from side to side as they fall.
+const SNOW_SWAY: f32 = 0.4;
+/// Avian's default friction, used in clear weather.
+const DRY_FRICTION: f32 = 0.5;
+const WET_FRICTION: f32 = 0.25;
+const ICY_FRICTION: f32 = 0.05;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+impl WeatherKind {
+    /// How much of the daylight gets through the clouds.
+    pub fn daylight(self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 0.4,
+            Self::Snow => 0.7,
+        }
+    }
+
+    fn friction(self) -> f32 {
+        match self {
+            Self::Clear => DRY_FRICTION,
+            Self::Rain => WET_FRICTION,
+            Self::Snow => ICY_FRICTION,
+        }
+    }
+
+    fn fog(self) -> Option<DistanceFog> {
+        let (color, start, end) = match self {
+            Self::Clear => return None,
+            Self::Rain => (Color::srgb(0.45, 0.5, 0.55), 10.0, 80.0),
+            Self::Snow => (Color::srgb(0.85, 0.88, 0.92), 5.0, 50.0),
+        };
+        Some(DistanceFog {
+            color,
+            falloff: FogFalloff::Linear { start, end },
+            ..default()
+        })
+    }
+}
+
+/// The current weather, which can be changed at any time.
+#[derive(Resource)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// Whether rain makes surfaces slippery and snow makes them icy. Only affects colliders
+    /// without a friction of their own.
+    pub affects_friction: bool,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            affects_friction: true,
+        }
+    }
+}
+
+/// Switches to the next kind of weather.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct CycleWeather;
+
+/// A raindrop or snowflake, with a random offset for when it sways.
+#[derive(Component)]
+struct Precipitation(f32);
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .add_systems(Startup, spawn_precipitation)
+            .add_systems(
+                Update,
+                (
+                    apply_weather.run_if(resource_changed::<Weather>),
+                    update_precipitation,
+                )
+                    .chain(),
+            )
+            .add_observer(cycle_weather);
+    }
+}
+
+fn spawn_precipitation(mut commands: Commands, assets: Res<GameAssets>) {
+    let mesh = assets.rain_mesh.clone();
+    let material = assets.rain_material.clone();
+    commands.spawn_batch((0..PARTICLE_COUNT).map(move |_| {
+        (
+            Precipitation(rand::random_range(0.0..std::f32::consts::TAU)),
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::default(),
+            Visibility::Hidden,
+            NotShadowCaster,
+        )
+    }));
+}
+
+fn cycle_weather(_trigger: Trigger<Started<CycleWeather>>, mut weather: ResMut<Weather>) {
+    weather.kind = match weather.kind {
+        WeatherKind::Clear => WeatherKind::Rain,
+        WeatherKind::Rain => WeatherKind::Snow,
+        WeatherKind::Snow => WeatherKind::Clear,
+    };
+    info!("Weather is now {:?}", weather.kind);
+}
+
+/// Swaps the particles, fog and surface friction over to the current weather.
+fn apply_weather(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    assets: Res<GameAssets>,
+    mut default_friction: ResMut<DefaultFriction>,
+    camera: Single<(Entity, &GlobalTransform), With<CameraRig>>,
+    mut particles: Query<
+        (
+            &mut Mesh3d,
+            &mut MeshMaterial3d<StandardMaterial>,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<Precipitation>,
+    >,
+) {
+    let (camera, camera_transform) = *camera;
+
+    let looks = match weather.kind {
+        WeatherKind::Clear => None,
+        WeatherKind::Rain => Some((&assets.rain_mesh, &assets.rain_material)),
+        WeatherKind::Snow => Some((&assets.snow_mesh, &assets.snow_material)),
+    };
+    for (mut mesh3d, mut material3d, mut transform, mut visibility) in &mut particles {
+        let Some((mesh, material)) = looks else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        mesh3d.0 = mesh.clone();
+        material3d.0 = material.clone();
+        // Spread out over the whole fall height, so it doesn't start as one sheet
+        transform.translation = camera_transform.translation()
+            + random_offset(rand::random_range(-PARTICLE_HEIGHT..PARTICLE_HEIGHT));
+        *visibility = Visibility::Inherited;
+    }
+
+    match weather.kind.fog() {
+        Some(fog) => commands.entity(camera).insert(fog),
+        None => commands.entity(camera).remove::<DistanceFog>(),
+    };
+
+    let friction = if weather.affects_friction {
+        weather.kind.friction()
+    } else {
+        DRY_FRICTION
+    };
+    default_friction.0 = Friction::new(friction);
+}
+
+/// A random point around the camera at the given height relative to it.
+fn random_offset(height: f32) -> Vec3 {
+    Vec3::new(
+        rand::random_range(-PARTICLE_RANGE..PARTICLE_RANGE),
+        height,
+        rand::random_range(-PARTICLE_RANGE..PARTICLE_RANGE),
+    )
+}
+
+/// Lets particles fall, and moves those that fell below or were left behind by the camera back
+/// up above it.
+fn update_precipitation(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    camera: Single<&GlobalTransform, With<CameraRig>>,
+    mut particles: Query<(&Precipitation, &mut Transform)>,
+) {
+    let (fall_speed, sway) = match weather.kind {
+        WeatherKind::Clear => return,
+        WeatherKind::Rain => (RAIN_FALL_SPEED, 0.0),
+        WeatherKind::Snow => (SNOW_FALL_SPEED, SNOW_SWAY),
+    };
+    let camera = camera.translation();
+
+    for (precipitation, mut transform) in &mut particles {
+        transform.translation.y -= fall_speed * time.delta_secs();
+        let phase = time.elapsed_secs() + precipitation.0;
+        transform.translation.x += sway * phase.cos() * time.delta_secs();
+
+        let offset = transform.translation - camera;
+        if offset.y < -PARTICLE_HEIGHT
+            || offset.x.abs() > PARTICLE_RANGE
+            || offset.z.abs() > PARTICLE_RANGE
+        {
+            transform.translation = camera + random_offset(PARTICLE_HEIGHT);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::layers::GameLayer;
+use crate::trigger::{TriggerEnter, TriggerExit, TriggerVolume};
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+/// How far a pressure plate's slab sinks while it's pressed.
+const PLATE_TRAVEL: f32 = 0.04;
+const PLATE_THICKNESS: f32 = 0.1;
+
+/// Sent to the entities a [`PressurePlate`] is wired to whenever it's pressed or released.
+/// Doors open while it's on, and waiting platforms set off when it turns on.
+#[derive(Event)]
+pub struct Signal {
+    pub on: bool,
+}
+
+/// The entities a signal source sends its [`Signal`]s to.
+#[derive(Component, Default)]
+pub struct SignalTargets(pub Vec<Entity>);
+
+/// A trigger volume that's pressed while a character or dynamic body is in it, like a box left
+/// on top. Drawn as a slab at its bottom that sinks in while pressed.
+#[derive(Component, Default)]
+#[require(TriggerVolume, SignalTargets)]
+pub struct PressurePlate {
+    pressed: bool,
+    /// The dynamic bodies on the plate, as they trigger [`TriggerEnter`] and [`TriggerExit`].
+    on_top: Vec<Entity>,
+}
+
+/// The visible slab of a [`PressurePlate`], spawned as its child.
+#[derive(Component)]
+struct PlateSlab {
+    /// The slab's height in the plate while released.
+    height: f32,
+}
+
+#[derive(Bundle)]
+pub struct PressurePlateBundle {
+    pressure_plate: PressurePlate,
+    pub rigid_body: RigidBody,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_layers: CollisionLayers,
+    pub transform: Transform,
+    pub visibility: Visibility,
+}
+
+impl PressurePlateBundle {
+    pub fn new(size: Vec3, transform: Transform) -> Self {
+        Self {
+            pressure_plate: PressurePlate::default(),
+            rigid_body: RigidBody::Static,
+            collider: Collider::cuboid(size.x, size.y, size.z),
+            sensor: Sensor,
+            collision_layers: CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+            transform,
+            visibility: Visibility::default(),
+        }
+    }
+
+    /// The slab at the bottom of a pressure plate of the given size, to be spawned as its child.
+    pub fn slab(
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        size: Vec3,
+    ) -> impl Bundle + use<> {
+        let height = (PLATE_THICKNESS - size.y) / 2.0;
+        (
+            PlateSlab { height },
+            Mesh3d(meshes.add(Cuboid::new(size.x, PLATE_THICKNESS, size.z))),
+            MeshMaterial3d(materials.add(Color::srgb(0.7, 0.15, 0.15))),
+            Transform::from_xyz(0.0, height, 0.0),
+        )
+    }
+}
+
+pub struct WiringPlugin;
+
+impl Plugin for WiringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, press_plates)
+            .add_observer(step_on_plate)
+            .add_observer(step_off_plate);
+    }
+}
+
+fn step_on_plate(
+    trigger: Trigger<TriggerEnter>,
+    bodies: Query<&RigidBody>,
+    mut plates: Query<&mut PressurePlate>,
+) {
+    // Characters are dynamic bodies too, so they press plates just like props
+    if !bodies.get(trigger.entity).is_ok_and(RigidBody::is_dynamic) {
+        return;
+    }
+    if let Ok(mut plate) = plates.get_mut(trigger.target()) {
+        plate.on_top.push(trigger.entity);
+    }
+}
+
+fn step_off_plate(trigger: Trigger<TriggerExit>, mut plates: Query<&mut PressurePlate>) {
+    if let Ok(mut plate) = plates.get_mut(trigger.target()) {
+        // Only the bodies stepping on it are in there, so leaving alone doesn't mark it changed
+        if plate.on_top.contains(&trigger.entity) {
+            plate.on_top.retain(|&entity| entity != trigger.entity);
+        }
+    }
+}
+
+/// Presses and releases plates by what's on them, signalling their targets when that changes.
+fn press_plates(
+    mut commands: Commands,
+    mut plates: Query<(&mut PressurePlate, &SignalTargets, &Children), Changed<PressurePlate>>,
+    mut slabs: Query<(&PlateSlab, &mut Transform)>,
+) {
+    for (mut plate, targets, children) in &mut plates {
+        let pressed = !plate.on_top.is_empty();
+        if pressed == plate.pressed {
+            continue;
+        }
+        plate.pressed = pressed;
+
+        // Triggering no targets at all would reach every signal observer
+        if !targets.0.is_empty() {
+            commands.trigger_targets(Signal { on: pressed }, targets.0.clone());
+        }
+
+        let mut slabs = slabs.iter_many_mut(children);
+        while let Some((slab, mut transform)) = slabs.fetch_next() {
+            transform.translation.y = slab.height - if pressed { PLATE_TRAVEL } else { 0.0 };
+        }
+    }
+}